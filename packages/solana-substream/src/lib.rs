@@ -3,13 +3,50 @@ mod pb;
 
 use anchor_lang::AnchorDeserialize;
 use anchor_lang::Discriminator;
-use base64::prelude::*;
+use anchor_lang::event::EVENT_IX_TAG_LE;
 use pb::substreams::v1::program::Data;
+use pb::substreams::v1::program::AccountMigratedEvent;
+use pb::substreams::v1::program::BalanceCreditedEvent;
+use pb::substreams::v1::program::BalanceDepositedEvent;
+use pb::substreams::v1::program::BalanceWithdrawnEvent;
+use pb::substreams::v1::program::BetCommittedEvent;
 use pb::substreams::v1::program::BetPlacedEvent;
+use pb::substreams::v1::program::BetRelayedEvent;
+use pb::substreams::v1::program::BetRevealedEvent;
+use pb::substreams::v1::program::BetSettledEvent;
+use pb::substreams::v1::program::BetsCloseAtExtendedEvent;
+use pb::substreams::v1::program::BondReturnedEvent;
+use pb::substreams::v1::program::ClaimWindowSetEvent;
+use pb::substreams::v1::program::EmergencyWithdrawnEvent;
+use pb::substreams::v1::program::ExpiredClaimsSweptEvent;
+use pb::substreams::v1::program::MarketCreatedEvent;
+use pb::substreams::v1::program::MarketSurplusWithdrawnEvent;
+use pb::substreams::v1::program::MatchClaimedEvent;
+use pb::substreams::v1::program::MatchEmergencyWithdrawnEvent;
+use pb::substreams::v1::program::MintAddedEvent;
+use pb::substreams::v1::program::MintUpdatedEvent;
+use pb::substreams::v1::program::OfferAcceptedEvent;
+use pb::substreams::v1::program::OfferCancelledEvent;
+use pb::substreams::v1::program::OfferCreatedEvent;
 use pb::substreams::v1::program::PayoutClaimedEvent;
+use pb::substreams::v1::program::PoolBondSetEvent;
 use pb::substreams::v1::program::PoolClosedEvent;
 use pb::substreams::v1::program::PoolCreatedEvent;
+use pb::substreams::v1::program::PoolFlaggedInvalidEvent;
 use pb::substreams::v1::program::PoolImageSetEvent;
+use pb::substreams::v1::program::PoolProposedEvent;
+use pb::substreams::v1::program::PoolSettledEvent;
+use pb::substreams::v1::program::PoolUpdatedEvent;
+use pb::substreams::v1::program::ProgramClosedEvent;
+use pb::substreams::v1::program::ProgramInitializedEvent;
+use pb::substreams::v1::program::SessionCreatedEvent;
+use pb::substreams::v1::program::SessionRevokedEvent;
+use pb::substreams::v1::program::SharesBoughtEvent;
+use pb::substreams::v1::program::SharesRedeemedEvent;
+use pb::substreams::v1::program::SolvencyReportEvent;
+use pb::substreams::v1::program::TreasurySetEvent;
+use pb::substreams::v1::program::UnclaimedSweptEvent;
+use pb::substreams::v1::program::VaultDrainedEvent;
 use pb::substreams::v1::program::ClaimPayoutInstruction;
 use pb::substreams::v1::program::CreatePoolInstruction;
 use pb::substreams::v1::program::GradeBetInstruction;
@@ -17,16 +54,13 @@ use pb::substreams::v1::program::InitializeInstruction;
 use pb::substreams::v1::program::PlaceBetInstruction;
 use pb::substreams::v1::program::SetImageInstruction;
 
-use pb::substreams::v1::program::TokenTypeEnum;
-
-use sologger_log_context::programs_selector::ProgramsSelector;
-use sologger_log_context::sologger_log_context::LogContext;
+use substreams::errors::Error;
 use substreams_solana::pb::sf::solana::r#type::v1::Block;
 
 const PROGRAM_ID: &str = "3G6TYRGE6JX4BZPdeix4L38VtbrAUMWyK1yJ5DQoRc7c";
 
 #[substreams::handlers::map]
-fn map_program_data(blk: Block) -> Data {
+fn map_program_data(blk: Block) -> Result<Data, Error> {
     let mut bet_placed_event_list: Vec<BetPlacedEvent> = Vec::new();
     let mut payout_claimed_event_list: Vec<PayoutClaimedEvent> = Vec::new();
     let mut pool_closed_event_list: Vec<PoolClosedEvent> = Vec::new();
@@ -38,120 +72,757 @@ fn map_program_data(blk: Block) -> Data {
     let mut initialize_instruction_list: Vec<InitializeInstruction> = Vec::new();
     let mut place_bet_instruction_list: Vec<PlaceBetInstruction> = Vec::new();
     let mut set_image_instruction_list: Vec<SetImageInstruction> = Vec::new();
+    let mut mint_added_event_list: Vec<MintAddedEvent> = Vec::new();
+    let mut mint_updated_event_list: Vec<MintUpdatedEvent> = Vec::new();
+    let mut account_migrated_event_list: Vec<AccountMigratedEvent> = Vec::new();
+    let mut treasury_set_event_list: Vec<TreasurySetEvent> = Vec::new();
+    let mut vault_drained_event_list: Vec<VaultDrainedEvent> = Vec::new();
+    let mut program_closed_event_list: Vec<ProgramClosedEvent> = Vec::new();
+    let mut bet_committed_event_list: Vec<BetCommittedEvent> = Vec::new();
+    let mut bet_revealed_event_list: Vec<BetRevealedEvent> = Vec::new();
+    let mut market_created_event_list: Vec<MarketCreatedEvent> = Vec::new();
+    let mut shares_bought_event_list: Vec<SharesBoughtEvent> = Vec::new();
+    let mut shares_redeemed_event_list: Vec<SharesRedeemedEvent> = Vec::new();
+    let mut market_surplus_withdrawn_event_list: Vec<MarketSurplusWithdrawnEvent> = Vec::new();
+    let mut offer_created_event_list: Vec<OfferCreatedEvent> = Vec::new();
+    let mut offer_accepted_event_list: Vec<OfferAcceptedEvent> = Vec::new();
+    let mut offer_cancelled_event_list: Vec<OfferCancelledEvent> = Vec::new();
+    let mut match_claimed_event_list: Vec<MatchClaimedEvent> = Vec::new();
+    let mut pool_updated_event_list: Vec<PoolUpdatedEvent> = Vec::new();
+    let mut bets_close_at_extended_event_list: Vec<BetsCloseAtExtendedEvent> = Vec::new();
+    let mut pool_bond_set_event_list: Vec<PoolBondSetEvent> = Vec::new();
+    let mut pool_proposed_event_list: Vec<PoolProposedEvent> = Vec::new();
+    let mut pool_flagged_invalid_event_list: Vec<PoolFlaggedInvalidEvent> = Vec::new();
+    let mut bond_returned_event_list: Vec<BondReturnedEvent> = Vec::new();
+    let mut bet_relayed_event_list: Vec<BetRelayedEvent> = Vec::new();
+    let mut session_created_event_list: Vec<SessionCreatedEvent> = Vec::new();
+    let mut session_revoked_event_list: Vec<SessionRevokedEvent> = Vec::new();
+    let mut balance_deposited_event_list: Vec<BalanceDepositedEvent> = Vec::new();
+    let mut balance_withdrawn_event_list: Vec<BalanceWithdrawnEvent> = Vec::new();
+    let mut balance_credited_event_list: Vec<BalanceCreditedEvent> = Vec::new();
+    let mut program_initialized_event_list: Vec<ProgramInitializedEvent> = Vec::new();
+    let mut bet_settled_event_list: Vec<BetSettledEvent> = Vec::new();
+    let mut solvency_report_event_list: Vec<SolvencyReportEvent> = Vec::new();
+    let mut claim_window_set_event_list: Vec<ClaimWindowSetEvent> = Vec::new();
+    let mut unclaimed_swept_event_list: Vec<UnclaimedSweptEvent> = Vec::new();
+    let mut emergency_withdrawn_event_list: Vec<EmergencyWithdrawnEvent> = Vec::new();
+    let mut pool_settled_event_list: Vec<PoolSettledEvent> = Vec::new();
+    let mut expired_claims_swept_event_list: Vec<ExpiredClaimsSweptEvent> = Vec::new();
+    let mut match_emergency_withdrawn_event_list: Vec<MatchEmergencyWithdrawnEvent> =
+        Vec::new();
 
-    blk.transactions().for_each(|transaction| {
+    for transaction in blk.transactions() {
 
         // ------------- EVENTS -------------
-        let meta_wrapped = &transaction.meta;
-        let meta = meta_wrapped.as_ref().unwrap();
-        let programs_selector: ProgramsSelector = ProgramsSelector::new(&["*".to_string()]);
-        let log_contexts = LogContext::parse_logs_basic(&meta.log_messages, &programs_selector);
-
-        log_contexts
-            .iter()
-            .filter(|context| context.program_id == PROGRAM_ID)
-            .for_each(|context| {
-                context.data_logs.iter().for_each(|data| {
-                    if let Ok(decoded) = BASE64_STANDARD.decode(data) {
-                        let slice_u8: &mut &[u8] = &mut &decoded[..];
-                        let slice_discriminator: [u8; 8] =
-                            slice_u8[0..8].try_into().expect("error");
-                        let static_discriminator_slice: &'static [u8] = Box::leak(Box::new(slice_discriminator));
+        // Events are emitted through a self-CPI, so they are read from the program's
+        // inner instruction data rather than from logs, which can be truncated
+        for inst in transaction
+            .walk_instructions()
+            .filter(|inst| inst.program_id().to_string() == PROGRAM_ID)
+            .filter(|inst| inst.data().starts_with(EVENT_IX_TAG_LE))
+        {
+            let slice_u8: &[u8] = &inst.data()[EVENT_IX_TAG_LE.len()..];
+            let discriminator = slice_u8.get(..8).ok_or_else(|| {
+                Error::msg(format!(
+                    "event in transaction {} has no discriminator",
+                    transaction.id()
+                ))
+            })?;
 
-                        match static_discriminator_slice {
-                            idl::idl::program::events::BetPlaced::DISCRIMINATOR => {
-                                if let Ok(event) =
-                                    idl::idl::program::events::BetPlaced::deserialize(
-                                        &mut &slice_u8[8..],
-                                    )
-                                {
-                                    bet_placed_event_list.push(BetPlacedEvent {
-                                        trx_hash: transaction.id(),
-                                        bet_id: event.bet_id,
-                                        pool_id: event.pool_id,
-                                        user: event.user.to_string(),
-                                        option_index: event.option_index,
-                                        amount: event.amount,
-                                        token_type: map_enum_token_type(event.token_type),
-                                        created_at: event.created_at,
-                                    });
-                                }
-                            }
-                            idl::idl::program::events::PayoutClaimed::DISCRIMINATOR => {
-                                if let Ok(event) =
-                                    idl::idl::program::events::PayoutClaimed::deserialize(
-                                        &mut &slice_u8[8..],
-                                    )
-                                {
-                                    payout_claimed_event_list.push(PayoutClaimedEvent {
-                                        trx_hash: transaction.id(),
-                                        bet_id: event.bet_id,
-                                        pool_id: event.pool_id,
-                                        user: event.user.to_string(),
-                                        amount: event.amount,
-                                        token_type: map_enum_token_type(event.token_type),
-                                    });
-                                }
-                            }
-                            idl::idl::program::events::PoolClosed::DISCRIMINATOR => {
-                                if let Ok(event) =
-                                    idl::idl::program::events::PoolClosed::deserialize(
-                                        &mut &slice_u8[8..],
-                                    )
-                                {
-                                    pool_closed_event_list.push(PoolClosedEvent {
-                                        trx_hash: transaction.id(),
-                                        pool_id: event.pool_id,
-                                        selected_option: event.selected_option,
-                                        decision_time: event.decision_time,
-                                    });
-                                }
-                            }
-                            idl::idl::program::events::PoolCreated::DISCRIMINATOR => {
-                                if let Ok(event) =
-                                    idl::idl::program::events::PoolCreated::deserialize(
-                                        &mut &slice_u8[8..],
-                                    )
-                                {
-                                    pool_created_event_list.push(PoolCreatedEvent {
-                                        trx_hash: transaction.id(),
-                                        pool_id: event.pool_id,
-                                        question: event.question,
-                                        options: event.options.to_vec(),
-                                        bets_close_at: event.bets_close_at,
-                                        original_truth_social_post_id: event.original_truth_social_post_id,
-                                        image_url: event.image_url,
-                                        created_at: event.created_at,
-                                    });
-                                }
-                            }
-                            idl::idl::program::events::PoolImageSet::DISCRIMINATOR => {
-                                if let Ok(event) =
-                                    idl::idl::program::events::PoolImageSet::deserialize(
-                                        &mut &slice_u8[8..],
-                                    )
-                                {
-                                    pool_image_set_event_list.push(PoolImageSetEvent {
-                                        trx_hash: transaction.id(),
-                                        pool_id: event.pool_id,
-                                        image_url: event.image_url,
-                                    });
-                                }
-                            }
-                            _ => {}
-                        }
-                    }
-                });
-            });// ------------- INSTRUCTIONS -------------
+            match discriminator {
+                idl::idl::program::events::BetPlaced::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BetPlaced::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        bet_placed_event_list.push(BetPlacedEvent {
+                            trx_hash: transaction.id(),
+                            bet_id: event.bet_id,
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            option_index: event.option_index,
+                            amount: event.amount,
+                            mint: event.mint.to_string(),
+                            created_at: event.created_at,
+                            bet_totals: event.bet_totals.to_vec(),
+                            weighted_totals: event.weighted_totals.to_vec(),
+                            slot: event.slot,
+                            version: event.version as u32,
+                            weight: event.weight,
+                            payout_if_won: payout_if_won(&event),
+                        });
+                    }
+                }
+                idl::idl::program::events::PayoutClaimed::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PayoutClaimed::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        payout_claimed_event_list.push(PayoutClaimedEvent {
+                            trx_hash: transaction.id(),
+                            bet_id: event.bet_id,
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            amount: event.amount,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::PoolClosed::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PoolClosed::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        pool_closed_event_list.push(PoolClosedEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            selected_option: event.selected_option,
+                            decision_time: event.decision_time,
+                            is_draw: event.is_draw,
+                            slot: event.slot,
+                            version: event.version as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::PoolCreated::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PoolCreated::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        pool_created_event_list.push(PoolCreatedEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            question: event.question,
+                            options: event.options.to_vec(),
+                            bets_close_at: event.bets_close_at,
+                            original_truth_social_post_id: event.original_truth_social_post_id,
+                            image_url: event.image_url,
+                            created_at: event.created_at,
+                            reveal_ends_at: event.reveal_ends_at,
+                            forfeit_unrevealed: event.forfeit_unrevealed,
+                            weight_curve: map_enum_weight_curve(event.weight_curve),
+                            early_bonus_bp: event.early_bonus_bp as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::PoolImageSet::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PoolImageSet::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        pool_image_set_event_list.push(PoolImageSetEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            image_url: event.image_url,
+                        });
+                    }
+                }
+                idl::idl::program::events::MintAdded::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::MintAdded::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        mint_added_event_list.push(MintAddedEvent {
+                            trx_hash: transaction.id(),
+                            mint: event.mint.to_string(),
+                            vault: event.vault.to_string(),
+                            decimals: event.decimals as u32,
+                            payout_fee_bp: event.payout_fee_bp as u32,
+                            min_bet: event.min_bet,
+                        });
+                    }
+                }
+                idl::idl::program::events::MintUpdated::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::MintUpdated::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        mint_updated_event_list.push(MintUpdatedEvent {
+                            trx_hash: transaction.id(),
+                            mint: event.mint.to_string(),
+                            payout_fee_bp: event.payout_fee_bp as u32,
+                            min_bet: event.min_bet,
+                            is_active: event.is_active,
+                        });
+                    }
+                }
+                idl::idl::program::events::AccountMigrated::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::AccountMigrated::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        account_migrated_event_list.push(AccountMigratedEvent {
+                            trx_hash: transaction.id(),
+                            account: event.account.to_string(),
+                            from_version: event.from_version as u32,
+                            to_version: event.to_version as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::TreasurySet::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::TreasurySet::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        treasury_set_event_list.push(TreasurySetEvent {
+                            trx_hash: transaction.id(),
+                            treasury: event.treasury.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::VaultDrained::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::VaultDrained::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        vault_drained_event_list.push(VaultDrainedEvent {
+                            trx_hash: transaction.id(),
+                            mint: event.mint.to_string(),
+                            treasury_token_account: event.treasury_token_account.to_string(),
+                            amount: event.amount,
+                        });
+                    }
+                }
+                idl::idl::program::events::ProgramClosed::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::ProgramClosed::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        program_closed_event_list.push(ProgramClosedEvent {
+                            trx_hash: transaction.id(),
+                            authority: event.authority.to_string(),
+                            treasury: event.treasury.to_string(),
+                            mints_closed: event.mints_closed,
+                            decommissioned_at: event.decommissioned_at,
+                            slot: event.slot,
+                            version: event.version as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::BetCommitted::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BetCommitted::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        bet_committed_event_list.push(BetCommittedEvent {
+                            trx_hash: transaction.id(),
+                            bet_id: event.bet_id,
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            commitment: event.commitment.to_vec(),
+                            amount: event.amount,
+                            mint: event.mint.to_string(),
+                            created_at: event.created_at,
+                        });
+                    }
+                }
+                idl::idl::program::events::BetRevealed::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BetRevealed::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        bet_revealed_event_list.push(BetRevealedEvent {
+                            trx_hash: transaction.id(),
+                            bet_id: event.bet_id,
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            option_index: event.option_index,
+                            amount: event.amount,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::MarketCreated::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::MarketCreated::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        market_created_event_list.push(MarketCreatedEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            mint: event.mint.to_string(),
+                            liquidity: event.liquidity,
+                            subsidy: event.subsidy,
+                        });
+                    }
+                }
+                idl::idl::program::events::SharesBought::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::SharesBought::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        shares_bought_event_list.push(SharesBoughtEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            option_index: event.option_index,
+                            shares: event.shares,
+                            cost: event.cost,
+                            mint: event.mint.to_string(),
+                            market_shares: event.market_shares.to_vec(),
+                        });
+                    }
+                }
+                idl::idl::program::events::SharesRedeemed::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::SharesRedeemed::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        shares_redeemed_event_list.push(SharesRedeemedEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            shares: event.shares.to_vec(),
+                            amount: event.amount,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::MarketSurplusWithdrawn::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::MarketSurplusWithdrawn::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        market_surplus_withdrawn_event_list.push(MarketSurplusWithdrawnEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            mint: event.mint.to_string(),
+                            amount: event.amount,
+                        });
+                    }
+                }
+                idl::idl::program::events::OfferCreated::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::OfferCreated::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        offer_created_event_list.push(OfferCreatedEvent {
+                            trx_hash: transaction.id(),
+                            offer_id: event.offer_id,
+                            pool_id: event.pool_id,
+                            maker: event.maker.to_string(),
+                            option_index: event.option_index,
+                            stake: event.stake,
+                            ask: event.ask,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::OfferAccepted::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::OfferAccepted::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        offer_accepted_event_list.push(OfferAcceptedEvent {
+                            trx_hash: transaction.id(),
+                            offer_id: event.offer_id,
+                            match_id: event.match_id,
+                            pool_id: event.pool_id,
+                            taker: event.taker.to_string(),
+                            maker_stake: event.maker_stake,
+                            taker_stake: event.taker_stake,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::OfferCancelled::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::OfferCancelled::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        offer_cancelled_event_list.push(OfferCancelledEvent {
+                            trx_hash: transaction.id(),
+                            offer_id: event.offer_id,
+                            pool_id: event.pool_id,
+                            refund: event.refund,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::MatchClaimed::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::MatchClaimed::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        match_claimed_event_list.push(MatchClaimedEvent {
+                            trx_hash: transaction.id(),
+                            offer_id: event.offer_id,
+                            match_id: event.match_id,
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            amount: event.amount,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::PoolUpdated::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PoolUpdated::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        pool_updated_event_list.push(PoolUpdatedEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            question: event.question,
+                            options: event.options.to_vec(),
+                        });
+                    }
+                }
+                idl::idl::program::events::BetsCloseAtExtended::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BetsCloseAtExtended::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        bets_close_at_extended_event_list.push(BetsCloseAtExtendedEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            previous_bets_close_at: event.previous_bets_close_at,
+                            bets_close_at: event.bets_close_at,
+                        });
+                    }
+                }
+                idl::idl::program::events::PoolBondSet::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PoolBondSet::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        pool_bond_set_event_list.push(PoolBondSetEvent {
+                            trx_hash: transaction.id(),
+                            mint: event.mint.to_string(),
+                            amount: event.amount,
+                        });
+                    }
+                }
+                idl::idl::program::events::PoolProposed::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PoolProposed::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        pool_proposed_event_list.push(PoolProposedEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            creator: event.creator.to_string(),
+                            bond: event.bond,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::PoolFlaggedInvalid::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PoolFlaggedInvalid::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        pool_flagged_invalid_event_list.push(PoolFlaggedInvalidEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            bond_slashed: event.bond_slashed,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::BondReturned::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BondReturned::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        bond_returned_event_list.push(BondReturnedEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            creator: event.creator.to_string(),
+                            amount: event.amount,
+                            mint: event.mint.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::BetRelayed::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BetRelayed::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        bet_relayed_event_list.push(BetRelayedEvent {
+                            trx_hash: transaction.id(),
+                            bet_id: event.bet_id,
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            relayer: event.relayer.to_string(),
+                            nonce: event.nonce,
+                        });
+                    }
+                }
+                idl::idl::program::events::SessionCreated::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::SessionCreated::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        session_created_event_list.push(SessionCreatedEvent {
+                            trx_hash: transaction.id(),
+                            owner: event.owner.to_string(),
+                            session_key: event.session_key.to_string(),
+                            spend_cap: event.spend_cap,
+                            expires_at: event.expires_at,
+                            allowed_mints: event.allowed_mints.iter().map(|key| key.to_string()).collect(),
+                        });
+                    }
+                }
+                idl::idl::program::events::SessionRevoked::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::SessionRevoked::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        session_revoked_event_list.push(SessionRevokedEvent {
+                            trx_hash: transaction.id(),
+                            owner: event.owner.to_string(),
+                            session_key: event.session_key.to_string(),
+                        });
+                    }
+                }
+                idl::idl::program::events::BalanceDeposited::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BalanceDeposited::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        balance_deposited_event_list.push(BalanceDepositedEvent {
+                            trx_hash: transaction.id(),
+                            owner: event.owner.to_string(),
+                            mint: event.mint.to_string(),
+                            amount: event.amount,
+                            balance: event.balance,
+                        });
+                    }
+                }
+                idl::idl::program::events::BalanceWithdrawn::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BalanceWithdrawn::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        balance_withdrawn_event_list.push(BalanceWithdrawnEvent {
+                            trx_hash: transaction.id(),
+                            owner: event.owner.to_string(),
+                            mint: event.mint.to_string(),
+                            amount: event.amount,
+                            balance: event.balance,
+                        });
+                    }
+                }
+                idl::idl::program::events::BalanceCredited::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BalanceCredited::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        balance_credited_event_list.push(BalanceCreditedEvent {
+                            trx_hash: transaction.id(),
+                            owner: event.owner.to_string(),
+                            mint: event.mint.to_string(),
+                            amount: event.amount,
+                            balance: event.balance,
+                        });
+                    }
+                }
+                idl::idl::program::events::ProgramInitialized::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::ProgramInitialized::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        program_initialized_event_list.push(ProgramInitializedEvent {
+                            trx_hash: transaction.id(),
+                            authority: event.authority.to_string(),
+                            treasury: event.treasury.to_string(),
+                            slot: event.slot,
+                            version: event.version as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::BetSettled::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::BetSettled::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        bet_settled_event_list.push(BetSettledEvent {
+                            trx_hash: transaction.id(),
+                            bet_id: event.bet_id,
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            mint: event.mint.to_string(),
+                            outcome: map_enum_bet_outcome(event.outcome),
+                            amount: event.amount,
+                            fee: event.fee,
+                            slot: event.slot,
+                            version: event.version as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::SolvencyReport::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::SolvencyReport::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        solvency_report_event_list.push(SolvencyReportEvent {
+                            trx_hash: transaction.id(),
+                            mint: event.mint.to_string(),
+                            vault_balance: event.vault_balance,
+                            liabilities: event.liabilities,
+                            shortfall: event.shortfall,
+                            pools_audited: event.pools_audited,
+                            slot: event.slot,
+                            version: event.version as u32,
+                            pools_owe: event.pools_owe,
+                            balances: event.balances,
+                            escrowed: event.escrowed,
+                            bonds: event.bonds,
+                        });
+                    }
+                }
+                idl::idl::program::events::ClaimWindowSet::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::ClaimWindowSet::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        claim_window_set_event_list.push(ClaimWindowSetEvent {
+                            trx_hash: transaction.id(),
+                            claim_window: event.claim_window,
+                        });
+                    }
+                }
+                idl::idl::program::events::UnclaimedSwept::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::UnclaimedSwept::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        unclaimed_swept_event_list.push(UnclaimedSweptEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            mint: event.mint.to_string(),
+                            treasury_token_account: event.treasury_token_account.to_string(),
+                            amount: event.amount,
+                            slot: event.slot,
+                            version: event.version as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::MatchEmergencyWithdrawn::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::MatchEmergencyWithdrawn::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        match_emergency_withdrawn_event_list.push(
+                            MatchEmergencyWithdrawnEvent {
+                                trx_hash: transaction.id(),
+                                offer_id: event.offer_id,
+                                match_id: event.match_id,
+                                pool_id: event.pool_id,
+                                maker: event.maker.to_string(),
+                                taker: event.taker.to_string(),
+                                maker_stake: event.maker_stake,
+                                taker_stake: event.taker_stake,
+                                mint: event.mint.to_string(),
+                                slot: event.slot,
+                                version: event.version as u32,
+                            },
+                        );
+                    }
+                }
+                idl::idl::program::events::ExpiredClaimsSwept::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::ExpiredClaimsSwept::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        expired_claims_swept_event_list.push(ExpiredClaimsSweptEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            mint: event.mint.to_string(),
+                            treasury_token_account: event.treasury_token_account.to_string(),
+                            claims: event.claims as u32,
+                            amount: event.amount,
+                            slot: event.slot,
+                            version: event.version as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::EmergencyWithdrawn::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::EmergencyWithdrawn::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        emergency_withdrawn_event_list.push(EmergencyWithdrawnEvent {
+                            trx_hash: transaction.id(),
+                            bet_id: event.bet_id,
+                            pool_id: event.pool_id,
+                            user: event.user.to_string(),
+                            amount: event.amount,
+                            mint: event.mint.to_string(),
+                            slot: event.slot,
+                            version: event.version as u32,
+                        });
+                    }
+                }
+                idl::idl::program::events::PoolSettled::DISCRIMINATOR => {
+                    if let Ok(event) =
+                        idl::idl::program::events::PoolSettled::deserialize(
+                            &mut &slice_u8[8..],
+                        )
+                    {
+                        pool_settled_event_list.push(PoolSettledEvent {
+                            trx_hash: transaction.id(),
+                            pool_id: event.pool_id,
+                            slot: event.slot,
+                            version: event.version as u32,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+        // ------------- INSTRUCTIONS -------------
         transaction
         .walk_instructions()
         .into_iter()
         .filter(|inst| inst.program_id().to_string() == PROGRAM_ID)
         .for_each(|inst| {
             let slice_u8: &[u8] = &inst.data()[..];
-            if &slice_u8[0..8] == idl::idl::program::client::args::ClaimPayout::DISCRIMINATOR {
-                if let Ok(instruction) =
-                    idl::idl::program::client::args::ClaimPayout::deserialize(&mut &slice_u8[8..])
+            if slice_u8.get(..8) == Some(idl::idl::program::client::args::ClaimPayout::DISCRIMINATOR) {
+                if idl::idl::program::client::args::ClaimPayout::deserialize(&mut &slice_u8[8..])
+                    .is_ok()
                 {
                     let accts = inst.accounts();
                     claim_payout_instruction_list.push(ClaimPayoutInstruction {
@@ -160,12 +831,16 @@ fn map_program_data(blk: Block) -> Data {
                         acct_pool: accts[1].to_string(),
                         acct_bet: accts[2].to_string(),
                         acct_bettor: accts[3].to_string(),
-                        acct_bettor_token_account: accts[4].to_string(),
-                        acct_program_token_account: accts[5].to_string(),
+                        acct_session: map_optional_account(&accts[4]),
+                        acct_mint: accts[5].to_string(),
+                        acct_mint_config: accts[6].to_string(),
+                        acct_pool_totals: accts[7].to_string(),
+                        acct_bettor_token_account: accts[8].to_string(),
+                        acct_program_token_account: accts[9].to_string(),
                     });
                 }
             }
-            if &slice_u8[0..8] == idl::idl::program::client::args::CreatePool::DISCRIMINATOR {
+            if slice_u8.get(..8) == Some(idl::idl::program::client::args::CreatePool::DISCRIMINATOR) {
                 if let Ok(instruction) =
                     idl::idl::program::client::args::CreatePool::deserialize(&mut &slice_u8[8..])
                 {
@@ -179,11 +854,25 @@ fn map_program_data(blk: Block) -> Data {
                         image_url: instruction.image_url,
                         acct_betting_pools: accts[0].to_string(),
                         acct_pool: accts[1].to_string(),
-                        acct_authority: accts[2].to_string(),
+                        acct_pool_metadata: accts[2].to_string(),
+                        acct_authority: accts[3].to_string(),
+                        hidden_bets: instruction.hidden_bets.is_some(),
+                        reveal_ends_at: instruction
+                            .hidden_bets
+                            .map_or(0, |config| config.reveal_ends_at),
+                        forfeit_unrevealed: instruction
+                            .hidden_bets
+                            .is_some_and(|config| config.forfeit_unrevealed),
+                        weight_curve: instruction
+                            .time_weighting
+                            .map_or(0, |weighting| map_enum_weight_curve(weighting.curve)),
+                        early_bonus_bp: instruction
+                            .time_weighting
+                            .map_or(0, |weighting| weighting.max_bonus_bp as u32),
                     });
                 }
             }
-            if &slice_u8[0..8] == idl::idl::program::client::args::GradeBet::DISCRIMINATOR {
+            if slice_u8.get(..8) == Some(idl::idl::program::client::args::GradeBet::DISCRIMINATOR) {
                 if let Ok(instruction) =
                     idl::idl::program::client::args::GradeBet::deserialize(&mut &slice_u8[8..])
                 {
@@ -197,21 +886,19 @@ fn map_program_data(blk: Block) -> Data {
                     });
                 }
             }
-            if &slice_u8[0..8] == idl::idl::program::client::args::Initialize::DISCRIMINATOR {
-                if let Ok(instruction) =
+            if slice_u8.get(..8) == Some(idl::idl::program::client::args::Initialize::DISCRIMINATOR) {
+                if let Ok(_instruction) =
                     idl::idl::program::client::args::Initialize::deserialize(&mut &slice_u8[8..])
                 {
                     let accts = inst.accounts();
                     initialize_instruction_list.push(InitializeInstruction {
                         trx_hash: transaction.id(),
-                        usdc_mint: instruction.usdc_mint.to_string(),
-                        freedom_mint: instruction.freedom_mint.to_string(),
                         acct_betting_pools: accts[0].to_string(),
                         acct_authority: accts[1].to_string(),
                     });
                 }
             }
-            if &slice_u8[0..8] == idl::idl::program::client::args::PlaceBet::DISCRIMINATOR {
+            if slice_u8.get(..8) == Some(idl::idl::program::client::args::PlaceBet::DISCRIMINATOR) {
                 if let Ok(instruction) =
                     idl::idl::program::client::args::PlaceBet::deserialize(&mut &slice_u8[8..])
                 {
@@ -220,17 +907,20 @@ fn map_program_data(blk: Block) -> Data {
                         trx_hash: transaction.id(),
                         option_index: instruction.option_index,
                         amount: instruction.amount,
-                        token_type: map_enum_token_type(instruction.token_type),
                         acct_betting_pools: accts[0].to_string(),
                         acct_pool: accts[1].to_string(),
                         acct_bet: accts[2].to_string(),
                         acct_bettor: accts[3].to_string(),
-                        acct_bettor_token_account: accts[4].to_string(),
-                        acct_program_token_account: accts[5].to_string(),
+                        acct_session: map_optional_account(&accts[4]),
+                        acct_mint: accts[5].to_string(),
+                        acct_mint_config: accts[6].to_string(),
+                        acct_pool_totals: accts[7].to_string(),
+                        acct_bettor_token_account: accts[8].to_string(),
+                        acct_program_token_account: accts[9].to_string(),
                     });
                 }
             }
-            if &slice_u8[0..8] == idl::idl::program::client::args::SetImage::DISCRIMINATOR {
+            if slice_u8.get(..8) == Some(idl::idl::program::client::args::SetImage::DISCRIMINATOR) {
                 if let Ok(instruction) =
                     idl::idl::program::client::args::SetImage::deserialize(&mut &slice_u8[8..])
                 {
@@ -238,17 +928,16 @@ fn map_program_data(blk: Block) -> Data {
                     set_image_instruction_list.push(SetImageInstruction {
                         trx_hash: transaction.id(),
                         image_url: instruction.image_url,
-                        acct_pool: accts[0].to_string(),
+                        acct_pool_metadata: accts[0].to_string(),
                         acct_betting_pools: accts[1].to_string(),
                         acct_authority: accts[2].to_string(),
                     });
                 }
             }
         });
-    });
-
+    }
 
-    Data {
+    Ok(Data {
         bet_placed_event_list,
         payout_claimed_event_list,
         pool_closed_event_list,
@@ -260,22 +949,87 @@ fn map_program_data(blk: Block) -> Data {
         initialize_instruction_list,
         place_bet_instruction_list,
         set_image_instruction_list,
-    }
+        mint_added_event_list,
+        mint_updated_event_list,
+        account_migrated_event_list,
+        treasury_set_event_list,
+        vault_drained_event_list,
+        program_closed_event_list,
+        bet_committed_event_list,
+        bet_revealed_event_list,
+        market_created_event_list,
+        shares_bought_event_list,
+        shares_redeemed_event_list,
+        market_surplus_withdrawn_event_list,
+        offer_created_event_list,
+        offer_accepted_event_list,
+        offer_cancelled_event_list,
+        match_claimed_event_list,
+        pool_updated_event_list,
+        bets_close_at_extended_event_list,
+        pool_bond_set_event_list,
+        pool_proposed_event_list,
+        pool_flagged_invalid_event_list,
+        bond_returned_event_list,
+        bet_relayed_event_list,
+        session_created_event_list,
+        session_revoked_event_list,
+        balance_deposited_event_list,
+        balance_withdrawn_event_list,
+        balance_credited_event_list,
+        program_initialized_event_list,
+        bet_settled_event_list,
+        solvency_report_event_list,
+        claim_window_set_event_list,
+        unclaimed_swept_event_list,
+        emergency_withdrawn_event_list,
+        pool_settled_event_list,
+        expired_claims_swept_event_list,
+        match_emergency_withdrawn_event_list,
+    })
 }
 
-fn map_enum_pool_status(value: idl::idl::program::types::PoolStatus) -> i32 {
+fn map_enum_weight_curve(value: idl::idl::program::types::WeightCurve) -> i32 {
     match value {
-        idl::idl::program::types::PoolStatus::None => return 0,
-        idl::idl::program::types::PoolStatus::Pending => return 1,
-        idl::idl::program::types::PoolStatus::Graded => return 2,
-        idl::idl::program::types::PoolStatus::Regraded => return 3,
-        _ => 0,
+        idl::idl::program::types::WeightCurve::None => return 0,
+        idl::idl::program::types::WeightCurve::Linear => return 1,
+        idl::idl::program::types::WeightCurve::Quadratic => return 2,
     }
 }
-fn map_enum_token_type(value: idl::idl::program::types::TokenType) -> i32 {
+
+fn map_enum_bet_outcome(value: idl::idl::program::types::BetOutcome) -> i32 {
     match value {
-        idl::idl::program::types::TokenType::Usdc => return 0,
-        idl::idl::program::types::TokenType::Points => return 1,
-        _ => 0,
+        idl::idl::program::types::BetOutcome::Won => return 0,
+        idl::idl::program::types::BetOutcome::Lost => return 1,
+        idl::idl::program::types::BetOutcome::Refunded => return 2,
+    }
+}
+
+// Payout of a placed bet if the pool were graded for its option right after it, by the
+// same rules the program pays claims with. The fee depends on the mint, so it is left out
+fn payout_if_won(event: &idl::idl::program::events::BetPlaced) -> u64 {
+    let pool = trump_fun_settlement::Pool {
+        winning_option: event.option_index,
+        is_draw: false,
+        forfeit_unrevealed: false,
+        bet_totals: event.bet_totals,
+        weighted_totals: event.weighted_totals,
+    };
+    let bet = trump_fun_settlement::Bet {
+        option: event.option_index,
+        amount: event.amount,
+        weight: event.weight,
+        is_revealed: true,
+    };
+    trump_fun_settlement::payout(&pool, &bet, 0).amount
+}
+
+// Anchor clients pass the program id in place of an optional account that is left out
+fn map_optional_account(account: &substreams_solana::Address) -> String {
+    let account = account.to_string();
+    if account == PROGRAM_ID {
+        String::new()
+    } else {
+        account
     }
 }
@@ -24,6 +24,80 @@ pub struct Data {
     pub place_bet_instruction_list: ::prost::alloc::vec::Vec<PlaceBetInstruction>,
     #[prost(message, repeated, tag="11")]
     pub set_image_instruction_list: ::prost::alloc::vec::Vec<SetImageInstruction>,
+    #[prost(message, repeated, tag="12")]
+    pub mint_added_event_list: ::prost::alloc::vec::Vec<MintAddedEvent>,
+    #[prost(message, repeated, tag="13")]
+    pub mint_updated_event_list: ::prost::alloc::vec::Vec<MintUpdatedEvent>,
+    #[prost(message, repeated, tag="14")]
+    pub account_migrated_event_list: ::prost::alloc::vec::Vec<AccountMigratedEvent>,
+    #[prost(message, repeated, tag="15")]
+    pub treasury_set_event_list: ::prost::alloc::vec::Vec<TreasurySetEvent>,
+    #[prost(message, repeated, tag="16")]
+    pub vault_drained_event_list: ::prost::alloc::vec::Vec<VaultDrainedEvent>,
+    #[prost(message, repeated, tag="17")]
+    pub program_closed_event_list: ::prost::alloc::vec::Vec<ProgramClosedEvent>,
+    #[prost(message, repeated, tag="18")]
+    pub bet_committed_event_list: ::prost::alloc::vec::Vec<BetCommittedEvent>,
+    #[prost(message, repeated, tag="19")]
+    pub bet_revealed_event_list: ::prost::alloc::vec::Vec<BetRevealedEvent>,
+    #[prost(message, repeated, tag="20")]
+    pub market_created_event_list: ::prost::alloc::vec::Vec<MarketCreatedEvent>,
+    #[prost(message, repeated, tag="21")]
+    pub shares_bought_event_list: ::prost::alloc::vec::Vec<SharesBoughtEvent>,
+    #[prost(message, repeated, tag="22")]
+    pub shares_redeemed_event_list: ::prost::alloc::vec::Vec<SharesRedeemedEvent>,
+    #[prost(message, repeated, tag="23")]
+    pub market_surplus_withdrawn_event_list: ::prost::alloc::vec::Vec<MarketSurplusWithdrawnEvent>,
+    #[prost(message, repeated, tag="24")]
+    pub offer_created_event_list: ::prost::alloc::vec::Vec<OfferCreatedEvent>,
+    #[prost(message, repeated, tag="25")]
+    pub offer_accepted_event_list: ::prost::alloc::vec::Vec<OfferAcceptedEvent>,
+    #[prost(message, repeated, tag="26")]
+    pub offer_cancelled_event_list: ::prost::alloc::vec::Vec<OfferCancelledEvent>,
+    #[prost(message, repeated, tag="27")]
+    pub match_claimed_event_list: ::prost::alloc::vec::Vec<MatchClaimedEvent>,
+    #[prost(message, repeated, tag="28")]
+    pub pool_updated_event_list: ::prost::alloc::vec::Vec<PoolUpdatedEvent>,
+    #[prost(message, repeated, tag="29")]
+    pub bets_close_at_extended_event_list: ::prost::alloc::vec::Vec<BetsCloseAtExtendedEvent>,
+    #[prost(message, repeated, tag="30")]
+    pub pool_bond_set_event_list: ::prost::alloc::vec::Vec<PoolBondSetEvent>,
+    #[prost(message, repeated, tag="31")]
+    pub pool_proposed_event_list: ::prost::alloc::vec::Vec<PoolProposedEvent>,
+    #[prost(message, repeated, tag="32")]
+    pub pool_flagged_invalid_event_list: ::prost::alloc::vec::Vec<PoolFlaggedInvalidEvent>,
+    #[prost(message, repeated, tag="33")]
+    pub bond_returned_event_list: ::prost::alloc::vec::Vec<BondReturnedEvent>,
+    #[prost(message, repeated, tag="34")]
+    pub bet_relayed_event_list: ::prost::alloc::vec::Vec<BetRelayedEvent>,
+    #[prost(message, repeated, tag="35")]
+    pub session_created_event_list: ::prost::alloc::vec::Vec<SessionCreatedEvent>,
+    #[prost(message, repeated, tag="36")]
+    pub session_revoked_event_list: ::prost::alloc::vec::Vec<SessionRevokedEvent>,
+    #[prost(message, repeated, tag="37")]
+    pub balance_deposited_event_list: ::prost::alloc::vec::Vec<BalanceDepositedEvent>,
+    #[prost(message, repeated, tag="38")]
+    pub balance_withdrawn_event_list: ::prost::alloc::vec::Vec<BalanceWithdrawnEvent>,
+    #[prost(message, repeated, tag="39")]
+    pub balance_credited_event_list: ::prost::alloc::vec::Vec<BalanceCreditedEvent>,
+    #[prost(message, repeated, tag="40")]
+    pub program_initialized_event_list: ::prost::alloc::vec::Vec<ProgramInitializedEvent>,
+    #[prost(message, repeated, tag="41")]
+    pub bet_settled_event_list: ::prost::alloc::vec::Vec<BetSettledEvent>,
+    #[prost(message, repeated, tag="42")]
+    pub solvency_report_event_list: ::prost::alloc::vec::Vec<SolvencyReportEvent>,
+    #[prost(message, repeated, tag="43")]
+    pub claim_window_set_event_list: ::prost::alloc::vec::Vec<ClaimWindowSetEvent>,
+    #[prost(message, repeated, tag="44")]
+    pub unclaimed_swept_event_list: ::prost::alloc::vec::Vec<UnclaimedSweptEvent>,
+    #[prost(message, repeated, tag="45")]
+    pub emergency_withdrawn_event_list: ::prost::alloc::vec::Vec<EmergencyWithdrawnEvent>,
+    #[prost(message, repeated, tag="46")]
+    pub pool_settled_event_list: ::prost::alloc::vec::Vec<PoolSettledEvent>,
+    #[prost(message, repeated, tag="47")]
+    pub expired_claims_swept_event_list: ::prost::alloc::vec::Vec<ExpiredClaimsSweptEvent>,
+    #[prost(message, repeated, tag="48")]
+    pub match_emergency_withdrawn_event_list: ::prost::alloc::vec::Vec<MatchEmergencyWithdrawnEvent>,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -40,10 +114,22 @@ pub struct BetPlacedEvent {
     pub option_index: u64,
     #[prost(uint64, tag="6")]
     pub amount: u64,
-    #[prost(enumeration="TokenTypeEnum", tag="7")]
-    pub token_type: i32,
     #[prost(int64, tag="8")]
     pub created_at: i64,
+    #[prost(string, tag="9")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, repeated, tag="10")]
+    pub bet_totals: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint64, repeated, tag="11")]
+    pub weighted_totals: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint64, tag="12")]
+    pub slot: u64,
+    #[prost(uint32, tag="13")]
+    pub version: u32,
+    #[prost(uint64, tag="14")]
+    pub weight: u64,
+    #[prost(uint64, tag="15")]
+    pub payout_if_won: u64,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -58,8 +144,8 @@ pub struct PayoutClaimedEvent {
     pub user: ::prost::alloc::string::String,
     #[prost(uint64, tag="5")]
     pub amount: u64,
-    #[prost(enumeration="TokenTypeEnum", tag="6")]
-    pub token_type: i32,
+    #[prost(string, tag="7")]
+    pub mint: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -72,6 +158,12 @@ pub struct PoolClosedEvent {
     pub selected_option: u64,
     #[prost(int64, tag="4")]
     pub decision_time: i64,
+    #[prost(bool, tag="5")]
+    pub is_draw: bool,
+    #[prost(uint64, tag="6")]
+    pub slot: u64,
+    #[prost(uint32, tag="7")]
+    pub version: u32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -92,6 +184,14 @@ pub struct PoolCreatedEvent {
     pub image_url: ::prost::alloc::string::String,
     #[prost(int64, tag="8")]
     pub created_at: i64,
+    #[prost(int64, tag="9")]
+    pub reveal_ends_at: i64,
+    #[prost(bool, tag="10")]
+    pub forfeit_unrevealed: bool,
+    #[prost(enumeration="WeightCurveEnum", tag="11")]
+    pub weight_curve: i32,
+    #[prost(uint32, tag="12")]
+    pub early_bonus_bp: u32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -105,6 +205,586 @@ pub struct PoolImageSetEvent {
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MintAddedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub vault: ::prost::alloc::string::String,
+    #[prost(uint32, tag="4")]
+    pub decimals: u32,
+    #[prost(uint32, tag="5")]
+    pub payout_fee_bp: u32,
+    #[prost(uint64, tag="6")]
+    pub min_bet: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MintUpdatedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint32, tag="3")]
+    pub payout_fee_bp: u32,
+    #[prost(uint64, tag="4")]
+    pub min_bet: u64,
+    #[prost(bool, tag="5")]
+    pub is_active: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AccountMigratedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub account: ::prost::alloc::string::String,
+    #[prost(uint32, tag="3")]
+    pub from_version: u32,
+    #[prost(uint32, tag="4")]
+    pub to_version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TreasurySetEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub treasury: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct VaultDrainedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub treasury_token_account: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub amount: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProgramClosedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub authority: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub treasury: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub mints_closed: u64,
+    #[prost(int64, tag="5")]
+    pub decommissioned_at: i64,
+    #[prost(uint64, tag="6")]
+    pub slot: u64,
+    #[prost(uint32, tag="7")]
+    pub version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BetCommittedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub bet_id: u64,
+    #[prost(uint64, tag="3")]
+    pub pool_id: u64,
+    #[prost(string, tag="4")]
+    pub user: ::prost::alloc::string::String,
+    #[prost(bytes="vec", tag="5")]
+    pub commitment: ::prost::alloc::vec::Vec<u8>,
+    #[prost(uint64, tag="6")]
+    pub amount: u64,
+    #[prost(string, tag="7")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(int64, tag="8")]
+    pub created_at: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BetRevealedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub bet_id: u64,
+    #[prost(uint64, tag="3")]
+    pub pool_id: u64,
+    #[prost(string, tag="4")]
+    pub user: ::prost::alloc::string::String,
+    #[prost(uint64, tag="5")]
+    pub option_index: u64,
+    #[prost(uint64, tag="6")]
+    pub amount: u64,
+    #[prost(string, tag="7")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MarketCreatedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub liquidity: u64,
+    #[prost(uint64, tag="5")]
+    pub subsidy: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SharesBoughtEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub user: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub option_index: u64,
+    #[prost(uint64, tag="5")]
+    pub shares: u64,
+    #[prost(uint64, tag="6")]
+    pub cost: u64,
+    #[prost(string, tag="7")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, repeated, tag="8")]
+    pub market_shares: ::prost::alloc::vec::Vec<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SharesRedeemedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub user: ::prost::alloc::string::String,
+    #[prost(uint64, repeated, tag="4")]
+    pub shares: ::prost::alloc::vec::Vec<u64>,
+    #[prost(uint64, tag="5")]
+    pub amount: u64,
+    #[prost(string, tag="6")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MarketSurplusWithdrawnEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub amount: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OfferCreatedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub offer_id: u64,
+    #[prost(uint64, tag="3")]
+    pub pool_id: u64,
+    #[prost(string, tag="4")]
+    pub maker: ::prost::alloc::string::String,
+    #[prost(uint64, tag="5")]
+    pub option_index: u64,
+    #[prost(uint64, tag="6")]
+    pub stake: u64,
+    #[prost(uint64, tag="7")]
+    pub ask: u64,
+    #[prost(string, tag="8")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OfferAcceptedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub offer_id: u64,
+    #[prost(uint64, tag="3")]
+    pub match_id: u64,
+    #[prost(uint64, tag="4")]
+    pub pool_id: u64,
+    #[prost(string, tag="5")]
+    pub taker: ::prost::alloc::string::String,
+    #[prost(uint64, tag="6")]
+    pub maker_stake: u64,
+    #[prost(uint64, tag="7")]
+    pub taker_stake: u64,
+    #[prost(string, tag="8")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct OfferCancelledEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub offer_id: u64,
+    #[prost(uint64, tag="3")]
+    pub pool_id: u64,
+    #[prost(uint64, tag="4")]
+    pub refund: u64,
+    #[prost(string, tag="5")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MatchClaimedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub offer_id: u64,
+    #[prost(uint64, tag="3")]
+    pub match_id: u64,
+    #[prost(uint64, tag="4")]
+    pub pool_id: u64,
+    #[prost(string, tag="5")]
+    pub user: ::prost::alloc::string::String,
+    #[prost(uint64, tag="6")]
+    pub amount: u64,
+    #[prost(string, tag="7")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolUpdatedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub question: ::prost::alloc::string::String,
+    #[prost(string, repeated, tag="4")]
+    pub options: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BetsCloseAtExtendedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(int64, tag="3")]
+    pub previous_bets_close_at: i64,
+    #[prost(int64, tag="4")]
+    pub bets_close_at: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolBondSetEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="3")]
+    pub amount: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolProposedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub creator: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub bond: u64,
+    #[prost(string, tag="5")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolFlaggedInvalidEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(uint64, tag="3")]
+    pub bond_slashed: u64,
+    #[prost(string, tag="4")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BondReturnedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub creator: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub amount: u64,
+    #[prost(string, tag="5")]
+    pub mint: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BetRelayedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub bet_id: u64,
+    #[prost(uint64, tag="3")]
+    pub pool_id: u64,
+    #[prost(string, tag="4")]
+    pub user: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub relayer: ::prost::alloc::string::String,
+    #[prost(uint64, tag="6")]
+    pub nonce: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SessionCreatedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub session_key: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub spend_cap: u64,
+    #[prost(int64, tag="5")]
+    pub expires_at: i64,
+    #[prost(string, repeated, tag="6")]
+    pub allowed_mints: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SessionRevokedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub session_key: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceDepositedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub amount: u64,
+    #[prost(uint64, tag="5")]
+    pub balance: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceWithdrawnEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub amount: u64,
+    #[prost(uint64, tag="5")]
+    pub balance: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BalanceCreditedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub owner: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub amount: u64,
+    #[prost(uint64, tag="5")]
+    pub balance: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ProgramInitializedEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub authority: ::prost::alloc::string::String,
+    #[prost(string, tag="3")]
+    pub treasury: ::prost::alloc::string::String,
+    #[prost(uint64, tag="4")]
+    pub slot: u64,
+    #[prost(uint32, tag="5")]
+    pub version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BetSettledEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub bet_id: u64,
+    #[prost(uint64, tag="3")]
+    pub pool_id: u64,
+    #[prost(string, tag="4")]
+    pub user: ::prost::alloc::string::String,
+    #[prost(string, tag="5")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(enumeration="BetOutcomeEnum", tag="6")]
+    pub outcome: i32,
+    #[prost(uint64, tag="7")]
+    pub amount: u64,
+    #[prost(uint64, tag="8")]
+    pub fee: u64,
+    #[prost(uint64, tag="9")]
+    pub slot: u64,
+    #[prost(uint32, tag="10")]
+    pub version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SolvencyReportEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(string, tag="2")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="3")]
+    pub vault_balance: u64,
+    #[prost(uint64, tag="4")]
+    pub liabilities: u64,
+    #[prost(uint64, tag="5")]
+    pub shortfall: u64,
+    #[prost(uint64, tag="6")]
+    pub pools_audited: u64,
+    #[prost(uint64, tag="8")]
+    pub slot: u64,
+    #[prost(uint32, tag="9")]
+    pub version: u32,
+    #[prost(uint64, tag="10")]
+    pub pools_owe: u64,
+    #[prost(uint64, tag="11")]
+    pub balances: u64,
+    #[prost(uint64, tag="12")]
+    pub escrowed: u64,
+    #[prost(uint64, tag="13")]
+    pub bonds: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClaimWindowSetEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(int64, tag="2")]
+    pub claim_window: i64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UnclaimedSweptEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub treasury_token_account: ::prost::alloc::string::String,
+    #[prost(uint64, tag="5")]
+    pub amount: u64,
+    #[prost(uint64, tag="6")]
+    pub slot: u64,
+    #[prost(uint32, tag="7")]
+    pub version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct MatchEmergencyWithdrawnEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub offer_id: u64,
+    #[prost(uint64, tag="3")]
+    pub match_id: u64,
+    #[prost(uint64, tag="4")]
+    pub pool_id: u64,
+    #[prost(string, tag="5")]
+    pub maker: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub taker: ::prost::alloc::string::String,
+    #[prost(uint64, tag="7")]
+    pub maker_stake: u64,
+    #[prost(uint64, tag="8")]
+    pub taker_stake: u64,
+    #[prost(string, tag="9")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="10")]
+    pub slot: u64,
+    #[prost(uint32, tag="11")]
+    pub version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExpiredClaimsSweptEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(string, tag="3")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(string, tag="4")]
+    pub treasury_token_account: ::prost::alloc::string::String,
+    #[prost(uint32, tag="5")]
+    pub claims: u32,
+    #[prost(uint64, tag="6")]
+    pub amount: u64,
+    #[prost(uint64, tag="7")]
+    pub slot: u64,
+    #[prost(uint32, tag="8")]
+    pub version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct EmergencyWithdrawnEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub bet_id: u64,
+    #[prost(uint64, tag="3")]
+    pub pool_id: u64,
+    #[prost(string, tag="4")]
+    pub user: ::prost::alloc::string::String,
+    #[prost(uint64, tag="5")]
+    pub amount: u64,
+    #[prost(string, tag="6")]
+    pub mint: ::prost::alloc::string::String,
+    #[prost(uint64, tag="7")]
+    pub slot: u64,
+    #[prost(uint32, tag="8")]
+    pub version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PoolSettledEvent {
+    #[prost(string, tag="1")]
+    pub trx_hash: ::prost::alloc::string::String,
+    #[prost(uint64, tag="2")]
+    pub pool_id: u64,
+    #[prost(uint64, tag="3")]
+    pub slot: u64,
+    #[prost(uint32, tag="4")]
+    pub version: u32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ClaimPayoutInstruction {
     #[prost(string, tag="1")]
     pub trx_hash: ::prost::alloc::string::String,
@@ -120,6 +800,14 @@ pub struct ClaimPayoutInstruction {
     pub acct_bettor_token_account: ::prost::alloc::string::String,
     #[prost(string, tag="7")]
     pub acct_program_token_account: ::prost::alloc::string::String,
+    #[prost(string, tag="8")]
+    pub acct_mint: ::prost::alloc::string::String,
+    #[prost(string, tag="9")]
+    pub acct_mint_config: ::prost::alloc::string::String,
+    #[prost(string, tag="10")]
+    pub acct_pool_totals: ::prost::alloc::string::String,
+    #[prost(string, tag="11")]
+    pub acct_session: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -142,6 +830,18 @@ pub struct CreatePoolInstruction {
     pub acct_pool: ::prost::alloc::string::String,
     #[prost(string, tag="9")]
     pub acct_authority: ::prost::alloc::string::String,
+    #[prost(string, tag="10")]
+    pub acct_pool_metadata: ::prost::alloc::string::String,
+    #[prost(bool, tag="11")]
+    pub hidden_bets: bool,
+    #[prost(int64, tag="12")]
+    pub reveal_ends_at: i64,
+    #[prost(bool, tag="13")]
+    pub forfeit_unrevealed: bool,
+    #[prost(enumeration="WeightCurveEnum", tag="14")]
+    pub weight_curve: i32,
+    #[prost(uint32, tag="15")]
+    pub early_bonus_bp: u32,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -162,10 +862,6 @@ pub struct GradeBetInstruction {
 pub struct InitializeInstruction {
     #[prost(string, tag="1")]
     pub trx_hash: ::prost::alloc::string::String,
-    #[prost(string, tag="2")]
-    pub usdc_mint: ::prost::alloc::string::String,
-    #[prost(string, tag="3")]
-    pub freedom_mint: ::prost::alloc::string::String,
     #[prost(string, tag="4")]
     pub acct_betting_pools: ::prost::alloc::string::String,
     #[prost(string, tag="5")]
@@ -180,8 +876,6 @@ pub struct PlaceBetInstruction {
     pub option_index: u64,
     #[prost(uint64, tag="3")]
     pub amount: u64,
-    #[prost(enumeration="TokenTypeEnum", tag="4")]
-    pub token_type: i32,
     #[prost(string, tag="5")]
     pub acct_betting_pools: ::prost::alloc::string::String,
     #[prost(string, tag="6")]
@@ -194,6 +888,14 @@ pub struct PlaceBetInstruction {
     pub acct_bettor_token_account: ::prost::alloc::string::String,
     #[prost(string, tag="10")]
     pub acct_program_token_account: ::prost::alloc::string::String,
+    #[prost(string, tag="11")]
+    pub acct_mint: ::prost::alloc::string::String,
+    #[prost(string, tag="12")]
+    pub acct_mint_config: ::prost::alloc::string::String,
+    #[prost(string, tag="13")]
+    pub acct_pool_totals: ::prost::alloc::string::String,
+    #[prost(string, tag="14")]
+    pub acct_session: ::prost::alloc::string::String,
 }
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -202,35 +904,67 @@ pub struct SetImageInstruction {
     pub trx_hash: ::prost::alloc::string::String,
     #[prost(string, tag="2")]
     pub image_url: ::prost::alloc::string::String,
-    #[prost(string, tag="3")]
-    pub acct_pool: ::prost::alloc::string::String,
     #[prost(string, tag="4")]
     pub acct_betting_pools: ::prost::alloc::string::String,
     #[prost(string, tag="5")]
     pub acct_authority: ::prost::alloc::string::String,
+    #[prost(string, tag="6")]
+    pub acct_pool_metadata: ::prost::alloc::string::String,
+}
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum WeightCurveEnum {
+    WeightCurveNone = 0,
+    WeightCurveLinear = 1,
+    WeightCurveQuadratic = 2,
+}
+impl WeightCurveEnum {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            WeightCurveEnum::WeightCurveNone => "WEIGHT_CURVE_NONE",
+            WeightCurveEnum::WeightCurveLinear => "WEIGHT_CURVE_LINEAR",
+            WeightCurveEnum::WeightCurveQuadratic => "WEIGHT_CURVE_QUADRATIC",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "WEIGHT_CURVE_NONE" => Some(Self::WeightCurveNone),
+            "WEIGHT_CURVE_LINEAR" => Some(Self::WeightCurveLinear),
+            "WEIGHT_CURVE_QUADRATIC" => Some(Self::WeightCurveQuadratic),
+            _ => None,
+        }
+    }
 }
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
-pub enum TokenTypeEnum {
-    TokenTypeUsdc = 0,
-    TokenTypePoints = 1,
+pub enum BetOutcomeEnum {
+    BetOutcomeWon = 0,
+    BetOutcomeLost = 1,
+    BetOutcomeRefunded = 2,
 }
-impl TokenTypeEnum {
+impl BetOutcomeEnum {
     /// String value of the enum field names used in the ProtoBuf definition.
     ///
     /// The values are not transformed in any way and thus are considered stable
     /// (if the ProtoBuf definition does not change) and safe for programmatic use.
     pub fn as_str_name(&self) -> &'static str {
         match self {
-            TokenTypeEnum::TokenTypeUsdc => "TOKEN_TYPE_USDC",
-            TokenTypeEnum::TokenTypePoints => "TOKEN_TYPE_POINTS",
+            BetOutcomeEnum::BetOutcomeWon => "BET_OUTCOME_WON",
+            BetOutcomeEnum::BetOutcomeLost => "BET_OUTCOME_LOST",
+            BetOutcomeEnum::BetOutcomeRefunded => "BET_OUTCOME_REFUNDED",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
     pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
         match value {
-            "TOKEN_TYPE_USDC" => Some(Self::TokenTypeUsdc),
-            "TOKEN_TYPE_POINTS" => Some(Self::TokenTypePoints),
+            "BET_OUTCOME_WON" => Some(Self::BetOutcomeWon),
+            "BET_OUTCOME_LOST" => Some(Self::BetOutcomeLost),
+            "BET_OUTCOME_REFUNDED" => Some(Self::BetOutcomeRefunded),
             _ => None,
         }
     }
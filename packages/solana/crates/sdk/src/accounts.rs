@@ -0,0 +1,123 @@
+//! Fetching and decoding trump-fun accounts
+
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::{AccountDeserialize, Discriminator, Result};
+use trump_fun::{
+    Bet, BettingPoolsState, LmsrMarket, LmsrPosition, MintConfig, Offer, OfferMatch, PoolBond,
+    PoolMetadata, PoolState, PoolTotals, Session, UserBalance, UserNonce,
+};
+
+use crate::pda;
+
+/// Anything that can look up the raw data of an account, e.g. an RPC client or a
+/// snapshot of a test bank
+pub trait AccountSource {
+    /// Data of the account at `address`, or `None` if it doesn't exist
+    fn account_data(&self, address: &Pubkey) -> Option<Vec<u8>>;
+}
+
+/// Decode a Borsh account, checking its discriminator
+pub fn decode<T: AccountDeserialize>(data: &[u8]) -> Result<T> {
+    T::try_deserialize(&mut &data[..])
+}
+
+/// Decode a zero-copy `PoolState`, checking its discriminator
+pub fn decode_pool(data: &[u8]) -> Result<PoolState> {
+    let body = data
+        .strip_prefix(PoolState::DISCRIMINATOR)
+        .ok_or(ErrorCode::AccountDiscriminatorMismatch)?;
+    let size = std::mem::size_of::<PoolState>();
+    if body.len() < size {
+        return Err(ErrorCode::AccountDidNotDeserialize.into());
+    }
+    Ok(bytemuck::pod_read_unaligned(&body[..size]))
+}
+
+/// Fetch and decode a Borsh account
+pub fn fetch<T: AccountDeserialize>(source: &impl AccountSource, address: &Pubkey) -> Result<T> {
+    let data = source
+        .account_data(address)
+        .ok_or(ErrorCode::AccountNotInitialized)?;
+    decode(&data)
+}
+
+pub fn fetch_betting_pools(source: &impl AccountSource) -> Result<BettingPoolsState> {
+    fetch(source, &pda::betting_pools())
+}
+
+pub fn fetch_pool(source: &impl AccountSource, pool_id: u64) -> Result<PoolState> {
+    let data = source
+        .account_data(&pda::pool(pool_id))
+        .ok_or(ErrorCode::AccountNotInitialized)?;
+    decode_pool(&data)
+}
+
+pub fn fetch_pool_metadata(source: &impl AccountSource, pool_id: u64) -> Result<PoolMetadata> {
+    fetch(source, &pda::pool_metadata(pool_id))
+}
+
+pub fn fetch_bet(source: &impl AccountSource, pool_id: u64, bet_id: u64) -> Result<Bet> {
+    fetch(source, &pda::bet(pool_id, bet_id))
+}
+
+pub fn fetch_mint_config(source: &impl AccountSource, mint: &Pubkey) -> Result<MintConfig> {
+    fetch(source, &pda::mint_config(mint))
+}
+
+pub fn fetch_pool_totals(
+    source: &impl AccountSource,
+    pool_id: u64,
+    mint: &Pubkey,
+) -> Result<PoolTotals> {
+    fetch(source, &pda::pool_totals(pool_id, mint))
+}
+
+pub fn fetch_market(source: &impl AccountSource, pool_id: u64) -> Result<LmsrMarket> {
+    fetch(source, &pda::market(pool_id))
+}
+
+pub fn fetch_position(
+    source: &impl AccountSource,
+    pool_id: u64,
+    owner: &Pubkey,
+) -> Result<LmsrPosition> {
+    fetch(source, &pda::position(pool_id, owner))
+}
+
+pub fn fetch_offer(source: &impl AccountSource, pool_id: u64, offer_id: u64) -> Result<Offer> {
+    fetch(source, &pda::offer(pool_id, offer_id))
+}
+
+pub fn fetch_offer_match(
+    source: &impl AccountSource,
+    pool_id: u64,
+    offer_id: u64,
+    match_id: u64,
+) -> Result<OfferMatch> {
+    fetch(source, &pda::offer_match(pool_id, offer_id, match_id))
+}
+
+pub fn fetch_pool_bond(source: &impl AccountSource, pool_id: u64) -> Result<PoolBond> {
+    fetch(source, &pda::pool_bond(pool_id))
+}
+
+pub fn fetch_user_nonce(source: &impl AccountSource, bettor: &Pubkey) -> Result<UserNonce> {
+    fetch(source, &pda::user_nonce(bettor))
+}
+
+pub fn fetch_session(
+    source: &impl AccountSource,
+    owner: &Pubkey,
+    session_key: &Pubkey,
+) -> Result<Session> {
+    fetch(source, &pda::session(owner, session_key))
+}
+
+pub fn fetch_user_balance(
+    source: &impl AccountSource,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Result<UserBalance> {
+    fetch(source, &pda::user_balance(owner, mint))
+}
@@ -0,0 +1,100 @@
+//! Decoding the events the program emits through `emit_cpi!`.
+//!
+//! Each event is the data of an inner instruction the program sends to itself:
+//! `EVENT_IX_TAG_LE`, the event's discriminator, then its Borsh fields
+
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::CompiledInstruction;
+use anchor_lang::{AnchorDeserialize, Discriminator};
+
+macro_rules! program_events {
+    ($($name:ident),* $(,)?) => {
+        /// Any event emitted by the program
+        pub enum ProgramEvent {
+            $($name(trump_fun::$name),)*
+        }
+
+        /// Decode an event from its discriminator and Borsh fields.
+        /// Fields appended by newer event versions are ignored
+        pub fn decode_event(data: &[u8]) -> Option<ProgramEvent> {
+            $(
+                if let Some(mut fields) = data.strip_prefix(trump_fun::$name::DISCRIMINATOR) {
+                    return trump_fun::$name::deserialize(&mut fields)
+                        .ok()
+                        .map(ProgramEvent::$name);
+                }
+            )*
+            None
+        }
+    };
+}
+
+program_events!(
+    PoolCreated,
+    BetPlaced,
+    BetCommitted,
+    BetRevealed,
+    PoolClosed,
+    BetSettled,
+    PayoutClaimed,
+    PoolImageSet,
+    AccountMigrated,
+    TreasurySet,
+    VaultDrained,
+    ProgramClosed,
+    ProgramInitialized,
+    MintAdded,
+    MintUpdated,
+    MarketCreated,
+    SharesBought,
+    SharesRedeemed,
+    MarketSurplusWithdrawn,
+    PoolUpdated,
+    BetsCloseAtExtended,
+    BalanceDeposited,
+    BalanceWithdrawn,
+    BalanceCredited,
+    SessionCreated,
+    SessionRevoked,
+    BetRelayed,
+    PoolBondSet,
+    PoolProposed,
+    PoolFlaggedInvalid,
+    BondReturned,
+    OfferCreated,
+    OfferAccepted,
+    OfferCancelled,
+    MatchClaimed,
+    SolvencyReport,
+    ClaimWindowSet,
+    UnclaimedSwept,
+    ExpiredClaimsSwept,
+    PoolSettled,
+    EmergencyWithdrawn,
+    MatchEmergencyWithdrawn,
+);
+
+/// Decode the event carried by an instruction, if it is one of the program's
+/// self-CPI events
+pub fn parse_cpi_event(program_id: &Pubkey, data: &[u8]) -> Option<ProgramEvent> {
+    if *program_id != trump_fun::ID {
+        return None;
+    }
+    decode_event(data.strip_prefix(EVENT_IX_TAG_LE)?)
+}
+
+/// Decode every event in a transaction's inner instructions, in emission order.
+/// `account_keys` are the transaction's keys, including any loaded from lookup tables
+pub fn parse_inner_instructions<'a>(
+    account_keys: &[Pubkey],
+    instructions: impl IntoIterator<Item = &'a CompiledInstruction>,
+) -> Vec<ProgramEvent> {
+    instructions
+        .into_iter()
+        .filter_map(|instruction| {
+            let program_id = account_keys.get(instruction.program_id_index as usize)?;
+            parse_cpi_event(program_id, &instruction.data)
+        })
+        .collect()
+}
@@ -0,0 +1,1162 @@
+//! Typed builders for every trump-fun instruction.
+//!
+//! Builders derive the program's PDAs from ids the caller already knows, e.g.
+//! `BettingPoolsState::next_pool_id` for a new pool or `PoolState::next_bet_id` for a new bet.
+//! Mints with a transfer hook need the hook's extra accounts appended to the returned
+//! instruction's accounts
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::{system_program, sysvar};
+use anchor_lang::{InstructionData, ToAccountMetas};
+use anchor_spl::associated_token;
+use trump_fun::{accounts, instruction, HiddenBetsConfig, PoolMode, TimeWeighting};
+
+use crate::pda;
+
+/// A mint and the user's token account on one side of a transfer to or from a vault
+#[derive(Clone, Copy, Debug)]
+pub struct TokenAccounts {
+    pub mint: Pubkey,
+    /// The user's token account for `mint`
+    pub token_account: Pubkey,
+    /// Token or Token-2022 program that owns `mint`
+    pub token_program: Pubkey,
+}
+
+/// Settings of a new pool, shared by `create_pool` and `propose_pool`
+#[derive(Clone)]
+pub struct PoolParams {
+    pub question: String,
+    pub options: [String; 2],
+    pub bets_close_at: i64,
+    pub original_truth_social_post_id: String,
+    pub image_url: String,
+    pub hidden_bets: Option<HiddenBetsConfig>,
+    pub time_weighting: Option<TimeWeighting>,
+}
+
+fn build(accounts: impl ToAccountMetas, data: impl InstructionData) -> Instruction {
+    Instruction {
+        program_id: trump_fun::ID,
+        accounts: accounts.to_account_metas(None),
+        data: data.data(),
+    }
+}
+
+pub fn initialize(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::Initialize {
+            betting_pools: pda::betting_pools(),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::Initialize {},
+    )
+}
+
+pub fn add_mint(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+    payout_fee_bp: u16,
+    min_bet: u64,
+) -> Instruction {
+    build(
+        accounts::AddMint {
+            betting_pools: pda::betting_pools(),
+            mint: *mint,
+            mint_config: pda::mint_config(mint),
+            vault: pda::vault(mint),
+            authority: *authority,
+            token_program: *token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::AddMint {
+            payout_fee_bp,
+            min_bet,
+        },
+    )
+}
+
+pub fn update_mint(
+    authority: &Pubkey,
+    mint: &Pubkey,
+    payout_fee_bp: u16,
+    min_bet: u64,
+    is_active: bool,
+) -> Instruction {
+    build(
+        accounts::UpdateMint {
+            betting_pools: pda::betting_pools(),
+            mint_config: pda::mint_config(mint),
+            authority: *authority,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::UpdateMint {
+            payout_fee_bp,
+            min_bet,
+            is_active,
+        },
+    )
+}
+
+/// `pool_id` must be the program's current `next_pool_id`
+pub fn create_pool(authority: &Pubkey, pool_id: u64, params: PoolParams) -> Instruction {
+    build(
+        accounts::CreatePool {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            pool_metadata: pda::pool_metadata(pool_id),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::CreatePool {
+            question: params.question,
+            options: params.options,
+            bets_close_at: params.bets_close_at,
+            original_truth_social_post_id: params.original_truth_social_post_id,
+            image_url: params.image_url,
+            hidden_bets: params.hidden_bets,
+            time_weighting: params.time_weighting,
+        },
+    )
+}
+
+pub fn set_pool_bond(authority: &Pubkey, mint: &Pubkey, amount: u64) -> Instruction {
+    build(
+        accounts::SetPoolBond {
+            betting_pools: pda::betting_pools(),
+            mint: *mint,
+            mint_config: pda::mint_config(mint),
+            authority: *authority,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::SetPoolBond { amount },
+    )
+}
+
+/// `pool_id` must be the program's current `next_pool_id`. The bond is paid in the
+/// program's configured bond mint
+pub fn propose_pool(
+    creator: &Pubkey,
+    pool_id: u64,
+    bond: &TokenAccounts,
+    params: PoolParams,
+) -> Instruction {
+    build(
+        accounts::ProposePool {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            pool_metadata: pda::pool_metadata(pool_id),
+            pool_bond: pda::pool_bond(pool_id),
+            creator: *creator,
+            mint: bond.mint,
+            mint_config: pda::mint_config(&bond.mint),
+            creator_token_account: bond.token_account,
+            program_token_account: pda::vault(&bond.mint),
+            token_program: bond.token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::ProposePool {
+            question: params.question,
+            options: params.options,
+            bets_close_at: params.bets_close_at,
+            original_truth_social_post_id: params.original_truth_social_post_id,
+            image_url: params.image_url,
+            hidden_bets: params.hidden_bets,
+            time_weighting: params.time_weighting,
+        },
+    )
+}
+
+/// `bond_mint` is the mint the pool's bond was posted in and `mints` every mint the pool
+/// has bets in, whose payouts are frozen as refunds
+pub fn flag_pool_invalid(
+    authority: &Pubkey,
+    pool_id: u64,
+    bond_mint: &Pubkey,
+    mints: &[Pubkey],
+) -> Instruction {
+    let mut instruction = build(
+        accounts::FlagPoolInvalid {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            pool_bond: pda::pool_bond(pool_id),
+            mint_config: pda::mint_config(bond_mint),
+            authority: *authority,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::FlagPoolInvalid {},
+    );
+    instruction.accounts.extend(totals_accounts(pool_id, mints));
+    instruction
+}
+
+/// `bond.token_account` must belong to the pool's creator
+pub fn return_bond(pool_id: u64, bond: &TokenAccounts) -> Instruction {
+    build(
+        accounts::ReturnBond {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            pool_bond: pda::pool_bond(pool_id),
+            mint: bond.mint,
+            mint_config: pda::mint_config(&bond.mint),
+            creator_token_account: bond.token_account,
+            program_token_account: pda::vault(&bond.mint),
+            token_program: bond.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::ReturnBond {},
+    )
+}
+
+fn place_bet_accounts(
+    bettor: &Pubkey,
+    session_owner: Option<&Pubkey>,
+    pool_id: u64,
+    bet_id: u64,
+    stake: &TokenAccounts,
+) -> accounts::PlaceBet {
+    accounts::PlaceBet {
+        betting_pools: pda::betting_pools(),
+        pool: pda::pool(pool_id),
+        bet: pda::bet(pool_id, bet_id),
+        bettor: *bettor,
+        session: session_owner.map(|owner| pda::session(owner, bettor)),
+        mint: stake.mint,
+        mint_config: pda::mint_config(&stake.mint),
+        pool_totals: pda::pool_totals(pool_id, &stake.mint),
+        bettor_token_account: stake.token_account,
+        program_token_account: pda::vault(&stake.mint),
+        token_program: stake.token_program,
+        associated_token_program: associated_token::ID,
+        system_program: system_program::ID,
+        rent: sysvar::rent::ID,
+        event_authority: pda::event_authority(),
+        program: trump_fun::ID,
+    }
+}
+
+/// `bet_id` must be the pool's current `next_bet_id`. When `bettor` is a session key,
+/// `session_owner` is the wallet it bets for and `stake.token_account` is that wallet's
+pub fn place_bet(
+    bettor: &Pubkey,
+    session_owner: Option<&Pubkey>,
+    pool_id: u64,
+    bet_id: u64,
+    stake: &TokenAccounts,
+    option_index: u64,
+    amount: u64,
+) -> Instruction {
+    build(
+        place_bet_accounts(bettor, session_owner, pool_id, bet_id, stake),
+        instruction::PlaceBet {
+            option_index,
+            amount,
+        },
+    )
+}
+
+/// Bet on a hidden pool. `commitment` is `trump_fun::bet_commitment` of the option and salt
+pub fn commit_bet(
+    bettor: &Pubkey,
+    session_owner: Option<&Pubkey>,
+    pool_id: u64,
+    bet_id: u64,
+    stake: &TokenAccounts,
+    commitment: [u8; 32],
+    amount: u64,
+) -> Instruction {
+    build(
+        place_bet_accounts(bettor, session_owner, pool_id, bet_id, stake),
+        instruction::CommitBet { commitment, amount },
+    )
+}
+
+pub fn create_session(
+    owner: &Pubkey,
+    session_key: &Pubkey,
+    spend_cap: u64,
+    expires_at: i64,
+    allowed_mints: Vec<Pubkey>,
+) -> Instruction {
+    build(
+        accounts::CreateSession {
+            session: pda::session(owner, session_key),
+            owner: *owner,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::CreateSession {
+            session_key: *session_key,
+            spend_cap,
+            expires_at,
+            allowed_mints,
+        },
+    )
+}
+
+pub fn revoke_session(owner: &Pubkey, session_key: &Pubkey) -> Instruction {
+    build(
+        accounts::RevokeSession {
+            session: pda::session(owner, session_key),
+            owner: *owner,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::RevokeSession {},
+    )
+}
+
+/// Relay a bet `bettor` signed off-chain. The transaction must also carry an ed25519
+/// program instruction verifying the bettor's signature over
+/// `trump_fun::bet_intent_message`, placed right before this one
+pub fn place_bet_signed(
+    relayer: &Pubkey,
+    bettor: &Pubkey,
+    pool_id: u64,
+    bet_id: u64,
+    stake: &TokenAccounts,
+    intent: instruction::PlaceBetSigned,
+) -> Instruction {
+    build(
+        accounts::PlaceBetSigned {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            bet: pda::bet(pool_id, bet_id),
+            bettor: *bettor,
+            user_nonce: pda::user_nonce(bettor),
+            relayer: *relayer,
+            mint: stake.mint,
+            mint_config: pda::mint_config(&stake.mint),
+            pool_totals: pda::pool_totals(pool_id, &stake.mint),
+            bettor_token_account: stake.token_account,
+            program_token_account: pda::vault(&stake.mint),
+            instructions: sysvar::instructions::ID,
+            token_program: stake.token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        intent,
+    )
+}
+
+pub fn reveal_bet(
+    bettor: &Pubkey,
+    pool_id: u64,
+    bet_id: u64,
+    mint: &Pubkey,
+    option_index: u64,
+    salt: [u8; 32],
+) -> Instruction {
+    build(
+        accounts::RevealBet {
+            pool: pda::pool(pool_id),
+            bet: pda::bet(pool_id, bet_id),
+            bettor: *bettor,
+            pool_totals: pda::pool_totals(pool_id, mint),
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::RevealBet { option_index, salt },
+    )
+}
+
+/// Grade a pool and freeze the payouts of its bets in each of `mints`, which must be
+/// every mint the pool has bets in
+pub fn grade_bet(
+    authority: &Pubkey,
+    pool_id: u64,
+    response_option: u64,
+    mints: &[Pubkey],
+) -> Instruction {
+    let mut instruction = build(
+        accounts::GradeBet {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::GradeBet { response_option },
+    );
+    instruction.accounts.extend(totals_accounts(pool_id, mints));
+    instruction
+}
+
+/// A pool's totals in each of `mints`, each followed by its mint's config
+fn totals_accounts(pool_id: u64, mints: &[Pubkey]) -> impl Iterator<Item = AccountMeta> + '_ {
+    mints.iter().flat_map(move |mint| {
+        [
+            AccountMeta::new(pda::pool_totals(pool_id, mint), false),
+            AccountMeta::new_readonly(pda::mint_config(mint), false),
+        ]
+    })
+}
+
+/// `payout.token_account` must belong to the bet's owner, which is `session_owner`
+/// when `bettor` is a session key
+pub fn claim_payout(
+    bettor: &Pubkey,
+    session_owner: Option<&Pubkey>,
+    pool_id: u64,
+    bet_id: u64,
+    payout: &TokenAccounts,
+) -> Instruction {
+    build(
+        accounts::ClaimPayout {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            bet: pda::bet(pool_id, bet_id),
+            bettor: *bettor,
+            session: session_owner.map(|owner| pda::session(owner, bettor)),
+            mint: payout.mint,
+            mint_config: pda::mint_config(&payout.mint),
+            pool_totals: pda::pool_totals(pool_id, &payout.mint),
+            bettor_token_account: payout.token_account,
+            program_token_account: pda::vault(&payout.mint),
+            token_program: payout.token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::ClaimPayout {},
+    )
+}
+
+/// `refund.token_account` must belong to `owner`
+pub fn emergency_withdraw(
+    owner: &Pubkey,
+    pool_id: u64,
+    bet_id: u64,
+    refund: &TokenAccounts,
+) -> Instruction {
+    build(
+        accounts::EmergencyWithdraw {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            bet: pda::bet(pool_id, bet_id),
+            owner: *owner,
+            mint: refund.mint,
+            mint_config: pda::mint_config(&refund.mint),
+            pool_totals: pda::pool_totals(pool_id, &refund.mint),
+            bettor_token_account: refund.token_account,
+            program_token_account: pda::vault(&refund.mint),
+            token_program: refund.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::EmergencyWithdraw {},
+    )
+}
+
+/// `maker` and `taker` are the accounts each side's stake is returned to
+pub fn emergency_withdraw_match(
+    claimant: &Pubkey,
+    pool_id: u64,
+    offer_id: u64,
+    match_id: u64,
+    maker: &TokenAccounts,
+    taker: &TokenAccounts,
+) -> Instruction {
+    build(
+        accounts::EmergencyWithdrawMatch {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            offer_match: pda::offer_match(pool_id, offer_id, match_id),
+            claimant: *claimant,
+            mint: maker.mint,
+            mint_config: pda::mint_config(&maker.mint),
+            maker_token_account: maker.token_account,
+            taker_token_account: taker.token_account,
+            program_token_account: pda::vault(&maker.mint),
+            token_program: maker.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::EmergencyWithdrawMatch {},
+    )
+}
+
+pub fn deposit(owner: &Pubkey, from: &TokenAccounts, amount: u64) -> Instruction {
+    build(
+        accounts::Deposit {
+            betting_pools: pda::betting_pools(),
+            user_balance: pda::user_balance(owner, &from.mint),
+            owner: *owner,
+            mint: from.mint,
+            mint_config: pda::mint_config(&from.mint),
+            owner_token_account: from.token_account,
+            program_token_account: pda::vault(&from.mint),
+            token_program: from.token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::Deposit { amount },
+    )
+}
+
+pub fn withdraw(owner: &Pubkey, to: &TokenAccounts, amount: u64) -> Instruction {
+    build(
+        accounts::Withdraw {
+            betting_pools: pda::betting_pools(),
+            user_balance: pda::user_balance(owner, &to.mint),
+            owner: *owner,
+            mint: to.mint,
+            mint_config: pda::mint_config(&to.mint),
+            owner_token_account: to.token_account,
+            program_token_account: pda::vault(&to.mint),
+            token_program: to.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::Withdraw { amount },
+    )
+}
+
+pub fn close_balance(owner: &Pubkey, mint: &Pubkey) -> Instruction {
+    build(
+        accounts::CloseBalance {
+            betting_pools: pda::betting_pools(),
+            user_balance: pda::user_balance(owner, mint),
+            owner: *owner,
+        },
+        instruction::CloseBalance {},
+    )
+}
+
+/// `bet_id` must be the pool's current `next_bet_id`
+pub fn place_bet_from_balance(
+    bettor: &Pubkey,
+    pool_id: u64,
+    bet_id: u64,
+    mint: &Pubkey,
+    option_index: u64,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::PlaceBetFromBalance {
+            pool: pda::pool(pool_id),
+            bet: pda::bet(pool_id, bet_id),
+            bettor: *bettor,
+            user_balance: pda::user_balance(bettor, mint),
+            mint_config: pda::mint_config(mint),
+            pool_totals: pda::pool_totals(pool_id, mint),
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::PlaceBetFromBalance {
+            option_index,
+            amount,
+        },
+    )
+}
+
+/// Credit a bet owned by `owner` to their balance. Anyone can pay for and send this
+pub fn claim_payout_to_balance(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    pool_id: u64,
+    bet_id: u64,
+    mint: &Pubkey,
+) -> Instruction {
+    build(
+        accounts::ClaimPayoutToBalance {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            bet: pda::bet(pool_id, bet_id),
+            mint_config: pda::mint_config(mint),
+            pool_totals: pda::pool_totals(pool_id, mint),
+            user_balance: pda::user_balance(owner, mint),
+            payer: *payer,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::ClaimPayoutToBalance {},
+    )
+}
+
+/// Simulate to read a `trump_fun::BetQuote` from the return data.
+/// `has_totals` is false until the pool's first bet in `mint`
+pub fn quote_bet(
+    pool_id: u64,
+    mint: &Pubkey,
+    has_totals: bool,
+    option_index: u64,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::QuoteBet {
+            pool: pda::pool(pool_id),
+            mint_config: pda::mint_config(mint),
+            pool_totals: has_totals.then(|| pda::pool_totals(pool_id, mint)),
+        },
+        instruction::QuoteBet {
+            option_index,
+            amount,
+        },
+    )
+}
+
+/// Simulate to read a `trump_fun::BetQuote` from the return data
+pub fn quote_claim(pool_id: u64, bet_id: u64, mint: &Pubkey) -> Instruction {
+    build(
+        accounts::QuoteClaim {
+            pool: pda::pool(pool_id),
+            bet: pda::bet(pool_id, bet_id),
+            mint_config: pda::mint_config(mint),
+            pool_totals: pda::pool_totals(pool_id, mint),
+        },
+        instruction::QuoteClaim {},
+    )
+}
+
+/// Audit the vault of `mint` against `pools` in increasing id order, each given with its
+/// mode. Simulate to read the `trump_fun::SolvencyReport` event
+pub fn audit_solvency(mint: &Pubkey, pools: &[(u64, PoolMode)], strict: bool) -> Instruction {
+    let mut instruction = build(
+        accounts::AuditSolvency {
+            mint_config: pda::mint_config(mint),
+            program_token_account: pda::vault(mint),
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::AuditSolvency { strict },
+    );
+    for (pool_id, mode) in pools {
+        let pool_funds = match mode {
+            PoolMode::Parimutuel => pda::pool_totals(*pool_id, mint),
+            PoolMode::Lmsr => pda::market(*pool_id),
+        };
+        instruction.accounts.extend([
+            AccountMeta::new_readonly(pda::pool(*pool_id), false),
+            AccountMeta::new_readonly(pool_funds, false),
+        ]);
+    }
+    instruction
+}
+
+pub fn create_market(
+    authority: &Pubkey,
+    pool_id: u64,
+    funding: &TokenAccounts,
+    liquidity: u64,
+) -> Instruction {
+    build(
+        accounts::CreateMarket {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            market: pda::market(pool_id),
+            mint: funding.mint,
+            mint_config: pda::mint_config(&funding.mint),
+            authority_token_account: funding.token_account,
+            program_token_account: pda::vault(&funding.mint),
+            authority: *authority,
+            token_program: funding.token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::CreateMarket { liquidity },
+    )
+}
+
+pub fn buy_shares(
+    buyer: &Pubkey,
+    pool_id: u64,
+    payment: &TokenAccounts,
+    option_index: u64,
+    shares: u64,
+    max_cost: u64,
+) -> Instruction {
+    build(
+        accounts::BuyShares {
+            pool: pda::pool(pool_id),
+            market: pda::market(pool_id),
+            position: pda::position(pool_id, buyer),
+            buyer: *buyer,
+            mint: payment.mint,
+            mint_config: pda::mint_config(&payment.mint),
+            buyer_token_account: payment.token_account,
+            program_token_account: pda::vault(&payment.mint),
+            token_program: payment.token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::BuyShares {
+            option_index,
+            shares,
+            max_cost,
+        },
+    )
+}
+
+pub fn redeem_shares(owner: &Pubkey, pool_id: u64, payout: &TokenAccounts) -> Instruction {
+    build(
+        accounts::RedeemShares {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            market: pda::market(pool_id),
+            position: pda::position(pool_id, owner),
+            owner: *owner,
+            mint: payout.mint,
+            mint_config: pda::mint_config(&payout.mint),
+            owner_token_account: payout.token_account,
+            program_token_account: pda::vault(&payout.mint),
+            token_program: payout.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::RedeemShares {},
+    )
+}
+
+pub fn withdraw_market_surplus(
+    authority: &Pubkey,
+    pool_id: u64,
+    to: &TokenAccounts,
+) -> Instruction {
+    build(
+        accounts::WithdrawMarketSurplus {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            market: pda::market(pool_id),
+            authority: *authority,
+            mint: to.mint,
+            mint_config: pda::mint_config(&to.mint),
+            authority_token_account: to.token_account,
+            program_token_account: pda::vault(&to.mint),
+            token_program: to.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::WithdrawMarketSurplus {},
+    )
+}
+
+/// `offer_id` must be the pool's current `next_offer_id`
+pub fn create_offer(
+    maker: &Pubkey,
+    pool_id: u64,
+    offer_id: u64,
+    stake: &TokenAccounts,
+    option_index: u64,
+    amount: u64,
+    ask: u64,
+) -> Instruction {
+    build(
+        accounts::CreateOffer {
+            pool: pda::pool(pool_id),
+            offer: pda::offer(pool_id, offer_id),
+            maker: *maker,
+            mint: stake.mint,
+            mint_config: pda::mint_config(&stake.mint),
+            maker_token_account: stake.token_account,
+            program_token_account: pda::vault(&stake.mint),
+            token_program: stake.token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::CreateOffer {
+            option_index,
+            stake: amount,
+            ask,
+        },
+    )
+}
+
+/// `match_id` must be the offer's current `next_match_id`
+pub fn accept_offer(
+    taker: &Pubkey,
+    pool_id: u64,
+    offer_id: u64,
+    match_id: u64,
+    stake: &TokenAccounts,
+    amount: u64,
+) -> Instruction {
+    build(
+        accounts::AcceptOffer {
+            pool: pda::pool(pool_id),
+            offer: pda::offer(pool_id, offer_id),
+            offer_match: pda::offer_match(pool_id, offer_id, match_id),
+            pool_totals: pda::pool_totals(pool_id, &stake.mint),
+            taker: *taker,
+            mint: stake.mint,
+            mint_config: pda::mint_config(&stake.mint),
+            taker_token_account: stake.token_account,
+            program_token_account: pda::vault(&stake.mint),
+            token_program: stake.token_program,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::AcceptOffer { amount },
+    )
+}
+
+pub fn cancel_offer(
+    maker: &Pubkey,
+    pool_id: u64,
+    offer_id: u64,
+    refund: &TokenAccounts,
+) -> Instruction {
+    build(
+        accounts::CancelOffer {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            offer: pda::offer(pool_id, offer_id),
+            maker: *maker,
+            mint: refund.mint,
+            mint_config: pda::mint_config(&refund.mint),
+            maker_token_account: refund.token_account,
+            program_token_account: pda::vault(&refund.mint),
+            token_program: refund.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::CancelOffer {},
+    )
+}
+
+/// Claim a matched offer's payout as either its maker or its taker
+pub fn claim_match(
+    claimant: &Pubkey,
+    pool_id: u64,
+    offer_id: u64,
+    match_id: u64,
+    payout: &TokenAccounts,
+) -> Instruction {
+    build(
+        accounts::ClaimMatch {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            offer_match: pda::offer_match(pool_id, offer_id, match_id),
+            pool_totals: pda::pool_totals(pool_id, &payout.mint),
+            claimant: *claimant,
+            mint: payout.mint,
+            mint_config: pda::mint_config(&payout.mint),
+            claimant_token_account: payout.token_account,
+            program_token_account: pda::vault(&payout.mint),
+            token_program: payout.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::ClaimMatch {},
+    )
+}
+
+pub fn update_pool(
+    authority: &Pubkey,
+    pool_id: u64,
+    question: String,
+    options: [String; 2],
+) -> Instruction {
+    build(
+        accounts::UpdatePool {
+            pool: pda::pool(pool_id),
+            pool_metadata: pda::pool_metadata(pool_id),
+            betting_pools: pda::betting_pools(),
+            authority: *authority,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::UpdatePool { question, options },
+    )
+}
+
+pub fn extend_bets_close_at(authority: &Pubkey, pool_id: u64, bets_close_at: i64) -> Instruction {
+    build(
+        accounts::ExtendBetsCloseAt {
+            pool: pda::pool(pool_id),
+            betting_pools: pda::betting_pools(),
+            authority: *authority,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::ExtendBetsCloseAt { bets_close_at },
+    )
+}
+
+pub fn set_image(authority: &Pubkey, pool_id: u64, image_url: String) -> Instruction {
+    build(
+        accounts::SetImage {
+            pool_metadata: pda::pool_metadata(pool_id),
+            betting_pools: pda::betting_pools(),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::SetImage { image_url },
+    )
+}
+
+pub fn set_treasury(authority: &Pubkey, treasury: &Pubkey) -> Instruction {
+    build(
+        accounts::SetTreasury {
+            betting_pools: pda::betting_pools(),
+            authority: *authority,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::SetTreasury {
+            treasury: *treasury,
+        },
+    )
+}
+
+pub fn set_claim_window(authority: &Pubkey, claim_window: i64) -> Instruction {
+    build(
+        accounts::SetClaimWindow {
+            betting_pools: pda::betting_pools(),
+            authority: *authority,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::SetClaimWindow { claim_window },
+    )
+}
+
+/// `treasury` is the treasury's token account for the mint being swept
+pub fn sweep_unclaimed(authority: &Pubkey, pool_id: u64, treasury: &TokenAccounts) -> Instruction {
+    build(
+        accounts::SweepUnclaimed {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            mint: treasury.mint,
+            mint_config: pda::mint_config(&treasury.mint),
+            pool_totals: pda::pool_totals(pool_id, &treasury.mint),
+            program_token_account: pda::vault(&treasury.mint),
+            treasury_token_account: treasury.token_account,
+            authority: *authority,
+            token_program: treasury.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::SweepUnclaimed {},
+    )
+}
+
+/// `claims` are the pool's `OfferMatch`, `LmsrPosition` and `PoolBond` accounts left
+/// unclaimed in the treasury's mint. `market` passes the pool's LMSR market, needed
+/// for positions
+pub fn sweep_expired_claims(
+    authority: &Pubkey,
+    pool_id: u64,
+    treasury: &TokenAccounts,
+    market: bool,
+    claims: &[Pubkey],
+) -> Instruction {
+    let mut instruction = build(
+        accounts::SweepExpiredClaims {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            mint: treasury.mint,
+            mint_config: pda::mint_config(&treasury.mint),
+            market: market.then(|| pda::market(pool_id)),
+            program_token_account: pda::vault(&treasury.mint),
+            treasury_token_account: treasury.token_account,
+            authority: *authority,
+            token_program: treasury.token_program,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::SweepExpiredClaims {
+            claims: claims.len() as u8,
+        },
+    );
+    instruction
+        .accounts
+        .extend(claims.iter().map(|claim| AccountMeta::new(*claim, false)));
+    instruction
+}
+
+pub fn mark_pool_settled(pool_id: u64) -> Instruction {
+    build(
+        accounts::MarkPoolSettled {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::MarkPoolSettled {},
+    )
+}
+
+pub fn settle_pool_totals(pool_id: u64, mint: &Pubkey) -> Instruction {
+    build(
+        accounts::SettlePoolTotals {
+            pool: pda::pool(pool_id),
+            pool_totals: pda::pool_totals(pool_id, mint),
+            mint_config: pda::mint_config(mint),
+        },
+        instruction::SettlePoolTotals {},
+    )
+}
+
+/// Decommission the program. `treasury_accounts` lists every accepted mint with the
+/// treasury's token account its vault is drained into
+pub fn close_betting_pool(authority: &Pubkey, treasury_accounts: &[TokenAccounts]) -> Instruction {
+    let mut instruction = build(
+        accounts::CloseBettingPool {
+            betting_pools: pda::betting_pools(),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::CloseBettingPool {},
+    );
+    for to in treasury_accounts {
+        instruction.accounts.extend([
+            AccountMeta::new(pda::mint_config(&to.mint), false),
+            AccountMeta::new(pda::vault(&to.mint), false),
+            AccountMeta::new_readonly(to.mint, false),
+            AccountMeta::new(to.token_account, false),
+            AccountMeta::new_readonly(to.token_program, false),
+        ]);
+    }
+    instruction
+}
+
+pub fn migrate_betting_pools(authority: &Pubkey) -> Instruction {
+    build(
+        accounts::MigrateBettingPools {
+            betting_pools: pda::betting_pools(),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::MigrateBettingPools {},
+    )
+}
+
+/// `legacy_mints` are the betting pools' `legacy_mints`, needed only for pools in the
+/// original layout, whose totals move into those mints' `PoolTotals`. `mints` lists every
+/// mint a pool written before its totals were counted has bets in, and `bet_ids` every bet
+/// of a pool written before claims were counted, in increasing order
+pub fn migrate_pool(
+    authority: &Pubkey,
+    pool_id: u64,
+    legacy_mints: Option<&[Pubkey; 2]>,
+    mints: &[Pubkey],
+    bet_ids: &[u64],
+) -> Instruction {
+    let mut instruction = build(
+        accounts::MigratePool {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            pool_metadata: pda::pool_metadata(pool_id),
+            usdc_totals: legacy_mints.map(|mints| pda::pool_totals(pool_id, &mints[0])),
+            points_totals: legacy_mints.map(|mints| pda::pool_totals(pool_id, &mints[1])),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::MigratePool { pool_id },
+    );
+    instruction.accounts.extend(
+        mints
+            .iter()
+            .map(|mint| AccountMeta::new_readonly(pda::pool_totals(pool_id, mint), false)),
+    );
+    instruction.accounts.extend(
+        bet_ids
+            .iter()
+            .map(|bet_id| AccountMeta::new_readonly(pda::bet(pool_id, *bet_id), false)),
+    );
+    instruction
+}
+
+pub fn migrate_bet(authority: &Pubkey, pool_id: u64, bet_id: u64) -> Instruction {
+    build(
+        accounts::MigrateBet {
+            betting_pools: pda::betting_pools(),
+            pool: pda::pool(pool_id),
+            bet: pda::bet(pool_id, bet_id),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::MigrateBet {
+            _pool_id: pool_id,
+            _bet_id: bet_id,
+        },
+    )
+}
+
+/// `bet_ids` are every bet of the pool in `mint`, in increasing order, for totals from
+/// before their bets were counted. The bets must be migrated first
+pub fn migrate_pool_totals(
+    authority: &Pubkey,
+    pool_id: u64,
+    mint: &Pubkey,
+    bet_ids: &[u64],
+) -> Instruction {
+    let mut instruction = build(
+        accounts::MigratePoolTotals {
+            betting_pools: pda::betting_pools(),
+            pool_totals: pda::pool_totals(pool_id, mint),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::MigratePoolTotals {
+            pool_id,
+            mint: *mint,
+        },
+    );
+    instruction.accounts.extend(
+        bet_ids
+            .iter()
+            .map(|bet_id| AccountMeta::new_readonly(pda::bet(pool_id, *bet_id), false)),
+    );
+    instruction
+}
+
+/// `accounts` are every `UserBalance`, `Offer` and `PoolBond` in the mint, and every
+/// `OfferMatch` followed by its pool, for configs from before those were totalled
+pub fn migrate_mint_config(authority: &Pubkey, mint: &Pubkey, accounts: &[Pubkey]) -> Instruction {
+    let mut instruction = build(
+        accounts::MigrateMintConfig {
+            betting_pools: pda::betting_pools(),
+            mint_config: pda::mint_config(mint),
+            authority: *authority,
+            system_program: system_program::ID,
+            event_authority: pda::event_authority(),
+            program: trump_fun::ID,
+        },
+        instruction::MigrateMintConfig { mint: *mint },
+    );
+    instruction.accounts.extend(
+        accounts
+            .iter()
+            .map(|account| AccountMeta::new_readonly(*account, false)),
+    );
+    instruction
+}
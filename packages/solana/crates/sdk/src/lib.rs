@@ -0,0 +1,12 @@
+//! Off-chain client for the trump-fun program: PDA derivation, instruction builders,
+//! account decoding, event parsing and the program's settlement rules. Instructions and
+//! keys are the `solana-program` types re-exported by `solana-sdk`, so they can be sent
+//! with any Solana client
+
+pub mod accounts;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+
+pub use trump_fun::{self as program, ID};
+pub use trump_fun_settlement as settlement;
@@ -0,0 +1,88 @@
+//! Program-derived addresses used by the trump-fun program
+
+use anchor_lang::prelude::Pubkey;
+use trump_fun::{
+    BALANCE_SEED, BETTING_POOLS_SEED, BET_SEED, MARKET_SEED, MATCH_SEED, MINT_CONFIG_SEED,
+    NONCE_SEED, OFFER_SEED, POOL_BOND_SEED, POOL_METADATA_SEED, POOL_SEED, POOL_TOTALS_SEED,
+    POSITION_SEED, SESSION_SEED, VAULT_SEED,
+};
+
+/// Seed of the PDA that signs `emit_cpi!` events
+const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+fn find(seeds: &[&[u8]]) -> Pubkey {
+    Pubkey::find_program_address(seeds, &trump_fun::ID).0
+}
+
+/// The program's global state, which also owns every vault
+pub fn betting_pools() -> Pubkey {
+    find(&[BETTING_POOLS_SEED])
+}
+
+pub fn pool(pool_id: u64) -> Pubkey {
+    find(&[POOL_SEED, &pool_id.to_le_bytes()])
+}
+
+pub fn pool_metadata(pool_id: u64) -> Pubkey {
+    find(&[POOL_METADATA_SEED, &pool_id.to_le_bytes()])
+}
+
+pub fn bet(pool_id: u64, bet_id: u64) -> Pubkey {
+    find(&[BET_SEED, &pool_id.to_le_bytes(), &bet_id.to_le_bytes()])
+}
+
+pub fn mint_config(mint: &Pubkey) -> Pubkey {
+    find(&[MINT_CONFIG_SEED, mint.as_ref()])
+}
+
+/// Token account holding every stake made in `mint`
+pub fn vault(mint: &Pubkey) -> Pubkey {
+    find(&[VAULT_SEED, mint.as_ref()])
+}
+
+pub fn pool_totals(pool_id: u64, mint: &Pubkey) -> Pubkey {
+    find(&[POOL_TOTALS_SEED, &pool_id.to_le_bytes(), mint.as_ref()])
+}
+
+pub fn market(pool_id: u64) -> Pubkey {
+    find(&[MARKET_SEED, &pool_id.to_le_bytes()])
+}
+
+pub fn position(pool_id: u64, owner: &Pubkey) -> Pubkey {
+    find(&[POSITION_SEED, &pool_id.to_le_bytes(), owner.as_ref()])
+}
+
+pub fn offer(pool_id: u64, offer_id: u64) -> Pubkey {
+    find(&[OFFER_SEED, &pool_id.to_le_bytes(), &offer_id.to_le_bytes()])
+}
+
+pub fn offer_match(pool_id: u64, offer_id: u64, match_id: u64) -> Pubkey {
+    find(&[
+        MATCH_SEED,
+        &pool_id.to_le_bytes(),
+        &offer_id.to_le_bytes(),
+        &match_id.to_le_bytes(),
+    ])
+}
+
+pub fn pool_bond(pool_id: u64) -> Pubkey {
+    find(&[POOL_BOND_SEED, &pool_id.to_le_bytes()])
+}
+
+/// Last nonce used by a bettor's relayed bets
+pub fn user_nonce(bettor: &Pubkey) -> Pubkey {
+    find(&[NONCE_SEED, bettor.as_ref()])
+}
+
+pub fn session(owner: &Pubkey, session_key: &Pubkey) -> Pubkey {
+    find(&[SESSION_SEED, owner.as_ref(), session_key.as_ref()])
+}
+
+pub fn user_balance(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    find(&[BALANCE_SEED, owner.as_ref(), mint.as_ref()])
+}
+
+/// Signer of the self-CPI every event is emitted through
+pub fn event_authority() -> Pubkey {
+    find(&[EVENT_AUTHORITY_SEED])
+}
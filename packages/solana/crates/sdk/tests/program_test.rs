@@ -0,0 +1,356 @@
+//! Drives the program through the SDK's builders in an in-process bank, with the
+//! program running natively and the SPL programs bundled with `solana-program-test`
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::{AccountInfo, Clock, Pubkey};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::AnchorDeserialize;
+use anchor_spl::associated_token::{get_associated_token_address, spl_associated_token_account};
+use anchor_spl::token::spl_token;
+use solana_program_test::{processor, ProgramTest, ProgramTestContext};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::Transaction;
+use trump_fun_sdk::accounts::{self, AccountSource};
+use trump_fun_sdk::events::{self, ProgramEvent};
+use trump_fun_sdk::instructions::{self, PoolParams, TokenAccounts};
+use trump_fun_sdk::pda;
+use trump_fun_sdk::program::{BetOutcome, BetQuote, PoolStatus};
+
+const STAKE: u64 = 10_000_000;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entrypoint ties the slice to the lifetime of the accounts it holds
+    let accounts = unsafe { std::mem::transmute::<&[AccountInfo], &[AccountInfo]>(accounts) };
+    trump_fun::entry(program_id, accounts, data)
+}
+
+/// Accounts copied out of the bank, so the SDK's fetch helpers can read them
+struct Snapshot(HashMap<Pubkey, Vec<u8>>);
+
+impl AccountSource for Snapshot {
+    fn account_data(&self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.0.get(address).cloned()
+    }
+}
+
+async fn snapshot(context: &mut ProgramTestContext, addresses: &[Pubkey]) -> Snapshot {
+    let mut accounts = HashMap::new();
+    for address in addresses {
+        if let Some(account) = context.banks_client.get_account(*address).await.unwrap() {
+            accounts.insert(*address, account.data);
+        }
+    }
+    Snapshot(accounts)
+}
+
+async fn send(
+    context: &mut ProgramTestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+async fn token_balance(context: &mut ProgramTestContext, address: &Pubkey) -> u64 {
+    let account = context
+        .banks_client
+        .get_account(*address)
+        .await
+        .unwrap()
+        .unwrap();
+    spl_token::state::Account::unpack(&account.data)
+        .unwrap()
+        .amount
+}
+
+/// Start a bank with the program initialized and one accepted mint
+async fn setup() -> (ProgramTestContext, Keypair) {
+    let program_test =
+        ProgramTest::new("trump_fun", trump_fun::ID, processor!(process_instruction));
+    let mut context = program_test.start_with_context().await;
+    let authority = context.payer.pubkey();
+
+    let mint = Keypair::new();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    send(
+        &mut context,
+        &[
+            system_instruction::create_account(
+                &authority,
+                &mint.pubkey(),
+                rent.minimum_balance(spl_token::state::Mint::LEN),
+                spl_token::state::Mint::LEN as u64,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::initialize_mint2(
+                &spl_token::ID,
+                &mint.pubkey(),
+                &authority,
+                None,
+                6,
+            )
+            .unwrap(),
+            instructions::initialize(&authority),
+            instructions::add_mint(&authority, &mint.pubkey(), &spl_token::ID, 500, 1),
+        ],
+        &[&mint],
+    )
+    .await;
+
+    (context, mint)
+}
+
+/// A new wallet holding `amount` of `mint`
+async fn funded_user(
+    context: &mut ProgramTestContext,
+    mint: &Pubkey,
+    amount: u64,
+) -> (Keypair, TokenAccounts) {
+    let user = Keypair::new();
+    let payer = context.payer.pubkey();
+    let token_account = get_associated_token_address(&user.pubkey(), mint);
+    send(
+        context,
+        &[
+            system_instruction::transfer(&payer, &user.pubkey(), 1_000_000_000),
+            spl_associated_token_account::instruction::create_associated_token_account(
+                &payer,
+                &user.pubkey(),
+                mint,
+                &spl_token::ID,
+            ),
+            spl_token::instruction::mint_to(
+                &spl_token::ID,
+                mint,
+                &token_account,
+                &payer,
+                &[],
+                amount,
+            )
+            .unwrap(),
+        ],
+        &[],
+    )
+    .await;
+
+    let tokens = TokenAccounts {
+        mint: *mint,
+        token_account,
+        token_program: spl_token::ID,
+    };
+    (user, tokens)
+}
+
+async fn create_pool(context: &mut ProgramTestContext, pool_id: u64) {
+    let clock = context.banks_client.get_sysvar::<Clock>().await.unwrap();
+    let authority = context.payer.pubkey();
+    let params = PoolParams {
+        question: "Will the SDK build every instruction?".to_string(),
+        options: ["Yes".to_string(), "No".to_string()],
+        bets_close_at: clock.unix_timestamp + 3600,
+        original_truth_social_post_id: "1".to_string(),
+        image_url: String::new(),
+        hidden_bets: None,
+        time_weighting: None,
+    };
+    send(
+        context,
+        &[instructions::create_pool(&authority, pool_id, params)],
+        &[],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn bets_are_placed_graded_and_claimed() {
+    let (mut context, mint) = setup().await;
+    let mint = mint.pubkey();
+    let authority = context.payer.pubkey();
+    create_pool(&mut context, 1).await;
+
+    let (winner, winner_tokens) = funded_user(&mut context, &mint, STAKE).await;
+    let (loser, loser_tokens) = funded_user(&mut context, &mint, STAKE).await;
+    send(
+        &mut context,
+        &[
+            instructions::place_bet(&winner.pubkey(), None, 1, 1, &winner_tokens, 0, STAKE),
+            instructions::place_bet(&loser.pubkey(), None, 1, 2, &loser_tokens, 1, STAKE),
+        ],
+        &[&winner, &loser],
+    )
+    .await;
+
+    let source = snapshot(
+        &mut context,
+        &[
+            pda::betting_pools(),
+            pda::pool(1),
+            pda::bet(1, 1),
+            pda::pool_totals(1, &mint),
+        ],
+    )
+    .await;
+    let betting_pools = accounts::fetch_betting_pools(&source).unwrap();
+    assert_eq!(betting_pools.next_pool_id, 2);
+    let pool = accounts::fetch_pool(&source, 1).unwrap();
+    assert_eq!(pool.next_bet_id, 3);
+    assert_eq!(pool.unclaimed_bets, 2);
+    let bet = accounts::fetch_bet(&source, 1, 1).unwrap();
+    assert_eq!(bet.owner, winner.pubkey());
+    assert_eq!(bet.amount, STAKE);
+    let totals = accounts::fetch_pool_totals(&source, 1, &mint).unwrap();
+    assert_eq!(totals.bet_totals, [STAKE, STAKE]);
+    assert!(accounts::fetch_bet(&source, 1, 3).is_err());
+
+    send(
+        &mut context,
+        &[instructions::grade_bet(&authority, 1, 0, &[mint])],
+        &[],
+    )
+    .await;
+
+    // The quote and the claim's events report the same payout: both stakes less the 5% fee
+    let payout = STAKE * 2 - STAKE * 2 * 500 / 10_000;
+    let blockhash = context.banks_client.get_latest_blockhash().await.unwrap();
+    let quote_transaction = Transaction::new_signed_with_payer(
+        &[instructions::quote_claim(1, 1, &mint)],
+        Some(&authority),
+        &[&context.payer],
+        blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(quote_transaction)
+        .await
+        .unwrap();
+    let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+    assert_eq!(return_data.program_id, trump_fun::ID);
+    let quote = BetQuote::deserialize(&mut &return_data.data[..]).unwrap();
+    assert_eq!(quote.payout.amount, payout);
+    assert!(quote.payout.outcome == BetOutcome::Won);
+
+    let claim_transaction = Transaction::new_signed_with_payer(
+        &[instructions::claim_payout(
+            &winner.pubkey(),
+            None,
+            1,
+            1,
+            &winner_tokens,
+        )],
+        Some(&authority),
+        &[&context.payer, &winner],
+        blockhash,
+    );
+    let account_keys = claim_transaction.message.account_keys.clone();
+    let simulation = context
+        .banks_client
+        .simulate_transaction(claim_transaction.clone())
+        .await
+        .unwrap();
+    let inner_instructions = simulation
+        .simulation_details
+        .unwrap()
+        .inner_instructions
+        .unwrap();
+    let parsed = events::parse_inner_instructions(
+        &account_keys,
+        inner_instructions
+            .iter()
+            .flatten()
+            .map(|inner| &inner.instruction),
+    );
+    let settled = parsed
+        .iter()
+        .find_map(|event| match event {
+            ProgramEvent::BetSettled(settled) => Some(settled),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(settled.bet_id, 1);
+    assert_eq!(settled.amount, payout);
+    assert!(parsed.iter().any(
+        |event| matches!(event, ProgramEvent::PayoutClaimed(claimed) if claimed.amount == payout)
+    ));
+
+    context
+        .banks_client
+        .process_transaction(claim_transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        token_balance(&mut context, &winner_tokens.token_account).await,
+        payout
+    );
+
+    let source = snapshot(&mut context, &[pda::pool(1), pda::bet(1, 1)]).await;
+    assert!(accounts::fetch_bet(&source, 1, 1).unwrap().is_withdrawn);
+    assert!(accounts::fetch_pool(&source, 1).unwrap().status() == PoolStatus::Graded);
+}
+
+#[tokio::test]
+async fn balances_fund_bets_without_transfers() {
+    let (mut context, mint) = setup().await;
+    let mint = mint.pubkey();
+    create_pool(&mut context, 1).await;
+
+    let (user, tokens) = funded_user(&mut context, &mint, STAKE * 2).await;
+    send(
+        &mut context,
+        &[
+            instructions::deposit(&user.pubkey(), &tokens, STAKE * 2),
+            instructions::place_bet_from_balance(&user.pubkey(), 1, 1, &mint, 1, STAKE),
+            instructions::withdraw(&user.pubkey(), &tokens, STAKE),
+        ],
+        &[&user],
+    )
+    .await;
+
+    let source = snapshot(
+        &mut context,
+        &[pda::user_balance(&user.pubkey(), &mint), pda::bet(1, 1)],
+    )
+    .await;
+    let balance = accounts::fetch_user_balance(&source, &user.pubkey(), &mint).unwrap();
+    assert_eq!(balance.amount, 0);
+    assert_eq!(accounts::fetch_bet(&source, 1, 1).unwrap().option, 1);
+    assert_eq!(
+        token_balance(&mut context, &tokens.token_account).await,
+        STAKE
+    );
+}
+
+#[test]
+fn events_from_other_programs_are_ignored() {
+    let event = anchor_lang::Event::data(&trump_fun::TreasurySet {
+        treasury: Pubkey::new_unique(),
+    });
+    let data = [anchor_lang::event::EVENT_IX_TAG_LE, &event].concat();
+
+    assert!(matches!(
+        events::parse_cpi_event(&trump_fun::ID, &data),
+        Some(ProgramEvent::TreasurySet(_))
+    ));
+    assert!(events::parse_cpi_event(&Pubkey::new_unique(), &data).is_none());
+    assert!(events::parse_cpi_event(&trump_fun::ID, &event).is_none());
+}
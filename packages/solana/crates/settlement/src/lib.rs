@@ -0,0 +1,188 @@
+//! Settlement rules of trump-fun pools: what each bet pays out once its pool is graded,
+//! and what the program keeps as fees.
+//!
+//! Everything is a pure function over plain integers so the same code runs in the
+//! program, in indexers compiled to wasm and in off-chain clients
+
+#![no_std]
+
+/// Basis points in 100%
+pub const BP_DENOMINATOR: u64 = 10_000;
+
+/// A graded pool's totals in a single mint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pool {
+    /// Option that won. Ignored for a draw
+    pub winning_option: u64,
+    pub is_draw: bool,
+    /// Bets left unrevealed on a hidden pool are forfeited instead of refunded
+    pub forfeit_unrevealed: bool,
+    /// Stakes on each option
+    pub bet_totals: [u64; 2],
+    /// Time-weighted stakes on each option
+    pub weighted_totals: [u64; 2],
+}
+
+/// A single bet in the pool's mint
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bet {
+    pub option: u64,
+    pub amount: u64,
+    /// Time-weighted stake, which is `amount` for pools without time weighting
+    pub weight: u64,
+    /// False for a hidden bet whose option was never revealed
+    pub is_revealed: bool,
+}
+
+/// How a bet was settled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    Refunded,
+}
+
+/// What settling a bet pays out
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Payout {
+    /// Paid to the bettor, after the fee
+    pub amount: u64,
+    /// Kept by the program
+    pub fee: u64,
+    pub outcome: Outcome,
+}
+
+impl Payout {
+    fn refund(amount: u64) -> Self {
+        Payout {
+            amount,
+            fee: 0,
+            outcome: Outcome::Refunded,
+        }
+    }
+
+    fn lost() -> Self {
+        Payout {
+            amount: 0,
+            fee: 0,
+            outcome: Outcome::Lost,
+        }
+    }
+}
+
+/// Fee of `fee_bp` basis points on `amount`, rounded down
+pub fn fee(amount: u64, fee_bp: u16) -> u64 {
+    (amount as u128 * fee_bp as u128 / BP_DENOMINATOR as u128) as u64
+}
+
+/// Whether every bet in the pool is refunded: on a draw, or when no one bet on one of
+/// the options, so there is nothing to win
+pub fn is_refunded(pool: &Pool) -> bool {
+    pool.is_draw || pool.bet_totals[0] == 0 || pool.bet_totals[1] == 0
+}
+
+/// Everything a graded pool needs to pay out its bets in a single mint. The program
+/// freezes it when the pool is graded so claims don't depend on later changes to the fee
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settlement {
+    /// Option that won. Ignored when every bet is refunded
+    pub winning_option: u64,
+    /// Every bet is refunded, see [`is_refunded`]
+    pub is_refund: bool,
+    pub forfeit_unrevealed: bool,
+    /// Winners are paid `payout_numerator / payout_denominator` per unit of weight on top
+    /// of their stake: the losing side's stakes over the winning side's weighted stakes
+    pub payout_numerator: u64,
+    pub payout_denominator: u64,
+    /// Fee charged on winnings and the stake returned with them
+    pub fee_bp: u16,
+}
+
+/// Freeze a graded pool's payout ratio and fee
+pub fn settle(pool: &Pool, fee_bp: u16) -> Settlement {
+    let winning_option = pool.winning_option as usize;
+    let is_refund = is_refunded(pool);
+
+    Settlement {
+        winning_option: pool.winning_option,
+        is_refund,
+        forfeit_unrevealed: pool.forfeit_unrevealed,
+        payout_numerator: if is_refund {
+            0
+        } else {
+            pool.bet_totals[1 - winning_option]
+        },
+        payout_denominator: if is_refund {
+            0
+        } else {
+            pool.weighted_totals[winning_option]
+        },
+        fee_bp,
+    }
+}
+
+/// What a graded pool pays out for a bet. Winners split the losing side in proportion
+/// to their time-weighted stakes, get their own stake back and pay `fee_bp` on the total
+pub fn payout(pool: &Pool, bet: &Bet, fee_bp: u16) -> Payout {
+    settled_payout(&settle(pool, fee_bp), bet)
+}
+
+/// What a bet is paid out under a frozen [`Settlement`]
+pub fn settled_payout(settlement: &Settlement, bet: &Bet) -> Payout {
+    if !bet.is_revealed {
+        return if settlement.forfeit_unrevealed {
+            Payout::lost()
+        } else {
+            Payout::refund(bet.amount)
+        };
+    }
+
+    if settlement.is_refund {
+        return Payout::refund(bet.amount);
+    }
+
+    if bet.option != settlement.winning_option {
+        return Payout::lost();
+    }
+
+    let winnings = (settlement.payout_numerator as u128 * bet.weight as u128
+        / settlement.payout_denominator as u128) as u64;
+    let gross = winnings + bet.amount;
+    let fee = fee(gross, settlement.fee_bp);
+
+    Payout {
+        amount: gross - fee,
+        fee,
+        outcome: Outcome::Won,
+    }
+}
+
+/// Most a graded pool can pay out in a single mint, fees included: the refunded stakes,
+/// or the winning side's stakes and the winnings on their weight. Each bet's winnings
+/// round down, so the payouts and fees of its bets never add up to more.
+/// `unrevealed` is the total staked by bets that were never revealed
+pub fn max_payout(
+    settlement: &Settlement,
+    bet_totals: [u64; 2],
+    weighted_totals: [u64; 2],
+    unrevealed: u64,
+) -> u128 {
+    let unrevealed = if settlement.forfeit_unrevealed {
+        0
+    } else {
+        unrevealed as u128
+    };
+
+    if settlement.is_refund {
+        return bet_totals[0] as u128 + bet_totals[1] as u128 + unrevealed;
+    }
+
+    let winning_option = settlement.winning_option as usize;
+    let winnings = if settlement.payout_denominator == 0 {
+        0
+    } else {
+        settlement.payout_numerator as u128 * weighted_totals[winning_option] as u128
+            / settlement.payout_denominator as u128
+    };
+    bet_totals[winning_option] as u128 + winnings + unrevealed
+}
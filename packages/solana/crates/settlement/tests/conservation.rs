@@ -0,0 +1,140 @@
+//! Property tests: settling every bet in a pool never pays out more than was staked
+
+use proptest::prelude::*;
+use trump_fun_settlement::{
+    fee, max_payout, payout, settle, settled_payout, Bet, Outcome, Pool, BP_DENOMINATOR,
+};
+
+/// A graded pool and the revealed bets that make up its totals
+fn graded_pool() -> impl Strategy<Value = (Pool, Vec<Bet>)> {
+    let bet = (0u64..2, 1u64..1_000_000_000_000, 0u64..=BP_DENOMINATOR);
+    (prop::collection::vec(bet, 1..40), 0u64..2, any::<bool>()).prop_map(
+        |(bets, winning_option, is_draw)| {
+            let bets: Vec<Bet> = bets
+                .into_iter()
+                .map(|(option, amount, bonus_bp)| Bet {
+                    option,
+                    amount,
+                    weight: (amount as u128 * (BP_DENOMINATOR + bonus_bp) as u128
+                        / BP_DENOMINATOR as u128) as u64,
+                    is_revealed: true,
+                })
+                .collect();
+            let mut pool = Pool {
+                winning_option,
+                is_draw,
+                forfeit_unrevealed: false,
+                bet_totals: [0; 2],
+                weighted_totals: [0; 2],
+            };
+            for bet in &bets {
+                pool.bet_totals[bet.option as usize] += bet.amount;
+                pool.weighted_totals[bet.option as usize] += bet.weight;
+            }
+            (pool, bets)
+        },
+    )
+}
+
+proptest! {
+    #[test]
+    fn payouts_and_fees_never_exceed_stakes((pool, bets) in graded_pool(), fee_bp in 0u16..=10_000) {
+        let staked: u128 = bets.iter().map(|bet| bet.amount as u128).sum();
+        let settled: u128 = bets
+            .iter()
+            .map(|bet| {
+                let payout = payout(&pool, bet, fee_bp);
+                payout.amount as u128 + payout.fee as u128
+            })
+            .sum();
+
+        prop_assert!(settled <= staked);
+        // Rounding leaves at most one unit per bet in the vault
+        prop_assert!(staked - settled <= bets.len() as u128);
+    }
+
+    #[test]
+    fn refunded_pools_return_every_stake((mut pool, bets) in graded_pool(), fee_bp in 0u16..=10_000) {
+        pool.is_draw = true;
+        for bet in &bets {
+            let payout = payout(&pool, bet, fee_bp);
+            prop_assert_eq!(payout.outcome, Outcome::Refunded);
+            prop_assert_eq!(payout.amount, bet.amount);
+            prop_assert_eq!(payout.fee, 0);
+        }
+    }
+
+    #[test]
+    fn winners_get_their_stake_and_losers_nothing((mut pool, bets) in graded_pool()) {
+        pool.is_draw = false;
+        let one_sided = pool.bet_totals[0] == 0 || pool.bet_totals[1] == 0;
+        for bet in &bets {
+            let payout = payout(&pool, bet, 0);
+            if one_sided {
+                prop_assert_eq!(payout.outcome, Outcome::Refunded);
+            } else if bet.option == pool.winning_option {
+                prop_assert_eq!(payout.outcome, Outcome::Won);
+                prop_assert!(payout.amount >= bet.amount);
+            } else {
+                prop_assert_eq!(payout.outcome, Outcome::Lost);
+                prop_assert_eq!(payout.amount, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn unrevealed_bets_follow_the_pool_policy(
+        (mut pool, bets) in graded_pool(),
+        forfeit_unrevealed in any::<bool>(),
+    ) {
+        pool.forfeit_unrevealed = forfeit_unrevealed;
+        let bet = Bet { is_revealed: false, ..bets[0] };
+        let payout = payout(&pool, &bet, 500);
+        if forfeit_unrevealed {
+            prop_assert_eq!(payout.outcome, Outcome::Lost);
+            prop_assert_eq!(payout.amount, 0);
+        } else {
+            prop_assert_eq!(payout.outcome, Outcome::Refunded);
+            prop_assert_eq!(payout.amount, bet.amount);
+        }
+    }
+
+    #[test]
+    fn frozen_settlements_pay_like_live_totals((pool, bets) in graded_pool(), fee_bp in 0u16..=10_000) {
+        let settlement = settle(&pool, fee_bp);
+        for bet in &bets {
+            prop_assert_eq!(settled_payout(&settlement, bet), payout(&pool, bet, fee_bp));
+        }
+    }
+
+    #[test]
+    fn max_payout_covers_every_bet(
+        (pool, bets) in graded_pool(),
+        fee_bp in 0u16..=10_000,
+        unrevealed in 0u64..1_000_000_000_000,
+    ) {
+        let settlement = settle(&pool, fee_bp);
+        let settled: u128 = bets
+            .iter()
+            .chain(&[Bet { is_revealed: false, amount: unrevealed, weight: unrevealed, option: 0 }])
+            .map(|bet| {
+                let payout = settled_payout(&settlement, bet);
+                payout.amount as u128 + payout.fee as u128
+            })
+            .sum();
+        let staked: u128 = bets.iter().map(|bet| bet.amount as u128).sum::<u128>() + unrevealed as u128;
+
+        let max = max_payout(&settlement, pool.bet_totals, pool.weighted_totals, unrevealed);
+        prop_assert!(settled <= max);
+        prop_assert!(max <= staked);
+        // Rounding leaves at most one unit per bet unaccounted for
+        prop_assert!(max - settled <= bets.len() as u128);
+    }
+
+    #[test]
+    fn fees_never_exceed_the_amount(amount in any::<u64>(), fee_bp in 0u16..=10_000) {
+        let fee = fee(amount, fee_bp);
+        prop_assert!(fee <= amount);
+        prop_assert_eq!(fee == amount, fee_bp == 10_000 || amount == 0);
+    }
+}
@@ -0,0 +1,2023 @@
+//! Accounts each instruction takes
+
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+use crate::*;
+
+// Initialize context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct Initialize<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + BettingPoolsState::INIT_SPACE,
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// BettingPools state
+// Add mint context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct AddMint<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + MintConfig::INIT_SPACE,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [VAULT_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = betting_pools,
+        token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Update mint context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct UpdateMint<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint_config.mint.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+// Create pool context
+#[derive(Accounts)]
+#[instruction(
+    question: String,
+    options: [String; 2],
+    bets_close_at: i64,
+    original_truth_social_post_id: String,
+    image_url: String
+)]
+#[event_cpi]
+pub struct CreatePool<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PoolState::INIT_SPACE,
+        seeds = [POOL_SEED, betting_pools.next_pool_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + PoolMetadata::INIT_SPACE,
+        seeds = [POOL_METADATA_SEED, betting_pools.next_pool_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_metadata: Account<'info, PoolMetadata>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Set pool bond context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct SetPoolBond<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    // Only registered mints can be held in a vault
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+// Propose pool context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct ProposePool<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PoolState::INIT_SPACE,
+        seeds = [POOL_SEED, betting_pools.next_pool_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PoolMetadata::INIT_SPACE,
+        seeds = [POOL_METADATA_SEED, betting_pools.next_pool_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_metadata: Account<'info, PoolMetadata>,
+
+    #[account(
+        init,
+        payer = creator,
+        space = 8 + PoolBond::INIT_SPACE,
+        seeds = [POOL_BOND_SEED, betting_pools.next_pool_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_bond: Account<'info, PoolBond>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+
+    #[account(
+        address = betting_pools.bond_mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = creator,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub creator_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Flag pool invalid context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct FlagPoolInvalid<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_BOND_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_bond: Account<'info, PoolBond>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, pool_bond.mint.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    pub authority: Signer<'info>,
+}
+
+// Return bond context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct ReturnBond<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_BOND_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_bond: Account<'info, PoolBond>,
+
+    #[account(
+        address = pool_bond.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = pool_bond.creator,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub creator_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Place bet context, shared by `place_bet` and `commit_bet`
+#[derive(Accounts)]
+#[event_cpi]
+pub struct PlaceBet<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = bettor,
+        space = 8 + Bet::INIT_SPACE,
+        seeds = [BET_SEED, pool.load()?.id.to_le_bytes().as_ref(), pool.load()?.next_bet_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    /// The bettor, or a session key acting for them
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [SESSION_SEED, session.owner.as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub session: Option<Account<'info, Session>>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + PoolTotals::INIT_SPACE,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    #[account(
+        mut,
+        constraint = bettor_token_account.owner == acting_owner(&session, &bettor.key()) @ BettingPoolsError::NotBetOwner,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub bettor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+// Create session context
+#[derive(Accounts)]
+#[instruction(session_key: Pubkey)]
+#[event_cpi]
+pub struct CreateSession<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Session::INIT_SPACE,
+        seeds = [SESSION_SEED, owner.key().as_ref(), session_key.as_ref()],
+        bump
+    )]
+    pub session: Account<'info, Session>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Revoke session context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct RevokeSession<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [SESSION_SEED, owner.key().as_ref(), session.session_key.as_ref()],
+        bump,
+        has_one = owner @ BettingPoolsError::NotAuthorized
+    )]
+    pub session: Account<'info, Session>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+// Place signed bet context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct PlaceBetSigned<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = relayer,
+        space = 8 + Bet::INIT_SPACE,
+        seeds = [BET_SEED, pool.load()?.id.to_le_bytes().as_ref(), pool.load()?.next_bet_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    /// CHECK: the bettor proves ownership with the ed25519 signature over the bet intent
+    pub bettor: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + UserNonce::INIT_SPACE,
+        seeds = [NONCE_SEED, bettor.key().as_ref()],
+        bump
+    )]
+    pub user_nonce: Account<'info, UserNonce>,
+
+    #[account(mut)]
+    pub relayer: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = relayer,
+        space = 8 + PoolTotals::INIT_SPACE,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    #[account(
+        mut,
+        token::authority = bettor,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub bettor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: address checked against the instructions sysvar
+    #[account(address = instructions_sysvar::ID)]
+    pub instructions: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Reveal bet context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct RevealBet<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, pool.load()?.id.to_le_bytes().as_ref(), bet.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = bet.owner == bettor.key() @ BettingPoolsError::NotBetOwner
+    )]
+    pub bet: Account<'info, Bet>,
+
+    pub bettor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), bet.mint.as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+}
+
+// Grade bet context
+#[derive(Accounts)]
+#[instruction(response_option: u64)]
+#[event_cpi]
+pub struct GradeBet<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Claim payout context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct ClaimPayout<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, pool.load()?.id.to_le_bytes().as_ref(), bet.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = bet.owner == acting_owner(&session, &bettor.key()) @ BettingPoolsError::NotBetOwner
+    )]
+    pub bet: Account<'info, Bet>,
+
+    /// The bettor, or a session key acting for them
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(
+        seeds = [SESSION_SEED, session.owner.as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub session: Option<Account<'info, Session>>,
+
+    #[account(
+        address = bet.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    #[account(
+        mut,
+        token::authority = bet.owner,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub bettor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Emergency withdraw context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct EmergencyWithdraw<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, pool.load()?.id.to_le_bytes().as_ref(), bet.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = owner @ BettingPoolsError::NotBetOwner
+    )]
+    pub bet: Account<'info, Bet>,
+
+    pub owner: Signer<'info>,
+
+    #[account(
+        address = bet.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    #[account(
+        mut,
+        token::authority = bet.owner,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub bettor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Emergency withdraw match context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct EmergencyWithdrawMatch<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            MATCH_SEED,
+            pool.load()?.id.to_le_bytes().as_ref(),
+            offer_match.offer_id.to_le_bytes().as_ref(),
+            offer_match.id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub offer_match: Account<'info, OfferMatch>,
+
+    pub claimant: Signer<'info>,
+
+    #[account(
+        address = offer_match.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = offer_match.maker,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub maker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::authority = offer_match.taker,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub taker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Deposit context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct Deposit<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + UserBalance::INIT_SPACE,
+        seeds = [BALANCE_SEED, owner.key().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Withdraw context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct Withdraw<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [BALANCE_SEED, owner.key().as_ref(), mint.key().as_ref()],
+        bump,
+        has_one = owner @ BettingPoolsError::NotAuthorized
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+
+    pub owner: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Close balance context
+#[derive(Accounts)]
+pub struct CloseBalance<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        close = owner,
+        seeds = [BALANCE_SEED, owner.key().as_ref(), user_balance.mint.as_ref()],
+        bump,
+        has_one = owner @ BettingPoolsError::NotAuthorized
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+// Place bet from balance context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct PlaceBetFromBalance<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = bettor,
+        space = 8 + Bet::INIT_SPACE,
+        seeds = [BET_SEED, pool.load()?.id.to_le_bytes().as_ref(), pool.load()?.next_bet_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BALANCE_SEED, bettor.key().as_ref(), mint_config.mint.as_ref()],
+        bump
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint_config.mint.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        init_if_needed,
+        payer = bettor,
+        space = 8 + PoolTotals::INIT_SPACE,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), mint_config.mint.as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Claim payout to balance context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct ClaimPayoutToBalance<'info> {
+    /// Only written to count a balance opened by the claim
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [BET_SEED, pool.load()?.id.to_le_bytes().as_ref(), bet.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, bet.mint.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), bet.mint.as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    /// Always the bet owner's own balance, whoever sends the claim
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + UserBalance::INIT_SPACE,
+        seeds = [BALANCE_SEED, bet.owner.as_ref(), bet.mint.as_ref()],
+        bump
+    )]
+    pub user_balance: Account<'info, UserBalance>,
+
+    /// Pays for the owner's balance account if it doesn't exist yet
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Quote bet context
+#[derive(Accounts)]
+pub struct QuoteBet<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint_config.mint.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Missing until the first bet in this mint
+    #[account(
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), mint_config.mint.as_ref()],
+        bump
+    )]
+    pub pool_totals: Option<Account<'info, PoolTotals>>,
+}
+
+// Quote claim context
+#[derive(Accounts)]
+pub struct QuoteClaim<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        seeds = [BET_SEED, pool.load()?.id.to_le_bytes().as_ref(), bet.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub bet: Account<'info, Bet>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, bet.mint.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), bet.mint.as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+}
+
+// Audit solvency context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct AuditSolvency<'info> {
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint_config.mint.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+}
+
+// Create market context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct CreateMarket<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + LmsrMarket::INIT_SPACE,
+        seeds = [MARKET_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, LmsrMarket>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = authority,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Buy shares context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct BuyShares<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, LmsrMarket>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + LmsrPosition::INIT_SPACE,
+        seeds = [POSITION_SEED, pool.load()?.id.to_le_bytes().as_ref(), buyer.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, LmsrPosition>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(
+        address = market.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = buyer,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Redeem shares context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct RedeemShares<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, LmsrMarket>,
+
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, pool.load()?.id.to_le_bytes().as_ref(), owner.key().as_ref()],
+        bump
+    )]
+    pub position: Account<'info, LmsrPosition>,
+
+    pub owner: Signer<'info>,
+
+    #[account(
+        address = market.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = owner,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Withdraw market surplus context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct WithdrawMarketSurplus<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub market: Account<'info, LmsrMarket>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        address = market.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = authority,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub authority_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Create offer context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct CreateOffer<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        init,
+        payer = maker,
+        space = 8 + Offer::INIT_SPACE,
+        seeds = [OFFER_SEED, pool.load()?.id.to_le_bytes().as_ref(), pool.load()?.next_offer_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(mut)]
+    pub maker: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = maker,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub maker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Accept offer context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct AcceptOffer<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [OFFER_SEED, pool.load()?.id.to_le_bytes().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub offer: Account<'info, Offer>,
+
+    #[account(
+        init,
+        payer = taker,
+        space = 8 + OfferMatch::INIT_SPACE,
+        seeds = [
+            MATCH_SEED,
+            pool.load()?.id.to_le_bytes().as_ref(),
+            offer.id.to_le_bytes().as_ref(),
+            offer.next_match_id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub offer_match: Account<'info, OfferMatch>,
+
+    #[account(
+        init_if_needed,
+        payer = taker,
+        space = 8 + PoolTotals::INIT_SPACE,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), offer.mint.as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    #[account(mut)]
+    pub taker: Signer<'info>,
+
+    #[account(
+        address = offer.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = taker,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub taker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+// Cancel offer context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct CancelOffer<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [OFFER_SEED, pool.load()?.id.to_le_bytes().as_ref(), offer.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = maker @ BettingPoolsError::NotBetOwner
+    )]
+    pub offer: Account<'info, Offer>,
+
+    pub maker: Signer<'info>,
+
+    #[account(
+        address = offer.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = maker,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub maker_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Claim match context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct ClaimMatch<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [
+            MATCH_SEED,
+            pool.load()?.id.to_le_bytes().as_ref(),
+            offer_match.offer_id.to_le_bytes().as_ref(),
+            offer_match.id.to_le_bytes().as_ref()
+        ],
+        bump
+    )]
+    pub offer_match: Account<'info, OfferMatch>,
+
+    /// Holds the fee frozen when the pool was graded
+    #[account(
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), offer_match.mint.as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    pub claimant: Signer<'info>,
+
+    #[account(
+        address = offer_match.mint @ BettingPoolsError::InvalidMint,
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        token::authority = claimant,
+        token::mint = mint,
+        token::token_program = token_program
+    )]
+    pub claimant_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Update pool context
+#[derive(Accounts)]
+#[instruction(question: String, options: [String; 2])]
+#[event_cpi]
+pub struct UpdatePool<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_METADATA_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_metadata: Account<'info, PoolMetadata>,
+
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    pub authority: Signer<'info>,
+}
+
+// Extend bets close time context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct ExtendBetsCloseAt<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    pub authority: Signer<'info>,
+}
+
+// Set image context
+#[derive(Accounts)]
+#[instruction(image_url: String)]
+#[event_cpi]
+pub struct SetImage<'info> {
+    #[account(
+        mut,
+        seeds = [POOL_METADATA_SEED, pool_metadata.pool_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_metadata: Account<'info, PoolMetadata>,
+
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Set treasury context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct SetTreasury<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    pub authority: Signer<'info>,
+}
+
+// Set claim window context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct SetClaimWindow<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    pub authority: Signer<'info>,
+}
+
+// Mark pool settled context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct MarkPoolSettled<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+}
+
+// Settle pool totals context
+#[derive(Accounts)]
+pub struct SettlePoolTotals<'info> {
+    #[account(
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), mint_config.mint.as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint_config.mint.as_ref()],
+        bump
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+}
+
+// Sweep unclaimed context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct SweepUnclaimed<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    #[account(
+        mut,
+        seeds = [POOL_TOTALS_SEED, pool.load()?.id.to_le_bytes().as_ref(), mint.key().as_ref()],
+        bump
+    )]
+    pub pool_totals: Account<'info, PoolTotals>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        constraint = treasury_token_account.owner == betting_pools.treasury @ BettingPoolsError::InvalidTreasury
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Sweep expired claims context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct SweepExpiredClaims<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.key().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub mint_config: Account<'info, MintConfig>,
+
+    /// Needed to sweep LMSR positions
+    #[account(
+        mut,
+        seeds = [MARKET_SEED, pool.load()?.id.to_le_bytes().as_ref()],
+        bump,
+        has_one = mint @ BettingPoolsError::InvalidMint
+    )]
+    pub market: Option<Account<'info, LmsrMarket>>,
+
+    #[account(
+        mut,
+        address = mint_config.vault @ BettingPoolsError::InvalidVault
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        constraint = treasury_token_account.owner == betting_pools.treasury @ BettingPoolsError::InvalidTreasury
+    )]
+    pub treasury_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+// Close betting pool context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct CloseBettingPool<'info> {
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized,
+        close = authority
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Migrate betting pools context
+#[derive(Accounts)]
+#[event_cpi]
+pub struct MigrateBettingPools<'info> {
+    /// CHECK: read and upgraded by `migrate_account`, which also checks the discriminator
+    #[account(
+        mut,
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        owner = crate::ID
+    )]
+    pub betting_pools: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Migrate pool context
+#[derive(Accounts)]
+#[instruction(pool_id: u64)]
+#[event_cpi]
+pub struct MigratePool<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    /// CHECK: read and rewritten by `migrate_pool`, which checks the discriminator
+    #[account(
+        mut,
+        seeds = [POOL_SEED, pool_id.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub pool: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PoolMetadata::INIT_SPACE,
+        seeds = [POOL_METADATA_SEED, pool_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub pool_metadata: Account<'info, PoolMetadata>,
+
+    /// Totals for the legacy USDC and points mints, only needed for pools in the
+    /// original layout
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PoolTotals::INIT_SPACE,
+        seeds = [POOL_TOTALS_SEED, pool_id.to_le_bytes().as_ref(), betting_pools.legacy_mints[0].as_ref()],
+        bump
+    )]
+    pub usdc_totals: Option<Account<'info, PoolTotals>>,
+
+    #[account(
+        init_if_needed,
+        payer = authority,
+        space = 8 + PoolTotals::INIT_SPACE,
+        seeds = [POOL_TOTALS_SEED, pool_id.to_le_bytes().as_ref(), betting_pools.legacy_mints[1].as_ref()],
+        bump
+    )]
+    pub points_totals: Option<Account<'info, PoolTotals>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Migrate bet context
+#[derive(Accounts)]
+#[instruction(pool_id: u64, bet_id: u64)]
+#[event_cpi]
+pub struct MigrateBet<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    /// Migrated before its bets, whose weights are computed from it
+    #[account(
+        seeds = [POOL_SEED, pool_id.to_le_bytes().as_ref()],
+        bump,
+        constraint = pool.load()?.version == PoolState::VERSION @ BettingPoolsError::AccountNotMigrated
+    )]
+    pub pool: AccountLoader<'info, PoolState>,
+
+    /// CHECK: read and upgraded by `migrate_account`, which also checks the discriminator
+    #[account(
+        mut,
+        seeds = [BET_SEED, pool_id.to_le_bytes().as_ref(), bet_id.to_le_bytes().as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub bet: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Migrate pool totals context
+#[derive(Accounts)]
+#[instruction(pool_id: u64, mint: Pubkey)]
+#[event_cpi]
+pub struct MigratePoolTotals<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    /// CHECK: read and upgraded by `migrate_account`, which also checks the discriminator
+    #[account(
+        mut,
+        seeds = [POOL_TOTALS_SEED, pool_id.to_le_bytes().as_ref(), mint.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub pool_totals: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Migrate mint config context
+#[derive(Accounts)]
+#[instruction(mint: Pubkey)]
+#[event_cpi]
+pub struct MigrateMintConfig<'info> {
+    #[account(
+        seeds = [BETTING_POOLS_SEED],
+        bump,
+        has_one = authority @ BettingPoolsError::NotAuthorized
+    )]
+    pub betting_pools: Account<'info, BettingPoolsState>,
+
+    /// CHECK: read and upgraded by `migrate_account`, which also checks the discriminator
+    #[account(
+        mut,
+        seeds = [MINT_CONFIG_SEED, mint.as_ref()],
+        bump,
+        owner = crate::ID
+    )]
+    pub mint_config: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
@@ -0,0 +1,143 @@
+//! Errors the program returns
+
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum BettingPoolsError {
+    #[msg("Bets close time must be in the future")]
+    BetsCloseTimeInPast,
+    #[msg("Pool is not open")]
+    PoolNotOpen,
+    #[msg("Betting period is closed")]
+    BettingPeriodClosed,
+    #[msg("Invalid option index")]
+    InvalidOptionIndex,
+    /// No longer returned, as `initialize` creates its account with `init`.
+    /// Kept so the codes after it don't shift
+    #[msg("BettingPools is already initialized")]
+    AlreadyInitialized,
+    #[msg("Zero amount")]
+    ZeroAmount,
+    #[msg("Not authorized")]
+    NotAuthorized,
+    #[msg("Token transfer failed")]
+    TokenTransferFailed,
+    #[msg("Pool is not graded")]
+    PoolNotGraded,
+    #[msg("Grading error")]
+    GradingError,
+    #[msg("Bet already withdrawn")]
+    BetAlreadyWithdrawn,
+    #[msg("Not bet owner")]
+    NotBetOwner,
+    #[msg("Mint is not accepted")]
+    InvalidMint,
+    #[msg("Fee must not exceed 10000 basis points")]
+    InvalidFee,
+    #[msg("Mint is not active")]
+    MintNotActive,
+    #[msg("Bet is below the minimum for this mint")]
+    BetBelowMinimum,
+    #[msg("Vault does not match the mint config")]
+    InvalidVault,
+    #[msg("Account is already at the current version")]
+    AccountAlreadyMigrated,
+    #[msg("Account must be migrated to the current version")]
+    AccountNotMigrated,
+    #[msg("Some pools are pending or have unclaimed bets")]
+    PoolsNotSettled,
+    #[msg("Every accepted mint's vault must be drained")]
+    VaultsNotDrained,
+    #[msg("Token account is not owned by the treasury")]
+    InvalidTreasury,
+    #[msg("Bets on this pool must be committed and revealed")]
+    HiddenPool,
+    #[msg("Pool does not take hidden bets")]
+    PoolNotHidden,
+    #[msg("Reveal phase must end after bets close")]
+    InvalidRevealWindow,
+    #[msg("Reveal phase has not started")]
+    RevealNotOpen,
+    #[msg("Reveal phase is over")]
+    RevealClosed,
+    #[msg("Pool can't be graded during its reveal phase")]
+    RevealInProgress,
+    #[msg("Bet is already revealed")]
+    BetAlreadyRevealed,
+    #[msg("Option and salt don't match the commitment")]
+    InvalidReveal,
+    #[msg("Early bonus must not exceed 10000 basis points")]
+    InvalidTimeWeighting,
+    #[msg("Instruction does not match the pool's mode")]
+    WrongPoolMode,
+    #[msg("Cost is above the maximum")]
+    SlippageExceeded,
+    #[msg("Math overflow")]
+    MathOverflow,
+    #[msg("Offer is cancelled or fully taken")]
+    OfferClosed,
+    #[msg("Amount is more than the offer has left")]
+    OfferExceeded,
+    #[msg("Pool already has bets")]
+    PoolHasBets,
+    #[msg("New close time must be later than the current one")]
+    CloseTimeNotExtended,
+    #[msg("Pool proposals are not enabled")]
+    BondNotConfigured,
+    #[msg("Bond already returned or slashed")]
+    BondAlreadySettled,
+    #[msg("Missing or invalid ed25519 signature over the bet intent")]
+    InvalidSignature,
+    #[msg("Bet intent has expired")]
+    IntentExpired,
+    #[msg("Nonce does not match the user's next nonce")]
+    InvalidNonce,
+    #[msg("Session has expired")]
+    SessionExpired,
+    #[msg("Session is not allowed to bet with this mint")]
+    SessionMintNotAllowed,
+    #[msg("Bet would exceed the session's spend cap")]
+    SessionSpendCapExceeded,
+    #[msg("Too many mints for a session")]
+    TooManySessionMints,
+    #[msg("Internal balance is too low")]
+    InsufficientBalance,
+    #[msg("Internal balance is not empty")]
+    BalanceNotEmpty,
+    #[msg("Users still hold internal balances")]
+    OpenBalances,
+    #[msg("Audit accounts must be each pool and its totals or market in increasing id order")]
+    InvalidAuditAccounts,
+    #[msg("Vault holds less than is owed in its mint")]
+    SolvencyShortfall,
+    #[msg("Settlement accounts must be each of the pool's totals followed by its mint's config")]
+    InvalidSettlementAccounts,
+    #[msg("Claim window must not be negative")]
+    InvalidClaimWindow,
+    #[msg("Claim window has closed")]
+    ClaimWindowExpired,
+    #[msg("Claim window has not closed")]
+    ClaimWindowOpen,
+    #[msg("Pool's unclaimed stakes in this mint are already swept")]
+    PoolAlreadySwept,
+    #[msg("Pool has not gone ungraded long enough for emergency withdrawals")]
+    EmergencyDelayNotElapsed,
+    #[msg("Pools in the original layout need totals accounts for both legacy mints")]
+    MissingLegacyTotals,
+    #[msg("Pool is not graded or still has claims")]
+    PoolNotSettled,
+    #[msg("Pool is already counted as settled")]
+    PoolAlreadySettled,
+    #[msg("Migration accounts are missing, repeated, out of order or not the account's own")]
+    InvalidMigrationAccounts,
+    #[msg("Makers can't take their own offers")]
+    OwnOffer,
+    #[msg("Pool's totals in this mint were not settled when it was graded")]
+    TotalsNotSettled,
+    #[msg("Pool's totals in this mint are already settled")]
+    TotalsAlreadySettled,
+    #[msg("Claim accounts must be unclaimed matches, positions or bonds of the pool in this mint")]
+    InvalidClaimAccounts,
+    #[msg("Stake reached the vault as zero tokens")]
+    ZeroStake,
+}
@@ -0,0 +1,450 @@
+//! Events the program emits through `emit_cpi!`
+
+use anchor_lang::prelude::*;
+
+use crate::*;
+
+#[event]
+pub struct PoolCreated {
+    pub pool_id: u64,
+    pub question: String,
+    pub options: [String; 2],
+    pub bets_close_at: i64,
+    pub original_truth_social_post_id: String,
+    pub image_url: String,
+    pub created_at: i64,
+    /// Zero unless the pool takes hidden bets
+    pub reveal_ends_at: i64,
+    pub forfeit_unrevealed: bool,
+    pub weight_curve: WeightCurve,
+    pub early_bonus_bp: u16,
+}
+
+#[event]
+pub struct BetPlaced {
+    pub bet_id: u64,
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub option_index: u64,
+    pub amount: u64,
+    /// Time-weighted stake, which is `amount` for pools without time weighting
+    pub weight: u64,
+    pub mint: Pubkey,
+    pub created_at: i64,
+    /// Pool totals in the bet's mint after the bet
+    pub bet_totals: [u64; 2],
+    pub weighted_totals: [u64; 2],
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl BetPlaced {
+    pub const VERSION: u8 = 3;
+}
+
+#[event]
+pub struct BetCommitted {
+    pub bet_id: u64,
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub commitment: [u8; 32],
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub created_at: i64,
+}
+
+#[event]
+pub struct BetRevealed {
+    pub bet_id: u64,
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub option_index: u64,
+    pub amount: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct PoolClosed {
+    pub pool_id: u64,
+    pub selected_option: u64,
+    pub decision_time: i64,
+    pub is_draw: bool,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl PoolClosed {
+    pub const VERSION: u8 = 2;
+}
+
+#[event]
+pub struct BetSettled {
+    pub bet_id: u64,
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub mint: Pubkey,
+    pub outcome: BetOutcome,
+    /// Paid out after the fee
+    pub amount: u64,
+    pub fee: u64,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl BetSettled {
+    pub const VERSION: u8 = 1;
+}
+
+#[event]
+pub struct PayoutClaimed {
+    pub bet_id: u64,
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+}
+
+/// A stake taken back from a pool that was never graded
+#[event]
+pub struct EmergencyWithdrawn {
+    pub bet_id: u64,
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl EmergencyWithdrawn {
+    pub const VERSION: u8 = 1;
+}
+
+/// Both stakes of a matched offer returned from a pool that was never graded
+#[event]
+pub struct MatchEmergencyWithdrawn {
+    pub offer_id: u64,
+    pub match_id: u64,
+    pub pool_id: u64,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub maker_stake: u64,
+    pub taker_stake: u64,
+    pub mint: Pubkey,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl MatchEmergencyWithdrawn {
+    pub const VERSION: u8 = 1;
+}
+
+#[event]
+pub struct PoolImageSet {
+    pub pool_id: u64,
+    pub image_url: String,
+}
+
+#[event]
+pub struct AccountMigrated {
+    pub account: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event]
+pub struct TreasurySet {
+    pub treasury: Pubkey,
+}
+
+#[event]
+pub struct ClaimWindowSet {
+    pub claim_window: i64,
+}
+
+/// What was left of a pool's stakes in one mint when its claim window closed
+#[event]
+pub struct UnclaimedSwept {
+    pub pool_id: u64,
+    pub mint: Pubkey,
+    pub treasury_token_account: Pubkey,
+    pub amount: u64,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl UnclaimedSwept {
+    pub const VERSION: u8 = 1;
+}
+
+/// Matched offers, LMSR positions and bonds of a pool in one mint left unclaimed when
+/// its claim window closed
+#[event]
+pub struct ExpiredClaimsSwept {
+    pub pool_id: u64,
+    pub mint: Pubkey,
+    pub treasury_token_account: Pubkey,
+    pub claims: u8,
+    pub amount: u64,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl ExpiredClaimsSwept {
+    pub const VERSION: u8 = 1;
+}
+
+/// A pool's last claim is gone and it counts towards decommissioning the program
+#[event]
+pub struct PoolSettled {
+    pub pool_id: u64,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl PoolSettled {
+    pub const VERSION: u8 = 1;
+}
+
+#[event]
+pub struct VaultDrained {
+    pub mint: Pubkey,
+    pub treasury_token_account: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct ProgramClosed {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub mints_closed: u64,
+    pub decommissioned_at: i64,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl ProgramClosed {
+    pub const VERSION: u8 = 1;
+}
+
+/// Result of `audit_solvency` for one vault
+#[event]
+pub struct SolvencyReport {
+    pub mint: Pubkey,
+    pub vault_balance: u64,
+    /// Everything owed in the mint: the audited pools, balances, escrows and bonds
+    pub liabilities: u64,
+    /// How much more the vault would need to cover the liabilities
+    pub shortfall: u64,
+    pub pools_audited: u64,
+    /// What the audited pools and their markets owe
+    pub pools_owe: u64,
+    /// Held in internal balances
+    pub balances: u64,
+    /// Held for open offers and unclaimed matches
+    pub escrowed: u64,
+    /// Held as bonds of proposed pools
+    pub bonds: u64,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl SolvencyReport {
+    pub const VERSION: u8 = 2;
+}
+
+#[event]
+pub struct ProgramInitialized {
+    pub authority: Pubkey,
+    pub treasury: Pubkey,
+    pub slot: u64,
+    pub version: u8,
+}
+
+impl ProgramInitialized {
+    pub const VERSION: u8 = 1;
+}
+
+#[event]
+pub struct MintAdded {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub decimals: u8,
+    pub payout_fee_bp: u16,
+    pub min_bet: u64,
+}
+
+#[event]
+pub struct MintUpdated {
+    pub mint: Pubkey,
+    pub payout_fee_bp: u16,
+    pub min_bet: u64,
+    pub is_active: bool,
+}
+
+#[event]
+pub struct MarketCreated {
+    pub pool_id: u64,
+    pub mint: Pubkey,
+    pub liquidity: u64,
+    pub subsidy: u64,
+}
+
+#[event]
+pub struct SharesBought {
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub option_index: u64,
+    pub shares: u64,
+    pub cost: u64,
+    pub mint: Pubkey,
+    /// Shares sold of each option after this purchase
+    pub market_shares: [u64; 2],
+}
+
+#[event]
+pub struct SharesRedeemed {
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub shares: [u64; 2],
+    pub amount: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct MarketSurplusWithdrawn {
+    pub pool_id: u64,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PoolUpdated {
+    pub pool_id: u64,
+    pub question: String,
+    pub options: [String; 2],
+}
+
+#[event]
+pub struct BetsCloseAtExtended {
+    pub pool_id: u64,
+    pub previous_bets_close_at: i64,
+    pub bets_close_at: i64,
+}
+
+#[event]
+pub struct BalanceDeposited {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct BalanceWithdrawn {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct BalanceCredited {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub balance: u64,
+}
+
+#[event]
+pub struct SessionCreated {
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    pub spend_cap: u64,
+    pub expires_at: i64,
+    pub allowed_mints: Vec<Pubkey>,
+}
+
+#[event]
+pub struct SessionRevoked {
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+}
+
+#[event]
+pub struct BetRelayed {
+    pub bet_id: u64,
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub relayer: Pubkey,
+    pub nonce: u64,
+}
+
+#[event]
+pub struct PoolBondSet {
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct PoolProposed {
+    pub pool_id: u64,
+    pub creator: Pubkey,
+    pub bond: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct PoolFlaggedInvalid {
+    pub pool_id: u64,
+    pub bond_slashed: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct BondReturned {
+    pub pool_id: u64,
+    pub creator: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct OfferCreated {
+    pub offer_id: u64,
+    pub pool_id: u64,
+    pub maker: Pubkey,
+    pub option_index: u64,
+    pub stake: u64,
+    pub ask: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct OfferAccepted {
+    pub offer_id: u64,
+    pub match_id: u64,
+    pub pool_id: u64,
+    pub taker: Pubkey,
+    pub maker_stake: u64,
+    pub taker_stake: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct OfferCancelled {
+    pub offer_id: u64,
+    pub pool_id: u64,
+    pub refund: u64,
+    pub mint: Pubkey,
+}
+
+#[event]
+pub struct MatchClaimed {
+    pub offer_id: u64,
+    pub match_id: u64,
+    pub pool_id: u64,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub mint: Pubkey,
+}
@@ -1,12 +1,58 @@
 use anchor_lang::prelude::*;
-use anchor_spl::associated_token::AssociatedToken;
-use anchor_spl::token::{self, Token, TokenAccount, Transfer};
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::hash::hashv;
+use anchor_lang::solana_program::sysvar::instructions::{
+    self as instructions_sysvar, load_current_index_checked, load_instruction_at_checked,
+};
+use anchor_spl::token_interface::spl_token_2022::onchain::invoke_transfer_checked;
+use anchor_spl::token_interface::{self, CloseAccount, Mint, TokenAccount, TokenInterface};
+use trump_fun_settlement as settlement;
+
+mod contexts;
+mod errors;
+mod events;
+mod migrations;
+mod payouts;
+
+pub use contexts::*;
+pub use errors::*;
+pub use events::*;
+pub use migrations::*;
+pub use payouts::*;
 
 declare_id!("3G6TYRGE6JX4BZPdeix4L38VtbrAUMWyK1yJ5DQoRc7c");
 
 pub const BETTING_POOLS_SEED: &[u8] = b"betting_pools_v1";
 pub const POOL_SEED: &[u8] = b"pool_v1";
+pub const POOL_METADATA_SEED: &[u8] = b"pool_metadata_v1";
 pub const BET_SEED: &[u8] = b"bet_v1";
+pub const MINT_CONFIG_SEED: &[u8] = b"mint_config_v1";
+pub const VAULT_SEED: &[u8] = b"vault_v1";
+pub const POOL_TOTALS_SEED: &[u8] = b"pool_totals_v1";
+pub const MARKET_SEED: &[u8] = b"market_v1";
+pub const POSITION_SEED: &[u8] = b"position_v1";
+pub const OFFER_SEED: &[u8] = b"offer_v1";
+pub const MATCH_SEED: &[u8] = b"match_v1";
+pub const POOL_BOND_SEED: &[u8] = b"pool_bond_v1";
+pub const NONCE_SEED: &[u8] = b"nonce_v1";
+pub const SESSION_SEED: &[u8] = b"session_v1";
+pub const BALANCE_SEED: &[u8] = b"balance_v1";
+
+/// Prefix of the message a bettor signs for `place_bet_signed`
+pub const BET_INTENT_DOMAIN: &[u8] = b"trump-fun:bet_intent:v1";
+
+/// Highest payout fee a mint can be configured with (100%)
+pub const MAX_FEE_BP: u16 = 10_000;
+
+/// Most mints a session key can be allowed to bet with
+pub const MAX_SESSION_MINTS: usize = 4;
+
+/// Largest time-weighting bonus a pool can give its earliest bets (2x weight)
+pub const MAX_EARLY_BONUS_BP: u16 = 10_000;
+
+/// How long a pool can stay ungraded once grading is possible before its bettors can
+/// take their stakes back with `emergency_withdraw` (30 days)
+pub const EMERGENCY_WITHDRAW_DELAY: i64 = 30 * 24 * 60 * 60;
 
 #[program]
 pub mod trump_fun {
@@ -14,42 +60,94 @@ pub mod trump_fun {
 
     /// Initialize the BettingPools program
     /// Similar to the constructor in the Solidity version
-    pub fn initialize(
-        ctx: Context<Initialize>,
-        usdc_mint: Pubkey,
-        freedom_mint: Pubkey,
-    ) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         let betting_pools = &mut ctx.accounts.betting_pools;
 
         msg!("Initializing BettingPools");
-        // Check if already initialized
-        if betting_pools.is_initialized {
-            return err!(BettingPoolsError::AlreadyInitialized);
-        }
 
-        // Mark as initialized
+        // `init` has already refused an account that exists, so this is its first run
         betting_pools.is_initialized = true;
+        betting_pools.version = BettingPoolsState::VERSION;
 
         // Set the authority to the signer
         betting_pools.authority = ctx.accounts.authority.key();
 
-        // Set up token mints
-        betting_pools.usdc_mint = usdc_mint;
-        betting_pools.freedom_mint = freedom_mint;
+        // Fees go to the authority until a treasury is set
+        betting_pools.treasury = ctx.accounts.authority.key();
 
         // Initialize counters
         betting_pools.next_pool_id = 1;
-        betting_pools.next_bet_id = 1;
 
-        // Set payout fee basis points (0.9% like in Solidity)
-        betting_pools.payout_fee_bp = 90;
+        emit_cpi!(ProgramInitialized {
+            authority: betting_pools.authority,
+            treasury: betting_pools.treasury,
+            slot: Clock::get()?.slot,
+            version: ProgramInitialized::VERSION,
+        });
 
         msg!("BettingPools program initialized");
         Ok(())
     }
 
+    /// Accept a new mint for betting
+    /// Creates the mint config PDA and the vault holding stakes in that mint
+    pub fn add_mint(ctx: Context<AddMint>, payout_fee_bp: u16, min_bet: u64) -> Result<()> {
+        if payout_fee_bp > MAX_FEE_BP {
+            return err!(BettingPoolsError::InvalidFee);
+        }
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.mint = ctx.accounts.mint.key();
+        mint_config.vault = ctx.accounts.vault.key();
+        mint_config.decimals = ctx.accounts.mint.decimals;
+        mint_config.payout_fee_bp = payout_fee_bp;
+        mint_config.min_bet = min_bet;
+        mint_config.is_active = true;
+        mint_config.version = MintConfig::VERSION;
+
+        ctx.accounts.betting_pools.mint_count += 1;
+
+        emit_cpi!(MintAdded {
+            mint: mint_config.mint,
+            vault: mint_config.vault,
+            decimals: mint_config.decimals,
+            payout_fee_bp,
+            min_bet,
+        });
+
+        Ok(())
+    }
+
+    /// Update the fee, minimum bet or active flag of an accepted mint
+    /// Deactivated mints reject new bets but existing bets can still be claimed
+    pub fn update_mint(
+        ctx: Context<UpdateMint>,
+        payout_fee_bp: u16,
+        min_bet: u64,
+        is_active: bool,
+    ) -> Result<()> {
+        if payout_fee_bp > MAX_FEE_BP {
+            return err!(BettingPoolsError::InvalidFee);
+        }
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.payout_fee_bp = payout_fee_bp;
+        mint_config.min_bet = min_bet;
+        mint_config.is_active = is_active;
+
+        emit_cpi!(MintUpdated {
+            mint: mint_config.mint,
+            payout_fee_bp,
+            min_bet,
+            is_active,
+        });
+
+        Ok(())
+    }
+
     /// Create a new betting pool
     /// Similar to the createPool function in the Solidity version
+    #[allow(clippy::too_many_arguments)]
     pub fn create_pool(
         ctx: Context<CreatePool>,
         question: String,
@@ -57,178 +155,535 @@ pub mod trump_fun {
         bets_close_at: i64,
         original_truth_social_post_id: String,
         image_url: String,
+        hidden_bets: Option<HiddenBetsConfig>,
+        time_weighting: Option<TimeWeighting>,
     ) -> Result<()> {
+        let event = init_pool(
+            &mut ctx.accounts.betting_pools,
+            &mut *ctx.accounts.pool.load_init()?,
+            &mut ctx.accounts.pool_metadata,
+            question,
+            options,
+            bets_close_at,
+            original_truth_social_post_id,
+            image_url,
+            hidden_bets,
+            time_weighting,
+        )?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    /// Set the bond anyone proposing a pool must post. A zero amount turns proposals off
+    pub fn set_pool_bond(ctx: Context<SetPoolBond>, amount: u64) -> Result<()> {
         let betting_pools = &mut ctx.accounts.betting_pools;
-        let pool = &mut ctx.accounts.pool;
+        betting_pools.bond_mint = ctx.accounts.mint.key();
+        betting_pools.bond_amount = amount;
 
-        // Check if bets close time is in the future
-        let clock = Clock::get()?;
-        if bets_close_at <= clock.unix_timestamp {
-            return err!(BettingPoolsError::BetsCloseTimeInPast);
+        emit_cpi!(PoolBondSet {
+            mint: betting_pools.bond_mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Create a pool without the authority by escrowing the configured bond.
+    /// The bond is returned once the pool grades and kept if the pool is flagged invalid
+    #[allow(clippy::too_many_arguments)]
+    pub fn propose_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProposePool<'info>>,
+        question: String,
+        options: [String; 2],
+        bets_close_at: i64,
+        original_truth_social_post_id: String,
+        image_url: String,
+        hidden_bets: Option<HiddenBetsConfig>,
+        time_weighting: Option<TimeWeighting>,
+    ) -> Result<()> {
+        let bond_amount = ctx.accounts.betting_pools.bond_amount;
+        if bond_amount == 0 {
+            return err!(BettingPoolsError::BondNotConfigured);
         }
 
-        // Set the pool ID and increment the counter
-        let pool_id = betting_pools.next_pool_id;
-        betting_pools.next_pool_id += 1;
+        // Check if the mint is still accepted
+        if !ctx.accounts.mint_config.is_active {
+            return err!(BettingPoolsError::MintNotActive);
+        }
 
-        // Initialize the pool
-        pool.id = pool_id;
-        pool.question = question;
-        pool.options = options;
-        pool.bets_close_at = bets_close_at;
-        pool.usdc_bet_totals = [0, 0];
-        pool.points_bet_totals = [0, 0];
-        pool.winning_option = 0;
-        pool.status = PoolStatus::Pending;
-        pool.is_draw = false;
-        pool.created_at = clock.unix_timestamp;
-        pool.original_truth_social_post_id = original_truth_social_post_id;
-        pool.image_url = image_url;
+        receive_exact(
+            &ctx.accounts.token_program,
+            &ctx.accounts.creator_token_account,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.program_token_account,
+            ctx.accounts.creator.to_account_info(),
+            ctx.remaining_accounts,
+            bond_amount,
+        )?;
 
-        emit!(PoolCreated {
+        let mut pool = ctx.accounts.pool.load_init()?;
+        let event = init_pool(
+            &mut ctx.accounts.betting_pools,
+            &mut pool,
+            &mut ctx.accounts.pool_metadata,
+            question,
+            options,
+            bets_close_at,
+            original_truth_social_post_id,
+            image_url,
+            hidden_bets,
+            time_weighting,
+        )?;
+        let pool_id = event.pool_id;
+        emit_cpi!(event);
+
+        let bond = &mut ctx.accounts.pool_bond;
+        bond.pool_id = pool_id;
+        bond.creator = ctx.accounts.creator.key();
+        bond.mint = ctx.accounts.mint.key();
+        bond.amount = bond_amount;
+        bond.is_settled = false;
+        bond.is_slashed = false;
+        bond.version = PoolBond::VERSION;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.bonds = mint_config
+            .bonds
+            .checked_add(bond_amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        // The bond is a claim on the pool until it is returned or slashed
+        add_claims(&mut pool, 1)?;
+
+        emit_cpi!(PoolProposed {
             pool_id,
-            question: pool.question.clone(),
-            options: pool.options.clone(),
-            bets_close_at: pool.bets_close_at,
-            original_truth_social_post_id: pool.original_truth_social_post_id.clone(),
-            image_url: pool.image_url.clone(),
-            created_at: pool.created_at
+            creator: bond.creator,
+            bond: bond_amount,
+            mint: bond.mint,
+        });
+
+        Ok(())
+    }
+
+    /// Void a proposed pool. Every bet is refunded as in a draw and the creator's
+    /// bond stays in the vault with the fees. The pool's totals follow in
+    /// `remaining_accounts` as for `grade_bet`
+    pub fn flag_pool_invalid<'info>(
+        ctx: Context<'_, '_, 'info, 'info, FlagPoolInvalid<'info>>,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+
+        // Check if pool is already graded
+        if pool.status() != PoolStatus::Pending {
+            return err!(BettingPoolsError::PoolNotOpen);
+        }
+
+        let clock = Clock::get()?;
+        let event = close_pool(
+            &mut ctx.accounts.betting_pools,
+            &mut pool,
+            2,
+            clock.unix_timestamp,
+        )?;
+        emit_cpi!(event);
+        settle_all_totals(&pool, ctx.remaining_accounts, ctx.program_id)?;
+
+        let bond = &mut ctx.accounts.pool_bond;
+        bond.is_settled = true;
+        bond.is_slashed = true;
+
+        // The slashed bond stays in the vault for the treasury
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.bonds = mint_config.bonds.saturating_sub(bond.amount);
+
+        emit_cpi!(PoolFlaggedInvalid {
+            pool_id: pool.id,
+            bond_slashed: bond.amount,
+            mint: bond.mint,
+        });
+
+        settle_claim(&mut pool);
+        mark_settled(&mut ctx.accounts.betting_pools, &mut pool);
+
+        Ok(())
+    }
+
+    /// Return a proposed pool's bond to its creator once the pool is graded
+    pub fn return_bond<'info>(ctx: Context<'_, '_, 'info, 'info, ReturnBond<'info>>) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let bond = &mut ctx.accounts.pool_bond;
+
+        // Check if the pool is graded
+        if pool.status() != PoolStatus::Graded {
+            return err!(BettingPoolsError::PoolNotGraded);
+        }
+        check_claim_window(&pool, Clock::get()?.unix_timestamp)?;
+
+        if bond.is_settled {
+            return err!(BettingPoolsError::BondAlreadySettled);
+        }
+        bond.is_settled = true;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.bonds = mint_config.bonds.saturating_sub(bond.amount);
+
+        let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+        let signer = &[&betting_pools_seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.program_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.creator_token_account,
+            ctx.accounts.betting_pools.to_account_info(),
+            ctx.remaining_accounts,
+            bond.amount,
+            signer,
+        )?;
+
+        emit_cpi!(BondReturned {
+            pool_id: pool.id,
+            creator: bond.creator,
+            amount: bond.amount,
+            mint: bond.mint,
         });
 
+        settle_claim(&mut pool);
+
         Ok(())
     }
 
     /// Place a bet on a pool
     /// Similar to the placeBet function in the Solidity version
-    pub fn place_bet(
-        ctx: Context<PlaceBet>,
+    pub fn place_bet<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceBet<'info>>,
         option_index: u64,
         amount: u64,
-        token_type: TokenType,
     ) -> Result<()> {
-        let betting_pools = &ctx.accounts.betting_pools;
-        let pool = &mut ctx.accounts.pool;
+        // Bets on hidden pools only reveal their option after betting closes
+        if ctx.accounts.pool.load()?.is_hidden() {
+            return err!(BettingPoolsError::HiddenPool);
+        }
+
+        // Check if option index is valid
+        if option_index >= 2 {
+            return err!(BettingPoolsError::InvalidOptionIndex);
+        }
+
+        stake_bet(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.betting_pools,
+            amount,
+        )?;
+
         let bet = &mut ctx.accounts.bet;
-        let bettor = &ctx.accounts.bettor;
-        let clock = Clock::get()?;
+        let event = place_option(
+            &*ctx.accounts.pool.load()?,
+            bet,
+            &mut ctx.accounts.pool_totals,
+            option_index,
+        )?;
+        emit_cpi!(event);
 
-        // Check if betting period is closed
-        if clock.unix_timestamp > pool.bets_close_at {
-            return err!(BettingPoolsError::BettingPeriodClosed);
+        Ok(())
+    }
+
+    /// Authorize an ephemeral key to place bets and claim payouts for the signer.
+    /// Bets placed by the key are capped at `spend_cap` in total and must use one of
+    /// `allowed_mints`. The owner's token accounts must approve the betting pools PDA
+    /// as delegate for the key to spend from them
+    pub fn create_session(
+        ctx: Context<CreateSession>,
+        session_key: Pubkey,
+        spend_cap: u64,
+        expires_at: i64,
+        allowed_mints: Vec<Pubkey>,
+    ) -> Result<()> {
+        if expires_at <= Clock::get()?.unix_timestamp {
+            return err!(BettingPoolsError::SessionExpired);
+        }
+        if allowed_mints.len() > MAX_SESSION_MINTS {
+            return err!(BettingPoolsError::TooManySessionMints);
         }
 
-        // Check if pool is open for betting
-        if pool.status != PoolStatus::Pending {
-            return err!(BettingPoolsError::PoolNotOpen);
+        let session = &mut ctx.accounts.session;
+        session.owner = ctx.accounts.owner.key();
+        session.session_key = session_key;
+        session.spend_cap = spend_cap;
+        session.spent = 0;
+        session.expires_at = expires_at;
+        session.allowed_mints = allowed_mints;
+        session.version = Session::VERSION;
+
+        emit_cpi!(SessionCreated {
+            owner: session.owner,
+            session_key,
+            spend_cap,
+            expires_at,
+            allowed_mints: session.allowed_mints.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Revoke a session key and return the session's rent to its owner
+    pub fn revoke_session(ctx: Context<RevokeSession>) -> Result<()> {
+        emit_cpi!(SessionRevoked {
+            owner: ctx.accounts.owner.key(),
+            session_key: ctx.accounts.session.session_key,
+        });
+
+        Ok(())
+    }
+
+    /// Place a bet for a bettor who signed a bet intent off-chain. The relayer pays the
+    /// fees and rent, and the stake moves through the bettor's delegate approval to the
+    /// betting pools PDA. The transaction must verify the signature with an ed25519
+    /// program instruction right before this one
+    pub fn place_bet_signed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceBetSigned<'info>>,
+        option_index: u64,
+        amount: u64,
+        nonce: u64,
+        expires_at: i64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let bettor = ctx.accounts.bettor.key();
+
+        if clock.unix_timestamp > expires_at {
+            return err!(BettingPoolsError::IntentExpired);
         }
 
+        // Bets on hidden pools only reveal their option after betting closes
+        let pool_id = {
+            let pool = ctx.accounts.pool.load()?;
+            if pool.is_hidden() {
+                return err!(BettingPoolsError::HiddenPool);
+            }
+            check_stake(
+                &pool,
+                &ctx.accounts.mint_config,
+                amount,
+                clock.unix_timestamp,
+            )?;
+            pool.id
+        };
+
         // Check if option index is valid
         if option_index >= 2 {
             return err!(BettingPoolsError::InvalidOptionIndex);
         }
 
-        // Check if amount is valid
-        if amount == 0 {
-            return err!(BettingPoolsError::ZeroAmount);
+        let message = bet_intent_message(
+            pool_id,
+            option_index,
+            amount,
+            &ctx.accounts.mint.key(),
+            nonce,
+            expires_at,
+        );
+        verify_ed25519_signature(&ctx.accounts.instructions, &bettor, &message)?;
+
+        // Each intent can only be used once
+        let user_nonce = &mut ctx.accounts.user_nonce;
+        if nonce != user_nonce.next_nonce {
+            return err!(BettingPoolsError::InvalidNonce);
         }
-
-        // Transfer tokens from bettor to program account
-        token::transfer(
-            CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.bettor_token_account.to_account_info(),
-                    to: ctx.accounts.program_token_account.to_account_info(),
-                    authority: bettor.to_account_info(),
-                },
-            ),
+        user_nonce.owner = bettor;
+        user_nonce.next_nonce += 1;
+        user_nonce.version = UserNonce::VERSION;
+
+        let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+        let signer = &[&betting_pools_seeds[..]];
+
+        let amount = receive_stake(
+            &ctx.accounts.token_program,
+            &ctx.accounts.bettor_token_account,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.program_token_account,
+            ctx.accounts.betting_pools.to_account_info(),
+            ctx.remaining_accounts,
             amount,
+            signer,
         )?;
 
-        // Initialize the bet
-        let bet_id = betting_pools.next_bet_id;
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let bet = &mut ctx.accounts.bet;
+        record_stake(
+            &mut pool,
+            &ctx.accounts.mint_config,
+            bet,
+            &mut ctx.accounts.pool_totals,
+            bettor,
+            amount,
+            clock.unix_timestamp,
+        )?;
+        let event = place_option(&pool, bet, &mut ctx.accounts.pool_totals, option_index)?;
+        emit_cpi!(event);
+
+        emit_cpi!(BetRelayed {
+            bet_id: bet.id,
+            pool_id: bet.pool_id,
+            user: bet.owner,
+            relayer: ctx.accounts.relayer.key(),
+            nonce,
+        });
 
-        bet.id = bet_id;
-        bet.owner = bettor.key();
-        bet.option = option_index;
-        bet.amount = amount;
-        bet.pool_id = pool.id;
-        bet.created_at = clock.unix_timestamp;
-        bet.updated_at = clock.unix_timestamp;
-        bet.is_withdrawn = false;
-        bet.token_type = token_type;
+        Ok(())
+    }
 
-        // Update totals in the pool
-        if token_type == TokenType::Usdc {
-            pool.usdc_bet_totals[option_index as usize] += amount;
-        } else {
-            pool.points_bet_totals[option_index as usize] += amount;
+    /// Place a bet on a hidden pool without revealing its option.
+    /// `commitment` is the hash checked by `reveal_bet` once betting closes
+    pub fn commit_bet<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlaceBet<'info>>,
+        commitment: [u8; 32],
+        amount: u64,
+    ) -> Result<()> {
+        if !ctx.accounts.pool.load()?.is_hidden() {
+            return err!(BettingPoolsError::PoolNotHidden);
         }
 
-        // Emit the BetPlaced event
-        emit!(BetPlaced {
-            bet_id,
-            pool_id: pool.id,
-            user: bettor.key(),
-            option_index,
+        let amount = stake_bet(
+            ctx.accounts,
+            ctx.remaining_accounts,
+            ctx.bumps.betting_pools,
+            amount,
+        )?;
+
+        // The option stays unset and the stake is left out of the pool's totals until revealed
+        let bet = &mut ctx.accounts.bet;
+        bet.commitment = commitment;
+
+        emit_cpi!(BetCommitted {
+            bet_id: bet.id,
+            pool_id: bet.pool_id,
+            user: bet.owner,
+            commitment,
             amount,
-            token_type,
-            created_at: clock.unix_timestamp,
+            mint: bet.mint,
+            created_at: bet.created_at,
         });
 
-        // Increment the bet ID counter
-        let betting_pools = &mut ctx.accounts.betting_pools;
-        betting_pools.next_bet_id += 1;
+        Ok(())
+    }
+
+    /// Reveal the option of a committed bet so it counts toward the pool's totals
+    /// Only allowed between `bets_close_at` and the end of the pool's reveal phase
+    pub fn reveal_bet(ctx: Context<RevealBet>, option_index: u64, salt: [u8; 32]) -> Result<()> {
+        let pool = ctx.accounts.pool.load()?;
+        let bet = &mut ctx.accounts.bet;
+        let clock = Clock::get()?;
+
+        if !pool.is_hidden() {
+            return err!(BettingPoolsError::PoolNotHidden);
+        }
+
+        // Check that the reveal phase is running
+        if clock.unix_timestamp <= pool.bets_close_at {
+            return err!(BettingPoolsError::RevealNotOpen);
+        }
+        if clock.unix_timestamp > pool.reveal_ends_at {
+            return err!(BettingPoolsError::RevealClosed);
+        }
+
+        if bet.is_revealed {
+            return err!(BettingPoolsError::BetAlreadyRevealed);
+        }
+
+        // Check if option index is valid
+        if option_index >= 2 {
+            return err!(BettingPoolsError::InvalidOptionIndex);
+        }
+
+        if bet_commitment(option_index, &salt, &bet.owner) != bet.commitment {
+            return err!(BettingPoolsError::InvalidReveal);
+        }
+
+        bet.option = option_index;
+        bet.is_revealed = true;
+        bet.updated_at = clock.unix_timestamp;
+        bet.weight = bet_weight(&pool, bet.amount, bet.created_at);
+
+        let pool_totals = &mut ctx.accounts.pool_totals;
+        pool_totals.bet_totals[option_index as usize] = pool_totals.bet_totals
+            [option_index as usize]
+            .checked_add(bet.amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+        pool_totals.weighted_totals[option_index as usize] = pool_totals.weighted_totals
+            [option_index as usize]
+            .checked_add(bet.weight)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        emit_cpi!(BetRevealed {
+            bet_id: bet.id,
+            pool_id: bet.pool_id,
+            user: bet.owner,
+            option_index,
+            amount: bet.amount,
+            mint: bet.mint,
+        });
 
         Ok(())
     }
 
     /// Grade a betting pool
-    /// Determines the winning option and releases payouts
-    pub fn grade_bet(ctx: Context<GradeBet>, response_option: u64) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
+    /// Determines the winning option and releases payouts. Every one of the pool's
+    /// totals follows in `remaining_accounts` with its mint's config,
+    /// `[pool_totals, mint_config, ...]`, and gets the payout ratio and current payout
+    /// fee frozen for every claim
+    pub fn grade_bet<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GradeBet<'info>>,
+        response_option: u64,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
 
         // Check if pool is already graded
-        if pool.status != PoolStatus::Pending {
+        if pool.status() != PoolStatus::Pending {
             return err!(BettingPoolsError::PoolNotOpen);
         }
 
-        pool.status = PoolStatus::Graded;
-
-        if response_option == 0 {
-            pool.winning_option = 0;
-        } else if response_option == 1 {
-            pool.winning_option = 1;
-        } else if response_option == 2 {
-            pool.is_draw = true;
-        } else {
-            return err!(BettingPoolsError::GradingError);
+        // Hidden pools can't be graded until every bet has had the chance to reveal
+        let clock = Clock::get()?;
+        if pool.is_hidden() && clock.unix_timestamp <= pool.reveal_ends_at {
+            return err!(BettingPoolsError::RevealInProgress);
         }
 
-        // Set decision time to current time
-        let clock = Clock::get()?;
-        pool.decision_time = clock.unix_timestamp;
+        let event = close_pool(
+            &mut ctx.accounts.betting_pools,
+            &mut pool,
+            response_option,
+            clock.unix_timestamp,
+        )?;
+        emit_cpi!(event);
 
-        emit!(PoolClosed {
-            pool_id: pool.id,
-            selected_option: response_option,
-            decision_time: pool.decision_time,
-        });
+        settle_all_totals(&pool, ctx.remaining_accounts, ctx.program_id)?;
 
         Ok(())
     }
 
     /// Claim payouts for a bet
-    pub fn claim_payout(ctx: Context<ClaimPayout>) -> Result<()> {
-        let pool = &ctx.accounts.pool;
+    pub fn claim_payout<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimPayout<'info>>,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
         let bet = &mut ctx.accounts.bet;
-        let betting_pools = &ctx.accounts.betting_pools;
 
         // Check if the pool is graded
-        if pool.status != PoolStatus::Graded {
+        if pool.status() != PoolStatus::Graded {
             return err!(BettingPoolsError::PoolNotGraded);
         }
 
+        let now = Clock::get()?.unix_timestamp;
+        check_claim_window(&pool, now)?;
+
+        if let Some(session) = &ctx.accounts.session {
+            if now > session.expires_at {
+                return err!(BettingPoolsError::SessionExpired);
+            }
+        }
+
         // Check if bet is already withdrawn
         if bet.is_withdrawn {
             return err!(BettingPoolsError::BetAlreadyWithdrawn);
@@ -237,445 +692,2718 @@ pub mod trump_fun {
         // Mark bet as withdrawn
         bet.is_withdrawn = true;
 
-        let token_type = bet.token_type;
-
-        // Get the appropriate betTotals based on token type
-        let bet_totals = if token_type == TokenType::Usdc {
-            pool.usdc_bet_totals
-        } else {
-            pool.points_bet_totals
-        };
-
-        let amount_to_transfer: u64;
-
-        // If it is a draw or there are no bets on one side or the other for this token type, refund the bet
-        if pool.is_draw || bet_totals[0] == 0 || bet_totals[1] == 0 {
-            amount_to_transfer = bet.amount;
-        } else {
-            let losing_option = if pool.winning_option == 0 { 1 } else { 0 };
-
-            if bet.option == pool.winning_option {
-                // Calculate winnings
-                let win_amount = (bet.amount * bet_totals[losing_option as usize])
-                    / bet_totals[pool.winning_option as usize]
-                    + bet.amount;
-                let fee = (win_amount * betting_pools.payout_fee_bp as u64) / 10000;
-                amount_to_transfer = win_amount - fee;
-
-                // Fee stays in the program account
-            } else {
-                // Losing bets get nothing
-                amount_to_transfer = 0;
-            }
-        }
+        let payout = bet_payout(&ctx.accounts.pool_totals.settlement(&pool)?, bet);
+        let amount_to_transfer = payout.amount;
+        ctx.accounts.pool_totals.claimed = ctx
+            .accounts
+            .pool_totals
+            .claimed
+            .checked_add(amount_to_transfer)
+            .ok_or(BettingPoolsError::MathOverflow)?;
 
         // If there's an amount to transfer, do the transfer
         if amount_to_transfer > 0 {
-            // Get the right mint based on token type
-            let mint = if token_type == TokenType::Usdc {
-                betting_pools.usdc_mint
-            } else {
-                betting_pools.freedom_mint
-            };
-
             // Transfer tokens from program account to bettor
             let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
             let signer = &[&betting_pools_seeds[..]];
 
-            token::transfer(
-                CpiContext::new_with_signer(
-                    ctx.accounts.token_program.to_account_info(),
-                    Transfer {
-                        from: ctx.accounts.program_token_account.to_account_info(),
-                        to: ctx.accounts.bettor_token_account.to_account_info(),
-                        authority: ctx.accounts.betting_pools.to_account_info(),
-                    },
-                    signer,
-                ),
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.program_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.bettor_token_account,
+                ctx.accounts.betting_pools.to_account_info(),
+                ctx.remaining_accounts,
                 amount_to_transfer,
+                signer,
             )?;
 
-            emit!(PayoutClaimed {
+            emit_cpi!(PayoutClaimed {
                 bet_id: bet.id,
                 pool_id: pool.id,
                 user: bet.owner,
                 amount: amount_to_transfer,
-                token_type,
+                mint: bet.mint,
             });
         }
 
+        emit_cpi!(bet_settled(bet, &payout)?);
+
+        settle_bet_claim(&mut pool, &mut ctx.accounts.pool_totals);
+
         Ok(())
     }
 
-    /// Update the image URL for a pool
-    pub fn set_image(ctx: Context<SetImage>, image_url: String) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
+    /// Take back a bet's stake from a pool left ungraded for `EMERGENCY_WITHDRAW_DELAY`
+    /// after its bets closed, or after its reveal phase for a hidden pool. Needs no
+    /// authority, so bettors aren't stuck if it disappears. The stake leaves the pool's
+    /// totals, so a pool graded later still pays its other bets in full.
+    /// Matched offers are unwound by `emergency_withdraw_match`, and open offers can be
+    /// cancelled at any time. LMSR positions aren't covered: they bought shares at the
+    /// market's price rather than staking a principal, and wait for the pool's grading
+    pub fn emergency_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EmergencyWithdraw<'info>>,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let bet = &mut ctx.accounts.bet;
 
-        // Update the image URL
-        pool.image_url = image_url.clone();
+        let clock = Clock::get()?;
+        check_emergency_delay(&pool, clock.unix_timestamp)?;
 
-        // Emit the PoolImageSet event
-        emit!(PoolImageSet {
+        // Check if bet is already withdrawn
+        if bet.is_withdrawn {
+            return err!(BettingPoolsError::BetAlreadyWithdrawn);
+        }
+        bet.is_withdrawn = true;
+
+        // Unrevealed bets never reached the totals
+        // Totals migrated before stakes were tracked don't hold unrevealed ones
+        let pool_totals = &mut ctx.accounts.pool_totals;
+        pool_totals.staked = pool_totals.staked.saturating_sub(bet.amount);
+        if bet.is_revealed {
+            let option_index = bet.option as usize;
+            pool_totals.bet_totals[option_index] = pool_totals.bet_totals[option_index]
+                .checked_sub(bet.amount)
+                .ok_or(BettingPoolsError::MathOverflow)?;
+            pool_totals.weighted_totals[option_index] = pool_totals.weighted_totals[option_index]
+                .checked_sub(bet.weight)
+                .ok_or(BettingPoolsError::MathOverflow)?;
+        }
+
+        let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+        let signer = &[&betting_pools_seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.program_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.bettor_token_account,
+            ctx.accounts.betting_pools.to_account_info(),
+            ctx.remaining_accounts,
+            bet.amount,
+            signer,
+        )?;
+
+        emit_cpi!(EmergencyWithdrawn {
+            bet_id: bet.id,
             pool_id: pool.id,
-            image_url,
+            user: bet.owner,
+            amount: bet.amount,
+            mint: bet.mint,
+            slot: clock.slot,
+            version: EmergencyWithdrawn::VERSION,
         });
 
-        Ok(())
-    }
+        settle_bet_claim(&mut pool, &mut ctx.accounts.pool_totals);
 
-    pub fn close_betting_pool(ctx: Context<CloseBettingPool>) -> Result<()> {
         Ok(())
     }
-}
 
-//--------- STATE STRUCTS ---------//
+    /// Unwind a matched offer in a pool left ungraded for `EMERGENCY_WITHDRAW_DELAY`,
+    /// returning each side's stake. Either side can call it. The match can't be settled
+    /// by grading once one stake is gone, so both are returned together
+    pub fn emergency_withdraw_match<'info>(
+        ctx: Context<'_, '_, 'info, 'info, EmergencyWithdrawMatch<'info>>,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let offer_match = &mut ctx.accounts.offer_match;
+        let claimant = ctx.accounts.claimant.key();
 
-// Initialize context
-#[derive(Accounts)]
-pub struct Initialize<'info> {
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + BettingPoolsState::INIT_SPACE,
-        seeds = [BETTING_POOLS_SEED],
-        bump
-    )]
-    pub betting_pools: Account<'info, BettingPoolsState>,
+        let clock = Clock::get()?;
+        check_emergency_delay(&pool, clock.unix_timestamp)?;
 
-    #[account(mut)]
-    pub authority: Signer<'info>,
+        if claimant != offer_match.maker && claimant != offer_match.taker {
+            return err!(BettingPoolsError::NotBetOwner);
+        }
+        if offer_match.maker_claimed || offer_match.taker_claimed {
+            return err!(BettingPoolsError::BetAlreadyWithdrawn);
+        }
+        offer_match.maker_claimed = true;
+        offer_match.taker_claimed = true;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.escrowed = mint_config
+            .escrowed
+            .saturating_sub(offer_match.maker_stake + offer_match.taker_stake);
+
+        let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+        let signer = &[&betting_pools_seeds[..]];
+
+        for (to, amount) in [
+            (&ctx.accounts.maker_token_account, offer_match.maker_stake),
+            (&ctx.accounts.taker_token_account, offer_match.taker_stake),
+        ] {
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.program_token_account,
+                &ctx.accounts.mint,
+                to,
+                ctx.accounts.betting_pools.to_account_info(),
+                ctx.remaining_accounts,
+                amount,
+                signer,
+            )?;
+        }
 
-    pub system_program: Program<'info, System>,
-}
+        emit_cpi!(MatchEmergencyWithdrawn {
+            offer_id: offer_match.offer_id,
+            match_id: offer_match.id,
+            pool_id: offer_match.pool_id,
+            maker: offer_match.maker,
+            taker: offer_match.taker,
+            maker_stake: offer_match.maker_stake,
+            taker_stake: offer_match.taker_stake,
+            mint: offer_match.mint,
+            slot: clock.slot,
+            version: MatchEmergencyWithdrawn::VERSION,
+        });
 
-// BettingPools state
-#[account]
-#[derive(InitSpace)]
-pub struct BettingPoolsState {
-    pub authority: Pubkey,
-    pub usdc_mint: Pubkey,
-    pub freedom_mint: Pubkey,
-    pub next_pool_id: u64,
-    pub next_bet_id: u64,
-    pub payout_fee_bp: u16,
-    pub is_initialized: bool,
-}
+        // `accept_offer` added a claim for each side, and both are cancelled here
+        settle_claims(&mut pool, 2);
 
-// Create pool context
-#[derive(Accounts)]
-#[instruction(
-    question: String,
-    options: [String; 2],
-    bets_close_at: i64,
-    original_truth_social_post_id: String,
-    image_url: String
-)]
-pub struct CreatePool<'info> {
-    #[account(
-        mut,
-        seeds = [BETTING_POOLS_SEED],
-        bump,
-        has_one = authority @ BettingPoolsError::NotAuthorized
-    )]
-    pub betting_pools: Account<'info, BettingPoolsState>,
-
-    #[account(
-        init,
-        payer = authority,
-        space = 8 + Pool::INIT_SPACE,
-        seeds = [POOL_SEED, betting_pools.next_pool_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-// Place bet context
-#[derive(Accounts)]
-#[instruction(option_index: u64, amount: u64, token_type: TokenType)]
-pub struct PlaceBet<'info> {
-    #[account(
-        mut,
-        seeds = [BETTING_POOLS_SEED],
-        bump
-    )]
-    pub betting_pools: Account<'info, BettingPoolsState>,
-
-    #[account(
-        mut,
-        seeds = [POOL_SEED, pool.id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
-
-    #[account(
-        init,
-        payer = bettor,
-        space = 8 + Bet::INIT_SPACE,
-        seeds = [BET_SEED, pool.id.to_le_bytes().as_ref(), betting_pools.next_bet_id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub bet: Account<'info, Bet>,
-
-    #[account(mut)]
-    pub bettor: Signer<'info>,
-
-    #[account(
-        mut,
-        token::authority = bettor,
-        token::mint = if token_type == TokenType::Usdc { betting_pools.usdc_mint } else { betting_pools.freedom_mint }
-    )]
-    pub bettor_token_account: Account<'info, token::TokenAccount>,
-
-    #[account(
-        mut,
-        token::mint = if token_type == TokenType::Usdc { betting_pools.usdc_mint } else { betting_pools.freedom_mint }
-    )]
-    pub program_token_account: Account<'info, token::TokenAccount>,
-
-    pub token_program: Program<'info, Token>,
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-    pub rent: Sysvar<'info, Rent>,
-}
-
-// Grade bet context
-#[derive(Accounts)]
-#[instruction(response_option: u64)]
-pub struct GradeBet<'info> {
-    #[account(
-        mut,
-        seeds = [BETTING_POOLS_SEED],
-        bump,
-        has_one = authority @ BettingPoolsError::NotAuthorized
-    )]
-    pub betting_pools: Account<'info, BettingPoolsState>,
-
-    #[account(
-        mut,
-        seeds = [POOL_SEED, pool.id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-// Claim payout context
-#[derive(Accounts)]
-pub struct ClaimPayout<'info> {
-    #[account(
-        seeds = [BETTING_POOLS_SEED],
-        bump
-    )]
-    pub betting_pools: Account<'info, BettingPoolsState>,
-
-    #[account(
-        seeds = [POOL_SEED, pool.id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
-
-    #[account(
-        mut,
-        seeds = [BET_SEED, pool.id.to_le_bytes().as_ref(), bet.id.to_le_bytes().as_ref()],
-        bump,
-        constraint = bet.owner == bettor.key() @ BettingPoolsError::NotBetOwner
-    )]
-    pub bet: Account<'info, Bet>,
-
-    #[account(mut)]
-    pub bettor: Signer<'info>,
-
-    #[account(
-        mut,
-        token::authority = bettor,
-        token::mint = if bet.token_type == TokenType::Usdc { betting_pools.usdc_mint } else { betting_pools.freedom_mint }
-    )]
-    pub bettor_token_account: Account<'info, token::TokenAccount>,
-
-    #[account(
-        mut,
-        token::mint = if bet.token_type == TokenType::Usdc { betting_pools.usdc_mint } else { betting_pools.freedom_mint }
-    )]
-    pub program_token_account: Account<'info, token::TokenAccount>,
-
-    pub token_program: Program<'info, Token>,
-    pub system_program: Program<'info, System>,
-}
-
-// Set image context
-#[derive(Accounts)]
-#[instruction(image_url: String)]
-pub struct SetImage<'info> {
-    #[account(
-        mut,
-        seeds = [POOL_SEED, pool.id.to_le_bytes().as_ref()],
-        bump
-    )]
-    pub pool: Account<'info, Pool>,
-
-    #[account(
-        seeds = [BETTING_POOLS_SEED],
-        bump,
-        has_one = authority @ BettingPoolsError::NotAuthorized
-    )]
-    pub betting_pools: Account<'info, BettingPoolsState>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>,
-}
-
-#[derive(Accounts)]
-pub struct CloseBettingPool<'info> {
-    #[account(
-        mut,
-        seeds = [BETTING_POOLS_SEED],
-        bump,
-        has_one = authority @ BettingPoolsError::NotAuthorized,
-        close = authority
-    )]
-    pub betting_pools: Account<'info, BettingPoolsState>,
-
-    #[account(mut)]
-    pub authority: Signer<'info>,
-
-    pub system_program: Program<'info, System>
-}
+        Ok(())
+    }
 
-//--------- DATA STRUCTURES ---------//
+    /// Move tokens into the signer's internal balance for `mint`.
+    /// Bets placed from the balance and payouts credited to it need no token transfer
+    pub fn deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Deposit<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return err!(BettingPoolsError::ZeroAmount);
+        }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
-pub enum PoolStatus {
+        // Check if the mint is still accepted
+        if !ctx.accounts.mint_config.is_active {
+            return err!(BettingPoolsError::MintNotActive);
+        }
+
+        let amount = receive_stake(
+            &ctx.accounts.token_program,
+            &ctx.accounts.owner_token_account,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.program_token_account,
+            ctx.accounts.owner.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            &[],
+        )?;
+
+        let balance = &mut ctx.accounts.user_balance;
+        if balance.version == 0 {
+            ctx.accounts.betting_pools.open_balances += 1;
+        }
+        balance.owner = ctx.accounts.owner.key();
+        balance.mint = ctx.accounts.mint.key();
+        balance.amount = balance
+            .amount
+            .checked_add(amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+        balance.version = UserBalance::VERSION;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.balances = mint_config
+            .balances
+            .checked_add(amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        emit_cpi!(BalanceDeposited {
+            owner: balance.owner,
+            mint: balance.mint,
+            amount,
+            balance: balance.amount,
+        });
+
+        Ok(())
+    }
+
+    /// Move tokens out of the signer's internal balance back to their token account
+    pub fn withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return err!(BettingPoolsError::ZeroAmount);
+        }
+
+        let balance = &mut ctx.accounts.user_balance;
+        balance.amount = balance
+            .amount
+            .checked_sub(amount)
+            .ok_or(BettingPoolsError::InsufficientBalance)?;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.balances = mint_config.balances.saturating_sub(amount);
+
+        let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+        let signer = &[&betting_pools_seeds[..]];
+
+        transfer_tokens(
+            &ctx.accounts.token_program,
+            &ctx.accounts.program_token_account,
+            &ctx.accounts.mint,
+            &ctx.accounts.owner_token_account,
+            ctx.accounts.betting_pools.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+            signer,
+        )?;
+
+        emit_cpi!(BalanceWithdrawn {
+            owner: balance.owner,
+            mint: balance.mint,
+            amount,
+            balance: balance.amount,
+        });
+
+        Ok(())
+    }
+
+    /// Close an empty internal balance and return its rent to the owner
+    pub fn close_balance(ctx: Context<CloseBalance>) -> Result<()> {
+        if ctx.accounts.user_balance.amount > 0 {
+            return err!(BettingPoolsError::BalanceNotEmpty);
+        }
+
+        let betting_pools = &mut ctx.accounts.betting_pools;
+        betting_pools.open_balances = betting_pools.open_balances.saturating_sub(1);
+
+        Ok(())
+    }
+
+    /// Place a bet paid from the bettor's internal balance
+    pub fn place_bet_from_balance(
+        ctx: Context<PlaceBetFromBalance>,
+        option_index: u64,
+        amount: u64,
+    ) -> Result<()> {
+        let clock = Clock::get()?;
+        let mut pool = ctx.accounts.pool.load_mut()?;
+
+        // Bets on hidden pools only reveal their option after betting closes
+        if pool.is_hidden() {
+            return err!(BettingPoolsError::HiddenPool);
+        }
+
+        // Check if option index is valid
+        if option_index >= 2 {
+            return err!(BettingPoolsError::InvalidOptionIndex);
+        }
+
+        check_stake(
+            &pool,
+            &ctx.accounts.mint_config,
+            amount,
+            clock.unix_timestamp,
+        )?;
+
+        let balance = &mut ctx.accounts.user_balance;
+        balance.amount = balance
+            .amount
+            .checked_sub(amount)
+            .ok_or(BettingPoolsError::InsufficientBalance)?;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.balances = mint_config.balances.saturating_sub(amount);
+
+        let bet = &mut ctx.accounts.bet;
+        record_stake(
+            &mut pool,
+            &ctx.accounts.mint_config,
+            bet,
+            &mut ctx.accounts.pool_totals,
+            ctx.accounts.bettor.key(),
+            amount,
+            clock.unix_timestamp,
+        )?;
+        let event = place_option(&pool, bet, &mut ctx.accounts.pool_totals, option_index)?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+
+    /// Credit a bet's payout to its owner's internal balance. Anyone can crank this,
+    /// since the payout can only go to the bet's owner. A balance it opens is the
+    /// owner's to withdraw like any other before the program is decommissioned
+    pub fn claim_payout_to_balance(ctx: Context<ClaimPayoutToBalance>) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let bet = &mut ctx.accounts.bet;
+
+        // Check if the pool is graded
+        if pool.status() != PoolStatus::Graded {
+            return err!(BettingPoolsError::PoolNotGraded);
+        }
+
+        check_claim_window(&pool, Clock::get()?.unix_timestamp)?;
+
+        // Check if bet is already withdrawn
+        if bet.is_withdrawn {
+            return err!(BettingPoolsError::BetAlreadyWithdrawn);
+        }
+
+        // Mark bet as withdrawn
+        bet.is_withdrawn = true;
+
+        let payout = bet_payout(&ctx.accounts.pool_totals.settlement(&pool)?, bet);
+        let amount = payout.amount;
+        ctx.accounts.pool_totals.claimed = ctx
+            .accounts
+            .pool_totals
+            .claimed
+            .checked_add(amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        let balance = &mut ctx.accounts.user_balance;
+        if balance.version == 0 {
+            ctx.accounts.betting_pools.open_balances += 1;
+        }
+        balance.owner = bet.owner;
+        balance.mint = bet.mint;
+        balance.amount = balance
+            .amount
+            .checked_add(amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+        balance.version = UserBalance::VERSION;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.balances = mint_config
+            .balances
+            .checked_add(amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        if amount > 0 {
+            emit_cpi!(PayoutClaimed {
+                bet_id: bet.id,
+                pool_id: pool.id,
+                user: bet.owner,
+                amount,
+                mint: bet.mint,
+            });
+
+            emit_cpi!(BalanceCredited {
+                owner: balance.owner,
+                mint: balance.mint,
+                amount,
+                balance: balance.amount,
+            });
+        }
+
+        emit_cpi!(bet_settled(bet, &payout)?);
+
+        settle_bet_claim(&mut pool, &mut ctx.accounts.pool_totals);
+
+        Ok(())
+    }
+
+    /// Quote what a bet of `amount` on `option_index` would pay if that option wins,
+    /// against the pool's current totals. Nothing is written, so this can be simulated
+    /// without signing
+    pub fn quote_bet(ctx: Context<QuoteBet>, option_index: u64, amount: u64) -> Result<BetQuote> {
+        let mint_config = &ctx.accounts.mint_config;
+
+        // Check if option index is valid
+        if option_index >= 2 {
+            return err!(BettingPoolsError::InvalidOptionIndex);
+        }
+
+        // Run the bet through a copy of the pool and its totals, then grade the copy
+        let mut pool = *ctx.accounts.pool.load()?;
+        let mut pool_totals = match &ctx.accounts.pool_totals {
+            Some(pool_totals) => PoolTotals::clone(pool_totals),
+            None => PoolTotals::default(),
+        };
+        let mut bet = Bet::default();
+
+        let clock = Clock::get()?;
+        check_stake(&pool, mint_config, amount, clock.unix_timestamp)?;
+        record_stake(
+            &mut pool,
+            mint_config,
+            &mut bet,
+            &mut pool_totals,
+            Pubkey::default(),
+            amount,
+            clock.unix_timestamp,
+        )?;
+        place_option(&pool, &mut bet, &mut pool_totals, option_index)?;
+        pool.winning_option = option_index;
+
+        Ok(bet_quote(
+            amount,
+            bet_payout(
+                &pool_totals.live_settlement(&pool, mint_config.payout_fee_bp),
+                &bet,
+            ),
+        ))
+    }
+
+    /// Quote what claiming a bet pays. Before the pool is graded the bet is priced
+    /// as if its option wins
+    pub fn quote_claim(ctx: Context<QuoteClaim>) -> Result<BetQuote> {
+        let mut pool = *ctx.accounts.pool.load()?;
+        let bet = &ctx.accounts.bet;
+
+        // Check if bet is already withdrawn
+        if bet.is_withdrawn {
+            return err!(BettingPoolsError::BetAlreadyWithdrawn);
+        }
+
+        let pool_totals = &ctx.accounts.pool_totals;
+        let settlement = if pool.status() == PoolStatus::Graded {
+            pool_totals.settlement(&pool)?
+        } else {
+            pool.winning_option = bet.option;
+            pool_totals.live_settlement(&pool, ctx.accounts.mint_config.payout_fee_bp)
+        };
+
+        Ok(bet_quote(bet.amount, bet_payout(&settlement, bet)))
+    }
+
+    /// Compare a vault's balance with what is owed in its mint and emit a `SolvencyReport`.
+    /// Internal balances, offer escrows and bonds come from the mint's running totals.
+    /// Pools are passed in `remaining_accounts` in increasing id order, each followed by
+    /// its totals in the mint, or by its market for an LMSR pool:
+    /// `[pool, pool_totals | market, pool, pool_totals | market, ...]`.
+    /// A pool owes every stake while open and what its totals can still pay out once graded,
+    /// fees included, and a market owes what it holds. Pools left out aren't counted.
+    /// With `strict`, fails if the vault holds less than is owed
+    pub fn audit_solvency<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AuditSolvency<'info>>,
+        strict: bool,
+    ) -> Result<()> {
+        let mint_config = &ctx.accounts.mint_config;
+        let mut pools_owe: u64 = 0;
+        let mut pools_audited = 0;
+        let mut last_pool_id = 0;
+
+        for accounts in ctx.remaining_accounts.chunks(2) {
+            let [pool, pool_funds] = accounts else {
+                return err!(BettingPoolsError::InvalidAuditAccounts);
+            };
+            let pool = *AccountLoader::<PoolState>::try_from(pool)?.load()?;
+
+            // Each pool is counted once, with what it holds in the audited mint
+            if pool.id <= last_pool_id {
+                return err!(BettingPoolsError::InvalidAuditAccounts);
+            }
+            last_pool_id = pool.id;
+            pools_audited += 1;
+
+            let owed = match pool.mode() {
+                PoolMode::Parimutuel => {
+                    let pool_totals = Account::<PoolTotals>::try_from(pool_funds)?;
+                    if pool_totals.pool_id != pool.id || pool_totals.mint != mint_config.mint {
+                        return err!(BettingPoolsError::InvalidAuditAccounts);
+                    }
+                    pool_totals.liability(&pool)?
+                }
+                PoolMode::Lmsr => {
+                    let market = Account::<LmsrMarket>::try_from(pool_funds)?;
+                    if market.pool_id != pool.id || market.mint != mint_config.mint {
+                        return err!(BettingPoolsError::InvalidAuditAccounts);
+                    }
+                    market.balance
+                }
+            };
+            pools_owe = pools_owe
+                .checked_add(owed)
+                .ok_or(BettingPoolsError::MathOverflow)?;
+        }
+
+        let liabilities = [
+            mint_config.balances,
+            mint_config.escrowed,
+            mint_config.bonds,
+        ]
+        .into_iter()
+        .try_fold(pools_owe, u64::checked_add)
+        .ok_or(BettingPoolsError::MathOverflow)?;
+        let vault_balance = ctx.accounts.program_token_account.amount;
+        let shortfall = liabilities.saturating_sub(vault_balance);
+
+        emit_cpi!(SolvencyReport {
+            mint: mint_config.mint,
+            vault_balance,
+            liabilities,
+            shortfall,
+            pools_audited,
+            pools_owe,
+            balances: mint_config.balances,
+            escrowed: mint_config.escrowed,
+            bonds: mint_config.bonds,
+            slot: Clock::get()?.slot,
+            version: SolvencyReport::VERSION,
+        });
+
+        if strict && shortfall > 0 {
+            return err!(BettingPoolsError::SolvencyShortfall);
+        }
+
+        Ok(())
+    }
+
+    /// Turn a pool without bets into an LMSR market priced by the program.
+    /// The authority funds the market's worst-case loss of `liquidity * ln 2`
+    pub fn create_market<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateMarket<'info>>,
+        liquidity: u64,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+
+        // Check if pool is open for betting
+        if pool.status() != PoolStatus::Pending {
+            return err!(BettingPoolsError::PoolNotOpen);
+        }
+
+        // Only a fresh parimutuel pool can switch modes
+        if pool.next_bet_id != 1 || pool.is_hidden() || pool.mode() != PoolMode::Parimutuel {
+            return err!(BettingPoolsError::WrongPoolMode);
+        }
+
+        if liquidity == 0 {
+            return err!(BettingPoolsError::ZeroAmount);
+        }
+
+        // Check if the mint is still accepted
+        if !ctx.accounts.mint_config.is_active {
+            return err!(BettingPoolsError::MintNotActive);
+        }
+
+        let subsidy = lmsr_subsidy(liquidity);
+        receive_exact(
+            &ctx.accounts.token_program,
+            &ctx.accounts.authority_token_account,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.program_token_account,
+            ctx.accounts.authority.to_account_info(),
+            ctx.remaining_accounts,
+            subsidy,
+        )?;
+
+        let market = &mut ctx.accounts.market;
+        market.pool_id = pool.id;
+        market.mint = ctx.accounts.mint.key();
+        market.liquidity = liquidity;
+        market.shares = [0; 2];
+        market.redeemed = [0; 2];
+        market.subsidy = subsidy;
+        market.balance = subsidy;
+        market.surplus_withdrawn = false;
+        market.version = LmsrMarket::VERSION;
+
+        // The surplus left after grading is one more claim that keeps the pool unsettled
+        pool.set_mode(PoolMode::Lmsr);
+        add_claims(&mut pool, 1)?;
+
+        emit_cpi!(MarketCreated {
+            pool_id: pool.id,
+            mint: market.mint,
+            liquidity,
+            subsidy,
+        });
+
+        Ok(())
+    }
+
+    /// Buy `shares` of an option from a pool's LMSR market.
+    /// Fails if the quoted cost is above `max_cost`
+    pub fn buy_shares<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyShares<'info>>,
+        option_index: u64,
+        shares: u64,
+        max_cost: u64,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let clock = Clock::get()?;
+
+        if pool.mode() != PoolMode::Lmsr {
+            return err!(BettingPoolsError::WrongPoolMode);
+        }
+
+        // Check if betting period is closed
+        if clock.unix_timestamp > pool.bets_close_at {
+            return err!(BettingPoolsError::BettingPeriodClosed);
+        }
+
+        // Check if pool is open for betting
+        if pool.status() != PoolStatus::Pending {
+            return err!(BettingPoolsError::PoolNotOpen);
+        }
+
+        // Check if option index is valid
+        if option_index >= 2 {
+            return err!(BettingPoolsError::InvalidOptionIndex);
+        }
+
+        if shares == 0 {
+            return err!(BettingPoolsError::ZeroAmount);
+        }
+
+        let market = &ctx.accounts.market;
+        let cost = lmsr_buy_cost(market.shares, market.liquidity, option_index, shares)?;
+        if cost > max_cost {
+            return err!(BettingPoolsError::SlippageExceeded);
+        }
+
+        receive_exact(
+            &ctx.accounts.token_program,
+            &ctx.accounts.buyer_token_account,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.program_token_account,
+            ctx.accounts.buyer.to_account_info(),
+            ctx.remaining_accounts,
+            cost,
+        )?;
+
+        let market = &mut ctx.accounts.market;
+        market.shares[option_index as usize] = market.shares[option_index as usize]
+            .checked_add(shares)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+        market.balance = market
+            .balance
+            .checked_add(cost)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        // A new position is one more claim on the pool
+        let position = &mut ctx.accounts.position;
+        if position.version == 0 {
+            position.pool_id = pool.id;
+            position.owner = ctx.accounts.buyer.key();
+            position.version = LmsrPosition::VERSION;
+            add_claims(&mut pool, 1)?;
+        }
+        position.shares[option_index as usize] = position.shares[option_index as usize]
+            .checked_add(shares)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        emit_cpi!(SharesBought {
+            pool_id: pool.id,
+            user: position.owner,
+            option_index,
+            shares,
+            cost,
+            mint: market.mint,
+            market_shares: market.shares,
+        });
+
+        Ok(())
+    }
+
+    /// Redeem a position once its pool is graded.
+    /// Winning shares pay one token unit each, and every share pays half a unit on a draw
+    pub fn redeem_shares<'info>(
+        ctx: Context<'_, '_, 'info, 'info, RedeemShares<'info>>,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let position = &mut ctx.accounts.position;
+
+        // Check if the pool is graded
+        if pool.status() != PoolStatus::Graded {
+            return err!(BettingPoolsError::PoolNotGraded);
+        }
+        check_claim_window(&pool, Clock::get()?.unix_timestamp)?;
+
+        if position.is_redeemed {
+            return err!(BettingPoolsError::BetAlreadyWithdrawn);
+        }
+        position.is_redeemed = true;
+
+        let market = &mut ctx.accounts.market;
+        let payout = redeem_position(&pool, market, position);
+
+        if payout > 0 {
+            let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+            let signer = &[&betting_pools_seeds[..]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.program_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.owner_token_account,
+                ctx.accounts.betting_pools.to_account_info(),
+                ctx.remaining_accounts,
+                payout,
+                signer,
+            )?;
+        }
+
+        emit_cpi!(SharesRedeemed {
+            pool_id: pool.id,
+            user: position.owner,
+            shares: position.shares,
+            amount: payout,
+            mint: market.mint,
+        });
+
+        settle_claim(&mut pool);
+
+        Ok(())
+    }
+
+    /// Return what a graded market holds beyond its outstanding redemptions to the authority
+    pub fn withdraw_market_surplus<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawMarketSurplus<'info>>,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let market = &mut ctx.accounts.market;
+
+        // Check if the pool is graded
+        if pool.status() != PoolStatus::Graded {
+            return err!(BettingPoolsError::PoolNotGraded);
+        }
+
+        if market.surplus_withdrawn {
+            return err!(BettingPoolsError::BetAlreadyWithdrawn);
+        }
+        market.surplus_withdrawn = true;
+
+        // Keep enough to pay every share that hasn't been redeemed yet
+        let liability = if pool.is_draw() {
+            (market.shares[0] - market.redeemed[0] + market.shares[1] - market.redeemed[1])
+                .div_ceil(2)
+        } else {
+            let winner = pool.winning_option as usize;
+            market.shares[winner] - market.redeemed[winner]
+        };
+        let surplus = market.balance.saturating_sub(liability);
+        market.balance -= surplus;
+
+        if surplus > 0 {
+            let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+            let signer = &[&betting_pools_seeds[..]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.program_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.authority_token_account,
+                ctx.accounts.betting_pools.to_account_info(),
+                ctx.remaining_accounts,
+                surplus,
+                signer,
+            )?;
+        }
+
+        emit_cpi!(MarketSurplusWithdrawn {
+            pool_id: pool.id,
+            mint: market.mint,
+            amount: surplus,
+        });
+
+        settle_claim(&mut pool);
+        mark_settled(&mut ctx.accounts.betting_pools, &mut pool);
+
+        Ok(())
+    }
+
+    /// Offer a fixed-odds bet: escrow `stake` on an option and ask counterparties to
+    /// put up `ask` in total on the other option. The offer can be taken in parts
+    pub fn create_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CreateOffer<'info>>,
+        option_index: u64,
+        stake: u64,
+        ask: u64,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let clock = Clock::get()?;
+
+        // Check if betting period is closed
+        if clock.unix_timestamp > pool.bets_close_at {
+            return err!(BettingPoolsError::BettingPeriodClosed);
+        }
+
+        // Check if pool is open for betting
+        if pool.status() != PoolStatus::Pending {
+            return err!(BettingPoolsError::PoolNotOpen);
+        }
+
+        // Check if option index is valid
+        if option_index >= 2 {
+            return err!(BettingPoolsError::InvalidOptionIndex);
+        }
+
+        if stake == 0 || ask == 0 {
+            return err!(BettingPoolsError::ZeroAmount);
+        }
+
+        // Check if the mint is still accepted
+        if !ctx.accounts.mint_config.is_active {
+            return err!(BettingPoolsError::MintNotActive);
+        }
+
+        receive_exact(
+            &ctx.accounts.token_program,
+            &ctx.accounts.maker_token_account,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.program_token_account,
+            ctx.accounts.maker.to_account_info(),
+            ctx.remaining_accounts,
+            stake,
+        )?;
+
+        let offer = &mut ctx.accounts.offer;
+        offer.id = pool.next_offer_id;
+        pool.next_offer_id += 1;
+
+        offer.pool_id = pool.id;
+        offer.maker = ctx.accounts.maker.key();
+        offer.mint = ctx.accounts.mint.key();
+        offer.option = option_index;
+        offer.stake = stake;
+        offer.ask = ask;
+        offer.stake_remaining = stake;
+        offer.ask_remaining = ask;
+        offer.next_match_id = 1;
+        offer.is_cancelled = false;
+        offer.created_at = clock.unix_timestamp;
+        offer.version = Offer::VERSION;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.escrowed = mint_config
+            .escrowed
+            .checked_add(stake)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        // The unmatched stake is a claim until the offer is cancelled
+        add_claims(&mut pool, 1)?;
+
+        emit_cpi!(OfferCreated {
+            offer_id: offer.id,
+            pool_id: pool.id,
+            maker: offer.maker,
+            option_index,
+            stake,
+            ask,
+            mint: offer.mint,
+        });
+
+        Ok(())
+    }
+
+    /// Take `amount` of an offer's ask. The maker's stake is matched at the offer's odds
+    pub fn accept_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, AcceptOffer<'info>>,
+        amount: u64,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let clock = Clock::get()?;
+
+        // Check if betting period is closed
+        if clock.unix_timestamp > pool.bets_close_at {
+            return err!(BettingPoolsError::BettingPeriodClosed);
+        }
+
+        // Check if pool is open for betting
+        if pool.status() != PoolStatus::Pending {
+            return err!(BettingPoolsError::PoolNotOpen);
+        }
+
+        let offer = &ctx.accounts.offer;
+        if offer.is_cancelled {
+            return err!(BettingPoolsError::OfferClosed);
+        }
+        if ctx.accounts.taker.key() == offer.maker {
+            return err!(BettingPoolsError::OwnOffer);
+        }
+        if amount == 0 {
+            return err!(BettingPoolsError::ZeroAmount);
+        }
+        if amount > offer.ask_remaining {
+            return err!(BettingPoolsError::OfferExceeded);
+        }
+
+        // The last fill takes whatever is left so no dust stays locked in the offer
+        let maker_stake = if amount == offer.ask_remaining {
+            offer.stake_remaining
+        } else {
+            (offer.stake as u128 * amount as u128 / offer.ask as u128) as u64
+        };
+        if maker_stake == 0 {
+            return err!(BettingPoolsError::ZeroAmount);
+        }
+
+        receive_exact(
+            &ctx.accounts.token_program,
+            &ctx.accounts.taker_token_account,
+            &ctx.accounts.mint,
+            &mut ctx.accounts.program_token_account,
+            ctx.accounts.taker.to_account_info(),
+            ctx.remaining_accounts,
+            amount,
+        )?;
+
+        let offer = &mut ctx.accounts.offer;
+        offer.stake_remaining -= maker_stake;
+        offer.ask_remaining -= amount;
+
+        // The maker's stake moves from the offer to the match, next to the taker's
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.escrowed = mint_config
+            .escrowed
+            .checked_add(amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+
+        let offer_match = &mut ctx.accounts.offer_match;
+        offer_match.id = offer.next_match_id;
+        offer.next_match_id += 1;
+
+        offer_match.offer_id = offer.id;
+        offer_match.pool_id = offer.pool_id;
+        offer_match.maker = offer.maker;
+        offer_match.taker = ctx.accounts.taker.key();
+        offer_match.mint = offer.mint;
+        offer_match.maker_option = offer.option;
+        offer_match.maker_stake = maker_stake;
+        offer_match.taker_stake = amount;
+        offer_match.maker_claimed = false;
+        offer_match.taker_claimed = false;
+        offer_match.version = OfferMatch::VERSION;
+
+        // Each side of the match is claimed separately
+        add_claims(&mut pool, 2)?;
+
+        // The mint's totals freeze the fee the match pays when the pool is graded
+        open_totals(&mut pool, &mut ctx.accounts.pool_totals, offer_match.mint)?;
+
+        // A fully taken offer has nothing left to refund, so it closes and gives up
+        // its own claim on the pool
+        if offer.ask_remaining == 0 {
+            offer.is_cancelled = true;
+            settle_claim(&mut pool);
+        }
+
+        emit_cpi!(OfferAccepted {
+            offer_id: offer.id,
+            match_id: offer_match.id,
+            pool_id: offer.pool_id,
+            taker: offer_match.taker,
+            maker_stake,
+            taker_stake: amount,
+            mint: offer.mint,
+        });
+
+        Ok(())
+    }
+
+    /// Cancel an offer and refund the part of the maker's stake that was never matched
+    pub fn cancel_offer<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CancelOffer<'info>>,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let offer = &mut ctx.accounts.offer;
+
+        // Offers taken in full before they closed themselves can still be cancelled
+        // to give up their claim, with nothing left to refund
+        if offer.is_cancelled {
+            return err!(BettingPoolsError::OfferClosed);
+        }
+        offer.is_cancelled = true;
+
+        let refund = offer.stake_remaining;
+        offer.stake_remaining = 0;
+        offer.ask_remaining = 0;
+
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.escrowed = mint_config.escrowed.saturating_sub(refund);
+
+        if refund > 0 {
+            let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+            let signer = &[&betting_pools_seeds[..]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.program_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.maker_token_account,
+                ctx.accounts.betting_pools.to_account_info(),
+                ctx.remaining_accounts,
+                refund,
+                signer,
+            )?;
+        }
+
+        emit_cpi!(OfferCancelled {
+            offer_id: offer.id,
+            pool_id: offer.pool_id,
+            refund,
+            mint: offer.mint,
+        });
+
+        settle_claim(&mut pool);
+
+        Ok(())
+    }
+
+    /// Claim one side of a matched offer once the pool is graded.
+    /// The winning side takes both stakes less the mint's fee as frozen at grading, and a
+    /// draw refunds each side
+    pub fn claim_match<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimMatch<'info>>) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let offer_match = &mut ctx.accounts.offer_match;
+        let claimant = ctx.accounts.claimant.key();
+
+        // Check if the pool is graded
+        if pool.status() != PoolStatus::Graded {
+            return err!(BettingPoolsError::PoolNotGraded);
+        }
+        check_claim_window(&pool, Clock::get()?.unix_timestamp)?;
+
+        // Work out which side is being claimed
+        let is_maker = if claimant == offer_match.maker && !offer_match.maker_claimed {
+            offer_match.maker_claimed = true;
+            true
+        } else if claimant == offer_match.taker && !offer_match.taker_claimed {
+            offer_match.taker_claimed = true;
+            false
+        } else if claimant == offer_match.maker || claimant == offer_match.taker {
+            return err!(BettingPoolsError::BetAlreadyWithdrawn);
+        } else {
+            return err!(BettingPoolsError::NotBetOwner);
+        };
+
+        let own_stake = if is_maker {
+            offer_match.maker_stake
+        } else {
+            offer_match.taker_stake
+        };
+        let maker_won = pool.winning_option == offer_match.maker_option;
+
+        // Stakes leave the escrow as they are paid out, with the winner's fee
+        let released = if pool.is_draw() {
+            own_stake
+        } else if is_maker == maker_won {
+            offer_match.maker_stake + offer_match.taker_stake
+        } else {
+            0
+        };
+        let fee_bp = ctx.accounts.pool_totals.settlement(&pool)?.fee_bp;
+        let amount_to_transfer = if pool.is_draw() {
+            released
+        } else {
+            released - settlement::fee(released, fee_bp)
+        };
+        let mint_config = &mut ctx.accounts.mint_config;
+        mint_config.escrowed = mint_config.escrowed.saturating_sub(released);
+
+        if amount_to_transfer > 0 {
+            let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+            let signer = &[&betting_pools_seeds[..]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.program_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.claimant_token_account,
+                ctx.accounts.betting_pools.to_account_info(),
+                ctx.remaining_accounts,
+                amount_to_transfer,
+                signer,
+            )?;
+        }
+
+        emit_cpi!(MatchClaimed {
+            offer_id: offer_match.offer_id,
+            match_id: offer_match.id,
+            pool_id: offer_match.pool_id,
+            user: claimant,
+            amount: amount_to_transfer,
+            mint: offer_match.mint,
+        });
+
+        settle_claim(&mut pool);
+
+        Ok(())
+    }
+
+    /// Fix the question and options of a pool that has not taken any bets yet
+    pub fn update_pool(
+        ctx: Context<UpdatePool>,
+        question: String,
+        options: [String; 2],
+    ) -> Result<()> {
+        let pool = ctx.accounts.pool.load()?;
+        let metadata = &mut ctx.accounts.pool_metadata;
+
+        // Check if pool is still open
+        if pool.status() != PoolStatus::Pending {
+            return err!(BettingPoolsError::PoolNotOpen);
+        }
+
+        // Bettors must see the pool they staked on, so the text is frozen after the first stake
+        if pool.next_bet_id != 1 || pool.next_offer_id != 1 || pool.mode() != PoolMode::Parimutuel {
+            return err!(BettingPoolsError::PoolHasBets);
+        }
+
+        metadata.question = question;
+        metadata.options = options;
+
+        emit_cpi!(PoolUpdated {
+            pool_id: pool.id,
+            question: metadata.question.clone(),
+            options: metadata.options.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Move a pending pool's close time later. Bets already placed keep the weight
+    /// they were given, only later bets are weighted against the new close time
+    pub fn extend_bets_close_at(ctx: Context<ExtendBetsCloseAt>, bets_close_at: i64) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+
+        // Check if pool is still open
+        if pool.status() != PoolStatus::Pending {
+            return err!(BettingPoolsError::PoolNotOpen);
+        }
+
+        // Bets were placed against the old close time, so it can only be pushed back
+        if bets_close_at <= pool.bets_close_at {
+            return err!(BettingPoolsError::CloseTimeNotExtended);
+        }
+
+        // The reveal phase of a hidden pool starts when betting closes
+        if pool.is_hidden() && pool.reveal_ends_at <= bets_close_at {
+            return err!(BettingPoolsError::InvalidRevealWindow);
+        }
+
+        let previous_bets_close_at = pool.bets_close_at;
+        pool.bets_close_at = bets_close_at;
+
+        emit_cpi!(BetsCloseAtExtended {
+            pool_id: pool.id,
+            previous_bets_close_at,
+            bets_close_at,
+        });
+
+        Ok(())
+    }
+
+    /// Update the image URL for a pool
+    pub fn set_image(ctx: Context<SetImage>, image_url: String) -> Result<()> {
+        let metadata = &mut ctx.accounts.pool_metadata;
+
+        // Update the image URL
+        metadata.image_url = image_url.clone();
+
+        // Emit the PoolImageSet event
+        emit_cpi!(PoolImageSet {
+            pool_id: metadata.pool_id,
+            image_url,
+        });
+
+        Ok(())
+    }
+
+    /// Set the owner of the token accounts that receive fees
+    pub fn set_treasury(ctx: Context<SetTreasury>, treasury: Pubkey) -> Result<()> {
+        ctx.accounts.betting_pools.treasury = treasury;
+
+        emit_cpi!(TreasurySet { treasury });
+
+        Ok(())
+    }
+
+    /// Set how long after grading bets can be claimed. Pools keep the window they were
+    /// graded with. Zero lets claims stay open forever
+    pub fn set_claim_window(ctx: Context<SetClaimWindow>, claim_window: i64) -> Result<()> {
+        if claim_window < 0 {
+            return err!(BettingPoolsError::InvalidClaimWindow);
+        }
+
+        ctx.accounts.betting_pools.claim_window = claim_window;
+
+        emit_cpi!(ClaimWindowSet { claim_window });
+
+        Ok(())
+    }
+
+    /// Move what's left of a pool's stakes in one mint to the treasury once its claim
+    /// window has closed: unclaimed payouts and refunds, forfeited stakes, fees and
+    /// rounding dust. Each of the pool's mints is swept separately, and the pool counts
+    /// as settled once every one of its claims is gone
+    pub fn sweep_unclaimed<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepUnclaimed<'info>>,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let pool_totals = &mut ctx.accounts.pool_totals;
+
+        // Check if the pool is graded
+        if pool.status() != PoolStatus::Graded {
+            return err!(BettingPoolsError::PoolNotGraded);
+        }
+
+        let clock = Clock::get()?;
+        if pool.claim_ends_at == 0 || clock.unix_timestamp <= pool.claim_ends_at {
+            return err!(BettingPoolsError::ClaimWindowOpen);
+        }
+
+        if pool_totals.is_swept {
+            return err!(BettingPoolsError::PoolAlreadySwept);
+        }
+        pool_totals.is_swept = true;
+
+        // Claims paid refunds of unrevealed bets as well as revealed payouts, and
+        // forfeited unrevealed stakes were never paid. Totals migrated before stakes
+        // were tracked can have paid out more than they count as staked
+        let amount = pool_totals.staked.saturating_sub(pool_totals.claimed);
+        if amount > 0 {
+            let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+            let signer = &[&betting_pools_seeds[..]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.program_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.treasury_token_account,
+                ctx.accounts.betting_pools.to_account_info(),
+                ctx.remaining_accounts,
+                amount,
+                signer,
+            )?;
+        }
+
+        // Bets left unclaimed in this mint can no longer be claimed. Matches, positions
+        // and bonds are written off by `sweep_expired_claims`
+        pool.unclaimed_bets = pool
+            .unclaimed_bets
+            .saturating_sub(pool_totals.unclaimed_bets);
+        pool_totals.unclaimed_bets = 0;
+        pool.is_swept = 1;
+        mark_settled(&mut ctx.accounts.betting_pools, &mut pool);
+
+        emit_cpi!(UnclaimedSwept {
+            pool_id: pool.id,
+            mint: pool_totals.mint,
+            treasury_token_account: ctx.accounts.treasury_token_account.key(),
+            amount,
+            slot: clock.slot,
+            version: UnclaimedSwept::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Move what's left of a pool's matched offers, LMSR positions and bonds in one mint
+    /// to the treasury once its claim window has closed, writing off their claims.
+    /// The first `claims` of `remaining_accounts` are those `OfferMatch`, `LmsrPosition`
+    /// and `PoolBond` accounts, the rest are passed on to the token transfer
+    pub fn sweep_expired_claims<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepExpiredClaims<'info>>,
+        claims: u8,
+    ) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+
+        // Check if the pool is graded
+        if pool.status() != PoolStatus::Graded {
+            return err!(BettingPoolsError::PoolNotGraded);
+        }
+
+        let clock = Clock::get()?;
+        if pool.claim_ends_at == 0 || clock.unix_timestamp <= pool.claim_ends_at {
+            return err!(BettingPoolsError::ClaimWindowOpen);
+        }
+
+        if claims as usize > ctx.remaining_accounts.len() {
+            return err!(BettingPoolsError::InvalidClaimAccounts);
+        }
+        let (claim_accounts, transfer_accounts) = ctx.remaining_accounts.split_at(claims as usize);
+
+        let mint = ctx.accounts.mint.key();
+        let mint_config = &mut ctx.accounts.mint_config;
+        let mut amount = 0u64;
+        for account in claim_accounts {
+            if !account.is_writable {
+                return err!(BettingPoolsError::InvalidClaimAccounts);
+            }
+
+            let swept = if account
+                .try_borrow_data()?
+                .starts_with(OfferMatch::DISCRIMINATOR)
+            {
+                let mut offer_match = Account::<OfferMatch>::try_from(account)?;
+                if offer_match.pool_id != pool.id || offer_match.mint != mint {
+                    return err!(BettingPoolsError::InvalidClaimAccounts);
+                }
+
+                let escrow = match_escrow(&offer_match, &pool);
+                let sides: &mut OfferMatch = &mut offer_match;
+                for claimed in [&mut sides.maker_claimed, &mut sides.taker_claimed] {
+                    if !*claimed {
+                        *claimed = true;
+                        settle_claim(&mut pool);
+                    }
+                }
+                mint_config.escrowed = mint_config.escrowed.saturating_sub(escrow);
+                offer_match.exit(ctx.program_id)?;
+                escrow
+            } else if account
+                .try_borrow_data()?
+                .starts_with(LmsrPosition::DISCRIMINATOR)
+            {
+                let mut position = Account::<LmsrPosition>::try_from(account)?;
+                let market = ctx
+                    .accounts
+                    .market
+                    .as_mut()
+                    .ok_or(BettingPoolsError::InvalidClaimAccounts)?;
+                if position.pool_id != pool.id || position.is_redeemed {
+                    return err!(BettingPoolsError::InvalidClaimAccounts);
+                }
+
+                position.is_redeemed = true;
+                let payout = redeem_position(&pool, market, &position);
+                settle_claim(&mut pool);
+                position.exit(ctx.program_id)?;
+                payout
+            } else {
+                let mut bond = Account::<PoolBond>::try_from(account)?;
+                if bond.pool_id != pool.id || bond.mint != mint || bond.is_settled {
+                    return err!(BettingPoolsError::InvalidClaimAccounts);
+                }
+
+                bond.is_settled = true;
+                mint_config.bonds = mint_config.bonds.saturating_sub(bond.amount);
+                settle_claim(&mut pool);
+                bond.exit(ctx.program_id)?;
+                bond.amount
+            };
+            amount = amount
+                .checked_add(swept)
+                .ok_or(BettingPoolsError::MathOverflow)?;
+        }
+
+        if amount > 0 {
+            let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+            let signer = &[&betting_pools_seeds[..]];
+
+            transfer_tokens(
+                &ctx.accounts.token_program,
+                &ctx.accounts.program_token_account,
+                &ctx.accounts.mint,
+                &ctx.accounts.treasury_token_account,
+                ctx.accounts.betting_pools.to_account_info(),
+                transfer_accounts,
+                amount,
+                signer,
+            )?;
+        }
+
+        mark_settled(&mut ctx.accounts.betting_pools, &mut pool);
+
+        emit_cpi!(ExpiredClaimsSwept {
+            pool_id: pool.id,
+            mint,
+            treasury_token_account: ctx.accounts.treasury_token_account.key(),
+            claims,
+            amount,
+            slot: clock.slot,
+            version: ExpiredClaimsSwept::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Count a graded pool whose claims have all been paid out or cancelled towards
+    /// the settled pools `close_betting_pool` waits for. Claims don't count the pool
+    /// themselves so they never write the global state; anyone can call this once
+    pub fn mark_pool_settled(ctx: Context<MarkPoolSettled>) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+
+        if pool.is_settled() {
+            return err!(BettingPoolsError::PoolAlreadySettled);
+        }
+        if !mark_settled(&mut ctx.accounts.betting_pools, &mut pool) {
+            return err!(BettingPoolsError::PoolNotSettled);
+        }
+
+        emit_cpi!(PoolSettled {
+            pool_id: pool.id,
+            slot: Clock::get()?.slot,
+            version: PoolSettled::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Freeze the payouts of a graded pool's totals in one mint at the mint's current fee.
+    /// Only totals of pools graded before payouts were frozen, or migrated with totals
+    /// `grade_bet` didn't know about, are left unsettled. Anyone can call this so their
+    /// bets can be claimed
+    pub fn settle_pool_totals(ctx: Context<SettlePoolTotals>) -> Result<()> {
+        let pool = ctx.accounts.pool.load()?;
+        let pool_totals = &mut ctx.accounts.pool_totals;
+
+        // Check if the pool is graded
+        if pool.status() != PoolStatus::Graded {
+            return err!(BettingPoolsError::PoolNotGraded);
+        }
+
+        if pool_totals.is_settled {
+            return err!(BettingPoolsError::TotalsAlreadySettled);
+        }
+        pool_totals.settle(&pool, ctx.accounts.mint_config.payout_fee_bp);
+
+        Ok(())
+    }
+
+    /// Decommission the program
+    /// Only allowed once every pool is graded and fully claimed and every internal balance
+    /// is closed. Every accepted mint must be passed in `remaining_accounts` as
+    /// `[mint_config, vault, mint, treasury_token_account, token_program]` so the fees left
+    /// in its vault are moved to the treasury and the vault and config are closed.
+    /// Any accounts after them are extra accounts for Token-2022 transfer hooks.
+    pub fn close_betting_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CloseBettingPool<'info>>,
+    ) -> Result<()> {
+        let betting_pools = &ctx.accounts.betting_pools;
+        let authority = ctx.accounts.authority.to_account_info();
+
+        // Check that no pool is pending or has unclaimed bets
+        if betting_pools.settled_pools < betting_pools.next_pool_id - 1 {
+            return err!(BettingPoolsError::PoolsNotSettled);
+        }
+
+        // Internal balances are owed to users, not swept to the treasury
+        if betting_pools.open_balances > 0 {
+            return err!(BettingPoolsError::OpenBalances);
+        }
+
+        // Check that every accepted mint is being drained
+        let mint_accounts = betting_pools.mint_count as usize * 5;
+        if ctx.remaining_accounts.len() < mint_accounts {
+            return err!(BettingPoolsError::VaultsNotDrained);
+        }
+
+        let betting_pools_seeds = &[BETTING_POOLS_SEED, &[ctx.bumps.betting_pools]];
+        let signer = &[&betting_pools_seeds[..]];
+
+        for accounts in ctx.remaining_accounts[..mint_accounts].chunks(5) {
+            let mint_config = Account::<MintConfig>::try_from(&accounts[0])?;
+            let vault = InterfaceAccount::<TokenAccount>::try_from(&accounts[1])?;
+            let mint = InterfaceAccount::<Mint>::try_from(&accounts[2])?;
+            let treasury_token_account = InterfaceAccount::<TokenAccount>::try_from(&accounts[3])?;
+            let token_program = Interface::<TokenInterface>::try_from(&accounts[4])?;
+
+            if vault.key() != mint_config.vault {
+                return err!(BettingPoolsError::InvalidVault);
+            }
+            if mint.key() != mint_config.mint || treasury_token_account.mint != mint.key() {
+                return err!(BettingPoolsError::InvalidMint);
+            }
+            if treasury_token_account.owner != betting_pools.treasury {
+                return err!(BettingPoolsError::InvalidTreasury);
+            }
+            if *accounts[1].owner != token_program.key() {
+                return err!(BettingPoolsError::InvalidVault);
+            }
+
+            // Move the remaining fees to the treasury
+            let amount = vault.amount;
+            if amount > 0 {
+                transfer_tokens(
+                    &token_program,
+                    &vault,
+                    &mint,
+                    &treasury_token_account,
+                    betting_pools.to_account_info(),
+                    ctx.remaining_accounts,
+                    amount,
+                    signer,
+                )?;
+            }
+
+            // Close the empty vault and the mint config
+            token_interface::close_account(CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                CloseAccount {
+                    account: vault.to_account_info(),
+                    destination: authority.clone(),
+                    authority: betting_pools.to_account_info(),
+                },
+                signer,
+            ))?;
+            mint_config.close(authority.clone())?;
+
+            emit_cpi!(VaultDrained {
+                mint: mint.key(),
+                treasury_token_account: treasury_token_account.key(),
+                amount,
+            });
+        }
+
+        let clock = Clock::get()?;
+        emit_cpi!(ProgramClosed {
+            authority: authority.key(),
+            treasury: betting_pools.treasury,
+            mints_closed: betting_pools.mint_count,
+            decommissioned_at: clock.unix_timestamp,
+            slot: clock.slot,
+            version: ProgramClosed::VERSION,
+        });
+
+        Ok(())
+    }
+
+    /// Upgrade the BettingPools account to the current layout
+    /// Must run before any pool or bet is migrated
+    pub fn migrate_betting_pools(ctx: Context<MigrateBettingPools>) -> Result<()> {
+        migrations::migrate_betting_pools(ctx)
+    }
+
+    /// Upgrade a pool account to the current layout
+    /// Legacy Borsh pools are split into a zero-copy PoolState and a PoolMetadata account.
+    /// Pools written before their totals were counted take every one of their totals at
+    /// the front of `remaining_accounts`. Pools written before claims were counted take
+    /// their bets after them, in increasing id order, to count the ones still unclaimed
+    pub fn migrate_pool<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigratePool<'info>>,
+        pool_id: u64,
+    ) -> Result<()> {
+        migrations::migrate_pool(ctx, pool_id)
+    }
+
+    /// Upgrade a bet account to the current layout
+    pub fn migrate_bet(ctx: Context<MigrateBet>, _pool_id: u64, _bet_id: u64) -> Result<()> {
+        migrations::migrate_bet(ctx)
+    }
+
+    /// Upgrade a pool's totals for one mint to the current layout
+    /// Totals written before their bets were counted take every one of the pool's bets
+    /// in the mint in `remaining_accounts`, migrated and in increasing id order, to
+    /// count the ones still unclaimed
+    pub fn migrate_pool_totals(
+        ctx: Context<MigratePoolTotals>,
+        pool_id: u64,
+        mint: Pubkey,
+    ) -> Result<()> {
+        migrations::migrate_pool_totals(ctx, pool_id, mint)
+    }
+
+    /// Upgrade a mint config to the current layout. Configs from before the mint's
+    /// balances, offers and bonds were totalled recount them from every `UserBalance`,
+    /// `Offer` and `PoolBond` in the mint and every `OfferMatch` followed by its pool,
+    /// passed in `remaining_accounts`
+    pub fn migrate_mint_config<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateMintConfig<'info>>,
+        mint: Pubkey,
+    ) -> Result<()> {
+        migrations::migrate_mint_config(ctx, mint)
+    }
+}
+
+//--------- HELPERS ---------//
+
+/// Move tokens with `transfer_checked` so both SPL Token and Token-2022 mints work.
+/// Any extra accounts required by a Token-2022 transfer hook are taken from
+/// `remaining_accounts`.
+#[allow(clippy::too_many_arguments)]
+fn transfer_tokens<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &InterfaceAccount<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    invoke_transfer_checked(
+        token_program.key,
+        from.to_account_info(),
+        mint.to_account_info(),
+        to.to_account_info(),
+        authority,
+        remaining_accounts,
+        amount,
+        mint.decimals,
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+/// Take a bettor's stake and fill in everything about a new bet except its option.
+/// Returns the amount that reached the vault.
+fn stake_bet<'info>(
+    accounts: &mut PlaceBet<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    betting_pools_bump: u8,
+    amount: u64,
+) -> Result<u64> {
+    let clock = Clock::get()?;
+    check_stake(
+        &*accounts.pool.load()?,
+        &accounts.mint_config,
+        amount,
+        clock.unix_timestamp,
+    )?;
+
+    // A session key spends through the owner's delegate approval to the betting pools PDA
+    let owner = acting_owner(&accounts.session, &accounts.bettor.key());
+    let betting_pools_seeds = &[BETTING_POOLS_SEED, &[betting_pools_bump]];
+    let (authority, signer): (AccountInfo<'info>, &[&[&[u8]]]) = match &mut accounts.session {
+        Some(session) => {
+            spend_session(session, &accounts.mint.key(), amount, clock.unix_timestamp)?;
+            (
+                accounts.betting_pools.to_account_info(),
+                &[&betting_pools_seeds[..]],
+            )
+        }
+        None => (accounts.bettor.to_account_info(), &[]),
+    };
+
+    // Transfer tokens from bettor to program account
+    let amount = receive_stake(
+        &accounts.token_program,
+        &accounts.bettor_token_account,
+        &accounts.mint,
+        &mut accounts.program_token_account,
+        authority,
+        remaining_accounts,
+        amount,
+        signer,
+    )?;
+
+    record_stake(
+        &mut *accounts.pool.load_mut()?,
+        &accounts.mint_config,
+        &mut accounts.bet,
+        &mut accounts.pool_totals,
+        owner,
+        amount,
+        clock.unix_timestamp,
+    )?;
+
+    Ok(amount)
+}
+
+/// Set a staked bet's option and add it to the pool's totals.
+/// Returns the event announcing the bet
+fn place_option(
+    pool: &PoolState,
+    bet: &mut Bet,
+    pool_totals: &mut PoolTotals,
+    option_index: u64,
+) -> Result<BetPlaced> {
+    bet.option = option_index;
+    bet.is_revealed = true;
+    bet.weight = bet_weight(pool, bet.amount, bet.created_at);
+
+    // Update the pool's totals for this mint
+    pool_totals.bet_totals[option_index as usize] = pool_totals.bet_totals[option_index as usize]
+        .checked_add(bet.amount)
+        .ok_or(BettingPoolsError::MathOverflow)?;
+    pool_totals.weighted_totals[option_index as usize] = pool_totals.weighted_totals
+        [option_index as usize]
+        .checked_add(bet.weight)
+        .ok_or(BettingPoolsError::MathOverflow)?;
+
+    Ok(BetPlaced {
+        bet_id: bet.id,
+        pool_id: bet.pool_id,
+        user: bet.owner,
+        option_index,
+        amount: bet.amount,
+        weight: bet.weight,
+        mint: bet.mint,
+        created_at: bet.created_at,
+        bet_totals: pool_totals.bet_totals,
+        weighted_totals: pool_totals.weighted_totals,
+        slot: Clock::get()?.slot,
+        version: BetPlaced::VERSION,
+    })
+}
+
+/// Wallet a bet is placed or claimed for: the session's owner when a session key
+/// signs, otherwise the signer itself
+pub fn acting_owner(session: &Option<Account<Session>>, signer: &Pubkey) -> Pubkey {
+    session.as_ref().map_or(*signer, |session| session.owner)
+}
+
+/// Charge a stake to a session's spend cap
+fn spend_session(session: &mut Session, mint: &Pubkey, amount: u64, now: i64) -> Result<()> {
+    if now > session.expires_at {
+        return err!(BettingPoolsError::SessionExpired);
+    }
+    if !session.allowed_mints.contains(mint) {
+        return err!(BettingPoolsError::SessionMintNotAllowed);
+    }
+
+    session.spent = session
+        .spent
+        .checked_add(amount)
+        .filter(|spent| *spent <= session.spend_cap)
+        .ok_or(BettingPoolsError::SessionSpendCapExceeded)?;
+
+    Ok(())
+}
+
+/// Check that a pool and mint accept a new stake
+fn check_stake(pool: &PoolState, mint_config: &MintConfig, amount: u64, now: i64) -> Result<()> {
+    // LMSR pools sell shares through `buy_shares` instead
+    if pool.mode() != PoolMode::Parimutuel {
+        return err!(BettingPoolsError::WrongPoolMode);
+    }
+
+    // Check if betting period is closed
+    if now > pool.bets_close_at {
+        return err!(BettingPoolsError::BettingPeriodClosed);
+    }
+
+    // Check if pool is open for betting
+    if pool.status() != PoolStatus::Pending {
+        return err!(BettingPoolsError::PoolNotOpen);
+    }
+
+    // Check if amount is valid
+    if amount == 0 {
+        return err!(BettingPoolsError::ZeroAmount);
+    }
+
+    // Check if the mint is still accepted
+    if !mint_config.is_active {
+        return err!(BettingPoolsError::MintNotActive);
+    }
+
+    Ok(())
+}
+
+/// Move a stake into the vault. Token-2022 mints with a transfer fee deliver less
+/// than `amount`, so only the amount that actually reached the vault is returned
+#[allow(clippy::too_many_arguments)]
+fn receive_stake<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    vault: &mut InterfaceAccount<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<u64> {
+    let vault_balance_before = vault.amount;
+    transfer_tokens(
+        token_program,
+        from,
+        mint,
+        vault,
+        authority,
+        remaining_accounts,
+        amount,
+        signer_seeds,
+    )?;
+
+    vault.reload()?;
+    Ok(vault
+        .amount
+        .checked_sub(vault_balance_before)
+        .ok_or(BettingPoolsError::TokenTransferFailed)?)
+}
+
+/// Fill in a new bet for a stake that has reached the vault
+fn record_stake(
+    pool: &mut PoolState,
+    mint_config: &MintConfig,
+    bet: &mut Bet,
+    pool_totals: &mut PoolTotals,
+    owner: Pubkey,
+    amount: u64,
+    now: i64,
+) -> Result<()> {
+    if amount < mint_config.min_bet {
+        return err!(BettingPoolsError::BetBelowMinimum);
+    }
+    // A transfer fee can take the whole of a small stake
+    if amount == 0 {
+        return err!(BettingPoolsError::ZeroStake);
+    }
+
+    // Initialize the bet and increment the pool's bet ID counter
+    bet.id = pool.next_bet_id;
+    pool.next_bet_id += 1;
+
+    bet.owner = owner;
+    bet.amount = amount;
+    bet.pool_id = pool.id;
+    bet.created_at = now;
+    bet.updated_at = now;
+    bet.is_withdrawn = false;
+    bet.mint = mint_config.mint;
+    bet.version = Bet::VERSION;
+
+    add_claims(pool, 1)?;
+
+    open_totals(pool, pool_totals, mint_config.mint)?;
+    pool_totals.staked = pool_totals
+        .staked
+        .checked_add(amount)
+        .ok_or(BettingPoolsError::MathOverflow)?;
+    pool_totals.unclaimed_bets = pool_totals
+        .unclaimed_bets
+        .checked_add(1)
+        .ok_or(BettingPoolsError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Fill in a pool's totals in `mint`, counting them the first time a bet or match uses them
+fn open_totals(pool: &mut PoolState, pool_totals: &mut PoolTotals, mint: Pubkey) -> Result<()> {
+    if pool_totals.mint == Pubkey::default() {
+        pool.totals_count = pool
+            .totals_count
+            .checked_add(1)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+    }
+    pool_totals.pool_id = pool.id;
+    pool_totals.mint = mint;
+    pool_totals.version = PoolTotals::VERSION;
+
+    Ok(())
+}
+
+/// Message a bettor signs to let a relayer place a bet for them
+pub fn bet_intent_message(
+    pool_id: u64,
+    option_index: u64,
+    amount: u64,
+    mint: &Pubkey,
+    nonce: u64,
+    expires_at: i64,
+) -> Vec<u8> {
+    [
+        BET_INTENT_DOMAIN,
+        crate::ID.as_ref(),
+        &pool_id.to_le_bytes(),
+        &option_index.to_le_bytes(),
+        &amount.to_le_bytes(),
+        mint.as_ref(),
+        &nonce.to_le_bytes(),
+        &expires_at.to_le_bytes(),
+    ]
+    .concat()
+}
+
+/// Check that the instruction before the current one is an ed25519 program
+/// instruction verifying `signer`'s signature over `message`
+fn verify_ed25519_signature(
+    instructions: &AccountInfo,
+    signer: &Pubkey,
+    message: &[u8],
+) -> Result<()> {
+    let current_index = load_current_index_checked(instructions)?;
+    if current_index == 0 {
+        return err!(BettingPoolsError::InvalidSignature);
+    }
+    let ix = load_instruction_at_checked(current_index as usize - 1, instructions)?;
+    if ix.program_id != ed25519_program::ID || !ix.accounts.is_empty() {
+        return err!(BettingPoolsError::InvalidSignature);
+    }
+
+    // One signature whose offsets header follows the count and a padding byte
+    let data = &ix.data;
+    if data.len() < 16 || data[0] != 1 {
+        return err!(BettingPoolsError::InvalidSignature);
+    }
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let public_key_offset = read_u16(6) as usize;
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+
+    // The signature, key and message must all live in the ed25519 instruction itself
+    if read_u16(4) != u16::MAX || read_u16(8) != u16::MAX || read_u16(14) != u16::MAX {
+        return err!(BettingPoolsError::InvalidSignature);
+    }
+
+    if data.get(public_key_offset..public_key_offset + 32) != Some(signer.as_ref())
+        || data.get(message_offset..message_offset + message_size) != Some(message)
+    {
+        return err!(BettingPoolsError::InvalidSignature);
+    }
+
+    Ok(())
+}
+
+/// Validate a new pool's settings and fill in its state and metadata.
+/// Returns the event announcing the pool
+#[allow(clippy::too_many_arguments)]
+fn init_pool(
+    betting_pools: &mut BettingPoolsState,
+    pool: &mut PoolState,
+    metadata: &mut PoolMetadata,
+    question: String,
+    options: [String; 2],
+    bets_close_at: i64,
+    original_truth_social_post_id: String,
+    image_url: String,
+    hidden_bets: Option<HiddenBetsConfig>,
+    time_weighting: Option<TimeWeighting>,
+) -> Result<PoolCreated> {
+    // Check if bets close time is in the future
+    let clock = Clock::get()?;
+    if bets_close_at <= clock.unix_timestamp {
+        return err!(BettingPoolsError::BetsCloseTimeInPast);
+    }
+
+    // The reveal phase of a hidden pool starts when betting closes
+    if let Some(config) = hidden_bets {
+        if config.reveal_ends_at <= bets_close_at {
+            return err!(BettingPoolsError::InvalidRevealWindow);
+        }
+    }
+
+    // Early bets can at most double their weight
+    if let Some(weighting) = time_weighting {
+        if weighting.max_bonus_bp > MAX_EARLY_BONUS_BP {
+            return err!(BettingPoolsError::InvalidTimeWeighting);
+        }
+    }
+
+    // Set the pool ID and increment the counter
+    let pool_id = betting_pools.next_pool_id;
+    betting_pools.next_pool_id += 1;
+
+    // Initialize the pool
+    pool.id = pool_id;
+    pool.bets_close_at = bets_close_at;
+    pool.winning_option = 0;
+    pool.set_status(PoolStatus::Pending);
+    pool.is_draw = 0;
+    pool.created_at = clock.unix_timestamp;
+    pool.version = PoolState::VERSION;
+    pool.next_bet_id = 1;
+    pool.next_offer_id = 1;
+    if let Some(config) = hidden_bets {
+        pool.is_hidden = 1;
+        pool.forfeit_unrevealed = config.forfeit_unrevealed as u8;
+        pool.reveal_ends_at = config.reveal_ends_at;
+    }
+    if let Some(weighting) = time_weighting {
+        pool.weight_curve = weighting.curve as u8;
+        pool.early_bonus_bp = weighting.max_bonus_bp;
+    }
+
+    metadata.pool_id = pool_id;
+    metadata.question = question;
+    metadata.options = options;
+    metadata.original_truth_social_post_id = original_truth_social_post_id;
+    metadata.image_url = image_url;
+    metadata.version = PoolMetadata::VERSION;
+
+    Ok(PoolCreated {
+        pool_id,
+        question: metadata.question.clone(),
+        options: metadata.options.clone(),
+        bets_close_at: pool.bets_close_at,
+        original_truth_social_post_id: metadata.original_truth_social_post_id.clone(),
+        image_url: metadata.image_url.clone(),
+        created_at: pool.created_at,
+        reveal_ends_at: pool.reveal_ends_at,
+        forfeit_unrevealed: pool.forfeit_unrevealed(),
+        weight_curve: pool.weight_curve(),
+        early_bonus_bp: pool.early_bonus_bp,
+    })
+}
+
+/// Grade a pool with `response_option` (0 or 1 for the winner, 2 for a draw).
+/// Returns the event announcing the result
+fn close_pool(
+    betting_pools: &mut BettingPoolsState,
+    pool: &mut PoolState,
+    response_option: u64,
+    now: i64,
+) -> Result<PoolClosed> {
+    pool.set_status(PoolStatus::Graded);
+
+    if response_option == 0 {
+        pool.winning_option = 0;
+    } else if response_option == 1 {
+        pool.winning_option = 1;
+    } else if response_option == 2 {
+        pool.is_draw = 1;
+    } else {
+        return err!(BettingPoolsError::GradingError);
+    }
+
+    // Set decision time to current time
+    pool.decision_time = now;
+    if betting_pools.claim_window > 0 {
+        pool.claim_ends_at = now.saturating_add(betting_pools.claim_window);
+    }
+
+    // A pool without bets has nothing left to claim
+    mark_settled(betting_pools, pool);
+
+    Ok(PoolClosed {
+        pool_id: pool.id,
+        selected_option: response_option,
+        decision_time: pool.decision_time,
+        is_draw: pool.is_draw(),
+        slot: Clock::get()?.slot,
+        version: PoolClosed::VERSION,
+    })
+}
+
+/// Freeze the payouts of a pool that was just graded in every mint it took bets in.
+/// `accounts` holds each of the pool's totals followed by its mint's config
+fn settle_all_totals<'info>(
+    pool: &PoolState,
+    accounts: &'info [AccountInfo<'info>],
+    program_id: &Pubkey,
+) -> Result<()> {
+    if accounts.len() != 2 * pool.totals_count as usize {
+        return err!(BettingPoolsError::InvalidSettlementAccounts);
+    }
+
+    for accounts in accounts.chunks(2) {
+        let [pool_totals, mint_config] = accounts else {
+            return err!(BettingPoolsError::InvalidSettlementAccounts);
+        };
+        let mut pool_totals = Account::<PoolTotals>::try_from(pool_totals)?;
+        let mint_config = Account::<MintConfig>::try_from(mint_config)?;
+        // Totals passed twice are already settled the second time
+        if pool_totals.pool_id != pool.id
+            || pool_totals.mint != mint_config.mint
+            || pool_totals.is_settled
+            || !pool_totals.to_account_info().is_writable
+        {
+            return err!(BettingPoolsError::InvalidSettlementAccounts);
+        }
+
+        pool_totals.settle(pool, mint_config.payout_fee_bp);
+        pool_totals.exit(program_id)?;
+    }
+
+    Ok(())
+}
+
+/// Record claims on a pool that must be paid out or cancelled before it is settled
+fn add_claims(pool: &mut PoolState, claims: u64) -> Result<()> {
+    pool.unclaimed_bets = pool
+        .unclaimed_bets
+        .checked_add(claims)
+        .ok_or(BettingPoolsError::MathOverflow)?;
+
+    Ok(())
+}
+
+/// Record that one of a pool's claims has been paid out or cancelled.
+/// The pool is settled once its last claim is gone, but claims leave the global
+/// state alone so they don't contend for it. `mark_settled` counts the pool
+fn settle_claim(pool: &mut PoolState) {
+    settle_claims(pool, 1);
+}
+
+/// Record that `claims` of a pool's claims have been paid out or cancelled at once
+fn settle_claims(pool: &mut PoolState, claims: u64) {
+    pool.unclaimed_bets = pool.unclaimed_bets.saturating_sub(claims);
+}
+
+/// Record that a bet has been paid out or withdrawn, in its pool and its mint's totals
+fn settle_bet_claim(pool: &mut PoolState, pool_totals: &mut PoolTotals) {
+    pool_totals.unclaimed_bets = pool_totals.unclaimed_bets.saturating_sub(1);
+    settle_claim(pool);
+}
+
+/// Count a graded pool whose claims are all gone towards the settled pools
+/// `close_betting_pool` waits for. Each pool is counted once.
+/// Returns whether the pool was counted by this call
+fn mark_settled(betting_pools: &mut BettingPoolsState, pool: &mut PoolState) -> bool {
+    if pool.is_settled() || pool.status() == PoolStatus::Pending || pool.unclaimed_bets > 0 {
+        return false;
+    }
+
+    pool.is_settled = 1;
+    betting_pools.settled_pools += 1;
+    true
+}
+
+/// Fail unless a pool has gone ungraded for `EMERGENCY_WITHDRAW_DELAY` after its bets
+/// closed, or after its reveal phase for a hidden pool
+fn check_emergency_delay(pool: &PoolState, now: i64) -> Result<()> {
+    if pool.status() != PoolStatus::Pending {
+        return err!(BettingPoolsError::PoolNotOpen);
+    }
+
+    let gradable_at = pool.bets_close_at.max(pool.reveal_ends_at);
+    if now <= gradable_at.saturating_add(EMERGENCY_WITHDRAW_DELAY) {
+        return err!(BettingPoolsError::EmergencyDelayNotElapsed);
+    }
+
+    Ok(())
+}
+
+/// Fail once a graded pool's claim window has closed
+fn check_claim_window(pool: &PoolState, now: i64) -> Result<()> {
+    if pool.claim_ends_at != 0 && now > pool.claim_ends_at {
+        return err!(BettingPoolsError::ClaimWindowExpired);
+    }
+
+    Ok(())
+}
+
+/// Pull exactly `amount` into a vault. Used where the program's books must match
+/// the vault, so mints that take a transfer fee are rejected.
+fn receive_exact<'info>(
+    token_program: &Interface<'info, TokenInterface>,
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    vault: &mut InterfaceAccount<'info, TokenAccount>,
+    authority: AccountInfo<'info>,
+    remaining_accounts: &[AccountInfo<'info>],
+    amount: u64,
+) -> Result<()> {
+    let vault_balance_before = vault.amount;
+    transfer_tokens(
+        token_program,
+        from,
+        mint,
+        vault,
+        authority,
+        remaining_accounts,
+        amount,
+        &[],
+    )?;
+
+    vault.reload()?;
+    if vault.amount.checked_sub(vault_balance_before) != Some(amount) {
+        return err!(BettingPoolsError::TokenTransferFailed);
+    }
+
+    Ok(())
+}
+
+/// Fixed-point scale of the LMSR math
+const LMSR_SCALE: u128 = 1_000_000_000_000;
+/// e^-1 scaled by `LMSR_SCALE`
+const LMSR_EXP_NEG_ONE: u128 = 367_879_441_171;
+/// ln 2 scaled by `LMSR_SCALE`
+const LMSR_LN_2: u128 = 693_147_180_560;
+
+/// e^-x for x >= 0, with x and the result scaled by `LMSR_SCALE`
+fn exp_neg(x: u128) -> u128 {
+    let whole = x / LMSR_SCALE;
+    if whole >= 40 {
+        return 0;
+    }
+
+    // Taylor series for the fractional part, then one factor of e^-1 per whole unit
+    let fraction = (x % LMSR_SCALE) as i128;
+    let scale = LMSR_SCALE as i128;
+    let mut result = scale;
+    let mut term = scale;
+    let mut n = 1;
+    while term != 0 {
+        term = -term * fraction / (n * scale);
+        result += term;
+        n += 1;
+    }
+
+    let mut result = result as u128;
+    for _ in 0..whole {
+        result = result * LMSR_EXP_NEG_ONE / LMSR_SCALE;
+    }
+    result
+}
+
+/// ln(1 + y) for 0 <= y <= 1, with y and the result scaled by `LMSR_SCALE`
+fn ln_1p(y: u128) -> u128 {
+    // ln(1 + y) = 2 atanh(z) with z = y / (2 + y) <= 1/3, so the series converges quickly
+    let z = y * LMSR_SCALE / (2 * LMSR_SCALE + y);
+    let z_squared = z * z / LMSR_SCALE;
+    let mut term = z;
+    let mut sum = 0;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = term * z_squared / LMSR_SCALE;
+        n += 2;
+    }
+    2 * sum
+}
+
+/// LMSR cost function b ln(e^(q0/b) + e^(q1/b)), scaled by `LMSR_SCALE`.
+/// Computed as max(q) + b ln(1 + e^(-|q0 - q1| / b)) so nothing overflows
+pub fn lmsr_cost(shares: [u64; 2], liquidity: u64) -> u128 {
+    let high = shares[0].max(shares[1]) as u128;
+    let low = shares[0].min(shares[1]) as u128;
+    let liquidity = liquidity as u128;
+    let spread = (high - low) * LMSR_SCALE / liquidity;
+    high * LMSR_SCALE + liquidity * ln_1p(exp_neg(spread))
+}
+
+/// Price of buying `amount` shares of an option, rounded up in the market's favour
+pub fn lmsr_buy_cost(
+    shares: [u64; 2],
+    liquidity: u64,
+    option_index: u64,
+    amount: u64,
+) -> Result<u64> {
+    let mut after = shares;
+    after[option_index as usize] = after[option_index as usize]
+        .checked_add(amount)
+        .ok_or(BettingPoolsError::MathOverflow)?;
+    let cost = lmsr_cost(after, liquidity) - lmsr_cost(shares, liquidity);
+    u64::try_from(cost.div_ceil(LMSR_SCALE) + 1)
+        .map_err(|_| error!(BettingPoolsError::MathOverflow))
+}
+
+/// Subsidy that covers a market's worst-case loss of b ln 2
+pub fn lmsr_subsidy(liquidity: u64) -> u64 {
+    ((liquidity as u128 * LMSR_LN_2).div_ceil(LMSR_SCALE) + 1) as u64
+}
+
+/// Weight of a bet's stake when winners split the losing side. Pools with time
+/// weighting add a bonus of up to `early_bonus_bp` that decays along the pool's
+/// curve from pool creation to `bets_close_at`.
+pub fn bet_weight(pool: &PoolState, amount: u64, created_at: i64) -> u64 {
+    let window = pool.bets_close_at - pool.created_at;
+    if pool.early_bonus_bp == 0 || window <= 0 {
+        return amount;
+    }
+
+    // Share of the betting window that was still left when the bet was placed
+    let remaining = (pool.bets_close_at - created_at).clamp(0, window) as u128;
+    let window = window as u128;
+    let max_bonus_bp = pool.early_bonus_bp as u128;
+    let bonus_bp = match pool.weight_curve() {
+        WeightCurve::None => 0,
+        WeightCurve::Linear => max_bonus_bp * remaining / window,
+        WeightCurve::Quadratic => max_bonus_bp * remaining * remaining / (window * window),
+    };
+
+    (amount as u128 * (10_000 + bonus_bp) / 10_000) as u64
+}
+
+/// Hash a hidden bet commits to. The owner is included so a commitment
+/// can't be copied by another bettor and revealed with the same salt.
+pub fn bet_commitment(option_index: u64, salt: &[u8; 32], owner: &Pubkey) -> [u8; 32] {
+    hashv(&[&option_index.to_le_bytes(), salt, owner.as_ref()]).to_bytes()
+}
+
+/// Take a position's shares out of its graded market and return what they pay
+fn redeem_position(pool: &PoolState, market: &mut LmsrMarket, position: &LmsrPosition) -> u64 {
+    let payout = if pool.is_draw() {
+        (position.shares[0] + position.shares[1]) / 2
+    } else {
+        position.shares[pool.winning_option as usize]
+    };
+    market.redeemed[0] += position.shares[0];
+    market.redeemed[1] += position.shares[1];
+    market.balance -= payout;
+    payout
+}
+
+/// Stakes of a match still in escrow. `claim_match` releases both stakes when the winner
+/// claims, and each side's own stake when it claims a draw
+fn match_escrow(offer_match: &OfferMatch, pool: &PoolState) -> u64 {
+    let stakes = offer_match.maker_stake + offer_match.taker_stake;
+    if pool.status() != PoolStatus::Graded {
+        return stakes;
+    }
+
+    if pool.is_draw() {
+        let maker = if offer_match.maker_claimed {
+            0
+        } else {
+            offer_match.maker_stake
+        };
+        let taker = if offer_match.taker_claimed {
+            0
+        } else {
+            offer_match.taker_stake
+        };
+        return maker + taker;
+    }
+
+    let winner_claimed = if pool.winning_option == offer_match.maker_option {
+        offer_match.maker_claimed
+    } else {
+        offer_match.taker_claimed
+    };
+    if winner_claimed {
+        0
+    } else {
+        stakes
+    }
+}
+
+//--------- DATA STRUCTURES ---------//
+
+#[account]
+#[derive(InitSpace)]
+pub struct BettingPoolsState {
+    pub authority: Pubkey,
+    pub next_pool_id: u64,
+    /// Global bet counter used before bet ids became per pool. No longer incremented,
+    /// only read when migrating pools created before the switch.
+    pub next_bet_id: u64,
+    pub is_initialized: bool,
+    pub version: u8,
+    pub treasury: Pubkey,
+    pub mint_count: u64,
+    pub settled_pools: u64,
+    /// Mint and amount of the bond posted by `propose_pool`. Proposals are off while zero
+    pub bond_mint: Pubkey,
+    pub bond_amount: u64,
+    /// Internal balance accounts that haven't been closed. Decommissioning waits for zero
+    pub open_balances: u64,
+    /// Seconds after grading that bets can be claimed. Claims never expire while zero
+    pub claim_window: i64,
+    /// USDC and points mints that bets named by `TokenType` before the mint registry.
+    /// Only set on accounts migrated from the original layout
+    pub legacy_mints: [Pubkey; 2],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum PoolStatus {
     None,
     Pending,
     Graded,
     Regraded, // Disputed (unused for now)
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
-pub enum TokenType {
-    Usdc,
-    Points,
+#[account]
+#[derive(InitSpace)]
+pub struct MintConfig {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub decimals: u8,
+    pub payout_fee_bp: u16,
+    pub min_bet: u64,
+    pub is_active: bool,
+    pub version: u8,
+    /// Held in internal balances
+    pub balances: u64,
+    /// Maker stakes in open offers and stakes of matches not yet claimed
+    pub escrowed: u64,
+    /// Bonds of proposed pools not yet returned or slashed
+    pub bonds: u64,
 }
 
-#[account]
+/// Hot numeric state of a pool, touched by every bet and claim.
+/// Fixed layout so it is read in place instead of being deserialized
+#[account(zero_copy)]
 #[derive(InitSpace)]
-pub struct Pool {
+pub struct PoolState {
     pub id: u64,
+    pub bets_close_at: i64,
+    pub decision_time: i64,
+    pub created_at: i64,
+    pub winning_option: u64,
+    pub unclaimed_bets: u64,
+    pub next_bet_id: u64,
+    /// `PoolStatus` discriminant
+    pub status: u8,
+    pub is_draw: u8,
+    pub version: u8,
+    /// Bets are committed as hashes and revealed after `bets_close_at`
+    pub is_hidden: u8,
+    /// Unrevealed bets are forfeited instead of refunded
+    pub forfeit_unrevealed: u8,
+    /// `WeightCurve` discriminant
+    pub weight_curve: u8,
+    /// Bonus weight of a bet placed when the pool was created
+    pub early_bonus_bp: u16,
+    /// End of a hidden pool's reveal phase
+    pub reveal_ends_at: i64,
+    /// `PoolMode` discriminant
+    pub mode: u8,
+    /// Unclaimed stakes have been swept to the treasury in at least one mint
+    pub is_swept: u8,
+    /// Counted in `settled_pools`
+    pub is_settled: u8,
+    /// `PoolTotals` accounts the pool has, one for each mint it took bets in
+    pub totals_count: u8,
+    pub _padding: [u8; 4],
+    pub next_offer_id: u64,
+    /// Claims fail after this time. Zero for pools graded without a claim window
+    pub claim_ends_at: i64,
+}
+
+impl PoolState {
+    /// Continues the numbering of the Borsh `Pool` layout it replaced
+    pub const VERSION: u8 = 11;
+
+    pub fn status(&self) -> PoolStatus {
+        match self.status {
+            1 => PoolStatus::Pending,
+            2 => PoolStatus::Graded,
+            3 => PoolStatus::Regraded,
+            _ => PoolStatus::None,
+        }
+    }
+
+    pub fn set_status(&mut self, status: PoolStatus) {
+        self.status = status as u8;
+    }
+
+    pub fn is_draw(&self) -> bool {
+        self.is_draw != 0
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.is_hidden != 0
+    }
+
+    pub fn is_swept(&self) -> bool {
+        self.is_swept != 0
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.is_settled != 0
+    }
+
+    pub fn forfeit_unrevealed(&self) -> bool {
+        self.forfeit_unrevealed != 0
+    }
+
+    pub fn mode(&self) -> PoolMode {
+        match self.mode {
+            1 => PoolMode::Lmsr,
+            _ => PoolMode::Parimutuel,
+        }
+    }
+
+    pub fn set_mode(&mut self, mode: PoolMode) {
+        self.mode = mode as u8;
+    }
+
+    pub fn weight_curve(&self) -> WeightCurve {
+        match self.weight_curve {
+            1 => WeightCurve::Linear,
+            2 => WeightCurve::Quadratic,
+            _ => WeightCurve::None,
+        }
+    }
+}
+
+/// Commit-reveal settings for a hidden pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct HiddenBetsConfig {
+    /// End of the reveal phase, which starts at `bets_close_at`
+    pub reveal_ends_at: i64,
+    /// Forfeit bets that are never revealed instead of refunding them
+    pub forfeit_unrevealed: bool,
+}
+
+/// How a pool prices its bets
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum PoolMode {
+    /// Winners split the losing side
+    Parimutuel,
+    /// Outcome shares are sold by an LMSR market maker and redeem for one unit each
+    Lmsr,
+}
+
+/// How a claimed bet was settled
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum BetOutcome {
+    Won,
+    Lost,
+    Refunded,
+}
+
+/// How a pool's early-bet bonus decays over its betting window
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum WeightCurve {
+    None,
+    Linear,
+    Quadratic,
+}
+
+/// Time-weighting settings for a pool
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct TimeWeighting {
+    pub curve: WeightCurve,
+    /// Bonus weight of a bet placed as soon as the pool opens
+    pub max_bonus_bp: u16,
+}
+
+/// Text describing a pool. Only written on creation, by `update_pool` and by `set_image`
+#[account]
+#[derive(InitSpace)]
+pub struct PoolMetadata {
+    pub pool_id: u64,
     #[max_len(150)]
     pub question: String,
     #[max_len(50, 50)]
     pub options: [String; 2],
-    pub bets_close_at: i64,
-    pub decision_time: i64,
-    pub usdc_bet_totals: [u64; 2],
-    pub points_bet_totals: [u64; 2],
-    pub winning_option: u64,
-    pub status: PoolStatus,
-    pub is_draw: bool,
-    pub created_at: i64,
     #[max_len(100)]
     pub original_truth_social_post_id: String,
     #[max_len(200)]
     pub image_url: String,
+    pub version: u8,
 }
 
+/// LMSR market maker of a pool in `PoolMode::Lmsr`
 #[account]
 #[derive(InitSpace)]
-pub struct Bet {
-    pub id: u64,
+pub struct LmsrMarket {
+    pub pool_id: u64,
+    pub mint: Pubkey,
+    /// Liquidity parameter b
+    pub liquidity: u64,
+    /// Shares sold of each option
+    pub shares: [u64; 2],
+    /// Shares of each option that have been redeemed
+    pub redeemed: [u64; 2],
+    /// Amount the authority put in to cover the market's worst-case loss
+    pub subsidy: u64,
+    /// Tokens held in the vault for this market
+    pub balance: u64,
+    pub surplus_withdrawn: bool,
+    pub version: u8,
+}
+
+/// Shares a user holds in a pool's LMSR market
+#[account]
+#[derive(InitSpace)]
+pub struct LmsrPosition {
+    pub pool_id: u64,
     pub owner: Pubkey,
-    pub option: u64,
+    pub shares: [u64; 2],
+    pub is_redeemed: bool,
+    pub version: u8,
+}
+
+/// Tokens a user holds in a mint's vault, spent by bets without a token transfer
+#[account]
+#[derive(InitSpace)]
+pub struct UserBalance {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
     pub amount: u64,
-    pub pool_id: u64,
-    pub created_at: i64,
-    pub updated_at: i64,
-    pub is_withdrawn: bool,
-    pub token_type: TokenType,
+    pub version: u8,
 }
 
-//--------- EVENTS ---------//
+/// Ephemeral key allowed to bet and claim for its owner
+#[account]
+#[derive(InitSpace)]
+pub struct Session {
+    pub owner: Pubkey,
+    pub session_key: Pubkey,
+    /// Most the key can stake in total, summed over its allowed mints
+    pub spend_cap: u64,
+    pub spent: u64,
+    pub expires_at: i64,
+    #[max_len(MAX_SESSION_MINTS)]
+    pub allowed_mints: Vec<Pubkey>,
+    pub version: u8,
+}
 
-#[event]
-pub struct PoolCreated {
-    pub pool_id: u64,
-    pub question: String,
-    pub options: [String; 2],
-    pub bets_close_at: i64,
-    pub original_truth_social_post_id: String,
-    pub image_url: String,
-    pub created_at: i64,
+/// Next nonce a user's signed bet intent must carry
+#[account]
+#[derive(InitSpace)]
+pub struct UserNonce {
+    pub owner: Pubkey,
+    pub next_nonce: u64,
+    pub version: u8,
 }
 
-#[event]
-pub struct BetPlaced {
-    pub bet_id: u64,
+/// Bond escrowed by the creator of a proposed pool
+#[account]
+#[derive(InitSpace)]
+pub struct PoolBond {
     pub pool_id: u64,
-    pub user: Pubkey,
-    pub option_index: u64,
+    pub creator: Pubkey,
+    pub mint: Pubkey,
     pub amount: u64,
-    pub token_type: TokenType,
-    pub created_at: i64,
+    /// Set once the bond is returned or slashed
+    pub is_settled: bool,
+    pub is_slashed: bool,
+    pub version: u8,
 }
 
-#[event]
-pub struct PoolClosed {
+/// Fixed-odds offer escrowing a maker's stake on one option
+#[account]
+#[derive(InitSpace)]
+pub struct Offer {
+    pub id: u64,
     pub pool_id: u64,
-    pub selected_option: u64,
-    pub decision_time: i64,
+    pub maker: Pubkey,
+    pub mint: Pubkey,
+    pub option: u64,
+    /// Maker's total stake
+    pub stake: u64,
+    /// Total counterparties must put up to take the whole offer
+    pub ask: u64,
+    pub stake_remaining: u64,
+    pub ask_remaining: u64,
+    pub next_match_id: u64,
+    /// Closed to takers, by its maker or by being taken in full. A closed offer no
+    /// longer holds a claim on its pool
+    pub is_cancelled: bool,
+    pub created_at: i64,
+    pub version: u8,
 }
 
-#[event]
-pub struct PayoutClaimed {
-    pub bet_id: u64,
+/// Part of an offer taken by one counterparty, settled by the pool's grade
+#[account]
+#[derive(InitSpace)]
+pub struct OfferMatch {
+    pub id: u64,
+    pub offer_id: u64,
     pub pool_id: u64,
-    pub user: Pubkey,
-    pub amount: u64,
-    pub token_type: TokenType,
+    pub maker: Pubkey,
+    pub taker: Pubkey,
+    pub mint: Pubkey,
+    pub maker_option: u64,
+    pub maker_stake: u64,
+    pub taker_stake: u64,
+    pub maker_claimed: bool,
+    pub taker_claimed: bool,
+    pub version: u8,
 }
 
-#[event]
-pub struct PoolImageSet {
+/// Totals staked on each option of a pool in a single mint
+#[account]
+#[derive(InitSpace, Default)]
+pub struct PoolTotals {
     pub pool_id: u64,
-    pub image_url: String,
+    pub mint: Pubkey,
+    pub bet_totals: [u64; 2],
+    pub version: u8,
+    /// Totals of each option's time-weighted stakes
+    pub weighted_totals: [u64; 2],
+    /// Set once `grade_bet` has frozen the payout ratio and fee below. Claims wait for it,
+    /// and totals graded without it are frozen by `settle_pool_totals`
+    pub is_settled: bool,
+    /// Every bet in this mint is refunded
+    pub is_refund: bool,
+    /// Winnings per unit of weight, see `settlement::Settlement`
+    pub payout_numerator: u64,
+    pub payout_denominator: u64,
+    /// `payout_fee_bp` of the mint when the pool was graded
+    pub fee_bp: u16,
+    /// Paid out to bettors by claims, in tokens or to internal balances
+    pub claimed: u64,
+    /// What was left after the claim window has gone to the treasury
+    pub is_swept: bool,
+    /// Every stake held for bets in this mint, including hidden bets never revealed.
+    /// Totals migrated from before it was tracked start from their revealed stakes
+    pub staked: u64,
+    /// Bets in this mint not yet claimed or withdrawn, the claims a sweep writes off
+    pub unclaimed_bets: u64,
 }
 
-//--------- ERRORS ---------//
-
-#[error_code]
-pub enum BettingPoolsError {
-    #[msg("Bets close time must be in the future")]
-    BetsCloseTimeInPast,
-    #[msg("Pool is not open")]
-    PoolNotOpen,
-    #[msg("Betting period is closed")]
-    BettingPeriodClosed,
-    #[msg("Invalid option index")]
-    InvalidOptionIndex,
-    #[msg("BettingPools is already initialized")]
-    AlreadyInitialized,
-    #[msg("Zero amount")]
-    ZeroAmount,
-    #[msg("Not authorized")]
-    NotAuthorized,
-    #[msg("Token transfer failed")]
-    TokenTransferFailed,
-    #[msg("Pool is not graded")]
-    PoolNotGraded,
-    #[msg("Grading error")]
-    GradingError,
-    #[msg("Bet already withdrawn")]
-    BetAlreadyWithdrawn,
-    #[msg("Not bet owner")]
-    NotBetOwner,
+#[account]
+#[derive(InitSpace, Default)]
+pub struct Bet {
+    pub id: u64,
+    pub owner: Pubkey,
+    pub option: u64,
+    pub amount: u64,
+    pub pool_id: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub is_withdrawn: bool,
+    pub mint: Pubkey,
+    pub version: u8,
+    /// Hash of the option and salt for bets on hidden pools
+    pub commitment: [u8; 32],
+    /// False until a hidden bet's option has been revealed
+    pub is_revealed: bool,
+    /// Added to `weighted_totals` when the option was set. Stored so a later change
+    /// to the pool's close time can't change what the bet is paid
+    pub weight: u64,
 }
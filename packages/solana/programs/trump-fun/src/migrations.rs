@@ -0,0 +1,734 @@
+//! Upgrades of accounts written in older layouts: the `migrate_*` instruction handlers,
+//! the layouts they read and the `Versioned` accounts they bring up to date
+
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use crate::*;
+
+/// Accounts whose layout is versioned and upgraded in place by the `migrate_*` instructions.
+/// New fields are only ever appended, and `VERSION` is bumped whenever that happens.
+pub trait Versioned: AccountSerialize + AccountDeserialize + Space {
+    const VERSION: u8;
+
+    fn version(&self) -> u8;
+    fn set_version(&mut self, version: u8);
+}
+
+/// Implement [`Versioned`] for accounts that keep their layout version in a `version` field
+macro_rules! versioned {
+    ($($account:ty => $version:literal),* $(,)?) => {
+        $(
+            impl Versioned for $account {
+                const VERSION: u8 = $version;
+
+                fn version(&self) -> u8 {
+                    self.version
+                }
+
+                fn set_version(&mut self, version: u8) {
+                    self.version = version;
+                }
+            }
+        )*
+    };
+}
+
+versioned! {
+    BettingPoolsState => 6,
+    MintConfig => 1,
+    PoolMetadata => 1,
+    PoolTotals => 5,
+    LmsrMarket => 1,
+    LmsrPosition => 1,
+    UserBalance => 1,
+    Session => 1,
+    UserNonce => 1,
+    PoolBond => 1,
+    Offer => 1,
+    OfferMatch => 1,
+    Bet => 3,
+}
+
+/// Resize a program-owned account to `space` bytes. The payer covers any extra
+/// rent when it grows and gets back whatever rent is freed when it shrinks.
+pub(crate) fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+) -> Result<()> {
+    if account.data_len() == space {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?.minimum_balance(space);
+    let lamports = account.lamports();
+    if rent > lamports {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                system_program::Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            rent - lamports,
+        )?;
+    } else if lamports > rent {
+        **account.try_borrow_mut_lamports()? = rent;
+        **payer.try_borrow_mut_lamports()? += lamports - rent;
+    }
+
+    account.realloc(space, true)?;
+
+    Ok(())
+}
+
+/// Whether an account holds `discriminator` and was written in the layout the program
+/// launched with. Those layouts predate versioning and put fields in different
+/// places, but accounts were always allocated at exactly their size.
+pub(crate) fn is_original_layout(
+    account: &AccountInfo,
+    discriminator: &[u8],
+    space: usize,
+) -> Result<bool> {
+    Ok(account.data_len() == 8 + space && account.try_borrow_data()?.starts_with(discriminator))
+}
+
+/// Overwrite an account with `state` at version 0, for `migrate_account` to upgrade
+/// from like any other unversioned account
+pub(crate) fn rewrite_account<'info, T: Versioned>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    state: &T,
+) -> Result<()> {
+    resize_account(account, payer, system_program, 8 + T::INIT_SPACE)?;
+    state.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+    Ok(())
+}
+
+/// Count the bets in `accounts` that haven't been claimed or refunded. Bets can be in
+/// any layout, and must belong to `pool_id` and be in increasing id order so none is
+/// counted twice
+pub(crate) fn count_unclaimed_bets(pool_id: u64, accounts: &[AccountInfo]) -> Result<u64> {
+    let mut last_id = None;
+    let mut unclaimed_bets = 0;
+    for account in accounts {
+        let data = account.try_borrow_data()?;
+        if *account.owner != crate::ID || !data.starts_with(Bet::DISCRIMINATOR) {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+
+        let bet = BetHeader::deserialize(&mut &data[8..])?;
+        if bet.pool_id != pool_id || last_id.is_some_and(|id| bet.id <= id) {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+        last_id = Some(bet.id);
+
+        if !bet.is_withdrawn {
+            unclaimed_bets += 1;
+        }
+    }
+
+    Ok(unclaimed_bets)
+}
+
+/// Count the bets of an original-layout pool still unclaimed in each legacy token type.
+/// Bets must be in increasing id order
+pub(crate) fn count_unclaimed_original_bets(
+    pool_id: u64,
+    accounts: &[AccountInfo],
+) -> Result<[u64; 2]> {
+    let mut last_id = None;
+    let mut unclaimed_bets = [0; 2];
+    for account in accounts {
+        if *account.owner != crate::ID
+            || !is_original_layout(account, Bet::DISCRIMINATOR, OriginalBet::INIT_SPACE)?
+        {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+
+        let bet = OriginalBet::deserialize(&mut &account.try_borrow_data()?[8..])?;
+        if bet.pool_id != pool_id || last_id.is_some_and(|id| bet.id <= id) {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+        last_id = Some(bet.id);
+        if !bet.is_withdrawn {
+            unclaimed_bets[bet.token_type as usize] += 1;
+        }
+    }
+
+    Ok(unclaimed_bets)
+}
+
+/// Count the bets of a pool in `mint` still unclaimed. Bets must be in the current
+/// layout and in increasing id order
+pub(crate) fn count_unclaimed_mint_bets(
+    pool_id: u64,
+    mint: &Pubkey,
+    accounts: &[AccountInfo],
+) -> Result<u64> {
+    let mut last_id = None;
+    let mut unclaimed_bets = 0;
+    for account in accounts {
+        if *account.owner != crate::ID {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+
+        let bet = Bet::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        if bet.pool_id != pool_id || bet.mint != *mint || last_id.is_some_and(|id| bet.id <= id) {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+        last_id = Some(bet.id);
+
+        if !bet.is_withdrawn {
+            unclaimed_bets += 1;
+        }
+    }
+
+    Ok(unclaimed_bets)
+}
+
+/// Count the pool's totals at the front of `accounts`, in any layout and each in a
+/// different mint. Returns the count and the accounts after them
+pub(crate) fn count_pool_totals<'a, 'info>(
+    pool_id: u64,
+    accounts: &'a [AccountInfo<'info>],
+) -> Result<(u8, &'a [AccountInfo<'info>])> {
+    let mut mints = Vec::new();
+    for account in accounts {
+        let data = account.try_borrow_data()?;
+        if !data.starts_with(PoolTotals::DISCRIMINATOR) {
+            break;
+        }
+
+        let totals = PoolTotalsHeader::deserialize(&mut &data[8..])?;
+        if *account.owner != crate::ID || totals.pool_id != pool_id || mints.contains(&totals.mint)
+        {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+        mints.push(totals.mint);
+    }
+
+    let totals_count = u8::try_from(mints.len()).map_err(|_| BettingPoolsError::MathOverflow)?;
+    Ok((totals_count, &accounts[mints.len()..]))
+}
+
+/// Total the internal balances, escrowed offer stakes and unsettled bonds in `mint` held
+/// by `accounts`. Each `OfferMatch` is followed by its pool, which decides what its
+/// claims have released. No account may be passed twice
+pub(crate) fn count_mint_liabilities<'info>(
+    mint: &Pubkey,
+    accounts: &'info [AccountInfo<'info>],
+) -> Result<(u64, u64, u64)> {
+    let (mut balances, mut escrowed, mut bonds) = (0u64, 0u64, 0u64);
+    let mut accounts_iter = accounts.iter().enumerate();
+    while let Some((index, account)) = accounts_iter.next() {
+        if *account.owner != crate::ID || accounts[..index].iter().any(|a| a.key == account.key) {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+
+        let data = account.try_borrow_data()?;
+        let (counter, amount, account_mint) = if data.starts_with(UserBalance::DISCRIMINATOR) {
+            let balance = UserBalance::try_deserialize(&mut &data[..])?;
+            (&mut balances, balance.amount, balance.mint)
+        } else if data.starts_with(Offer::DISCRIMINATOR) {
+            let offer = Offer::try_deserialize(&mut &data[..])?;
+            (&mut escrowed, offer.stake_remaining, offer.mint)
+        } else if data.starts_with(PoolBond::DISCRIMINATOR) {
+            let bond = PoolBond::try_deserialize(&mut &data[..])?;
+            let amount = if bond.is_settled { 0 } else { bond.amount };
+            (&mut bonds, amount, bond.mint)
+        } else if data.starts_with(OfferMatch::DISCRIMINATOR) {
+            let offer_match = OfferMatch::try_deserialize(&mut &data[..])?;
+            let (_, pool_account) = accounts_iter
+                .next()
+                .ok_or(BettingPoolsError::InvalidMigrationAccounts)?;
+            let pool = *AccountLoader::<PoolState>::try_from(pool_account)?.load()?;
+            if pool.id != offer_match.pool_id {
+                return err!(BettingPoolsError::InvalidMigrationAccounts);
+            }
+            (
+                &mut escrowed,
+                match_escrow(&offer_match, &pool),
+                offer_match.mint,
+            )
+        } else {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        };
+
+        if account_mint != *mint {
+            return err!(BettingPoolsError::InvalidMigrationAccounts);
+        }
+        *counter = counter
+            .checked_add(amount)
+            .ok_or(BettingPoolsError::MathOverflow)?;
+    }
+
+    Ok((balances, escrowed, bonds))
+}
+
+/// Grow a versioned account to its current size and stamp the current version.
+/// Layout changes only append fields, so fields added after the account was
+/// written deserialize from the zeroed tail as their default values. `upgrade`
+/// can fill in fields whose default isn't right for existing accounts.
+/// Returns the migrated state and the version it was upgraded from.
+pub(crate) fn migrate_account<'info, T: Versioned>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    upgrade: impl FnOnce(&mut T, u8),
+) -> Result<(T, u8)> {
+    let space = 8 + T::INIT_SPACE;
+    if account.data_len() < space {
+        resize_account(account, payer, system_program, space)?;
+    }
+
+    let mut state = T::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+    let from_version = state.version();
+    if from_version >= T::VERSION {
+        return err!(BettingPoolsError::AccountAlreadyMigrated);
+    }
+
+    upgrade(&mut state, from_version);
+    state.set_version(T::VERSION);
+    state.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+    Ok((state, from_version))
+}
+
+/// Pool layout before the zero-copy split. Only read by `migrate_pool`
+#[account]
+#[derive(InitSpace)]
+pub struct Pool {
+    pub id: u64,
+    #[max_len(150)]
+    pub question: String,
+    #[max_len(50, 50)]
+    pub options: [String; 2],
+    pub bets_close_at: i64,
+    pub decision_time: i64,
+    pub winning_option: u64,
+    pub status: PoolStatus,
+    pub is_draw: bool,
+    pub created_at: i64,
+    #[max_len(100)]
+    pub original_truth_social_post_id: String,
+    #[max_len(200)]
+    pub image_url: String,
+    pub version: u8,
+    pub unclaimed_bets: u64,
+    pub next_bet_id: u64,
+}
+
+/// BettingPools layout the program launched with. Only read by `migrate_betting_pools`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct OriginalBettingPoolsState {
+    pub authority: Pubkey,
+    pub usdc_mint: Pubkey,
+    pub freedom_mint: Pubkey,
+    pub next_pool_id: u64,
+    pub next_bet_id: u64,
+    pub payout_fee_bp: u16,
+    pub is_initialized: bool,
+}
+
+/// Pool layout the program launched with, when stakes were totalled per token type.
+/// Only read by `migrate_pool`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct OriginalPool {
+    pub id: u64,
+    #[max_len(150)]
+    pub question: String,
+    #[max_len(50, 50)]
+    pub options: [String; 2],
+    pub bets_close_at: i64,
+    pub decision_time: i64,
+    pub usdc_bet_totals: [u64; 2],
+    pub points_bet_totals: [u64; 2],
+    pub winning_option: u64,
+    pub status: PoolStatus,
+    pub is_draw: bool,
+    pub created_at: i64,
+    #[max_len(100)]
+    pub original_truth_social_post_id: String,
+    #[max_len(200)]
+    pub image_url: String,
+}
+
+/// Bet layout the program launched with. Only read by `migrate_bet`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct OriginalBet {
+    pub id: u64,
+    pub owner: Pubkey,
+    pub option: u64,
+    pub amount: u64,
+    pub pool_id: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub is_withdrawn: bool,
+    pub token_type: TokenType,
+}
+
+/// Fields every layout of `PoolTotals` starts with. Only read by `migrate_pool`
+#[derive(AnchorDeserialize)]
+struct PoolTotalsHeader {
+    pool_id: u64,
+    mint: Pubkey,
+}
+
+/// Fields every layout of `Bet` starts with. Only read by `migrate_pool`
+#[derive(AnchorDeserialize)]
+struct BetHeader {
+    id: u64,
+    _owner: Pubkey,
+    _option: u64,
+    _amount: u64,
+    pool_id: u64,
+    _created_at: i64,
+    _updated_at: i64,
+    is_withdrawn: bool,
+}
+
+/// Token a bet was placed in before the mint registry, indexes `legacy_mints`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace)]
+pub enum TokenType {
+    Usdc,
+    Points,
+}
+
+/// Handler of the `migrate_betting_pools` instruction
+pub(crate) fn migrate_betting_pools(ctx: Context<MigrateBettingPools>) -> Result<()> {
+    let account = ctx.accounts.betting_pools.to_account_info();
+    let space = OriginalBettingPoolsState::INIT_SPACE;
+    if is_original_layout(&account, BettingPoolsState::DISCRIMINATOR, space)? {
+        let original =
+            OriginalBettingPoolsState::deserialize(&mut &account.try_borrow_data()?[8..])?;
+        let betting_pools = BettingPoolsState {
+            authority: original.authority,
+            next_pool_id: original.next_pool_id,
+            next_bet_id: original.next_bet_id,
+            is_initialized: original.is_initialized,
+            version: 0,
+            treasury: Pubkey::default(),
+            mint_count: 0,
+            settled_pools: 0,
+            bond_mint: Pubkey::default(),
+            bond_amount: 0,
+            open_balances: 0,
+            claim_window: 0,
+            legacy_mints: [original.usdc_mint, original.freedom_mint],
+        };
+        rewrite_account(
+            &account,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            &betting_pools,
+        )?;
+    }
+
+    let (betting_pools, from_version) = migrate_account::<BettingPoolsState>(
+        &account,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+        |_, _| {},
+    )?;
+
+    if betting_pools.authority != ctx.accounts.authority.key() {
+        return err!(BettingPoolsError::NotAuthorized);
+    }
+
+    emit_cpi!(AccountMigrated {
+        account: account.key(),
+        from_version,
+        to_version: BettingPoolsState::VERSION,
+    });
+
+    Ok(())
+}
+
+/// Handler of the `migrate_pool` instruction
+pub(crate) fn migrate_pool<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigratePool<'info>>,
+    pool_id: u64,
+) -> Result<()> {
+    let account = ctx.accounts.pool.to_account_info();
+    let authority = &ctx.accounts.authority;
+    let system_program = &ctx.accounts.system_program;
+    let space = 8 + PoolState::INIT_SPACE;
+
+    // Totals weren't counted before version 11
+    let (mut totals_count, bet_accounts) = count_pool_totals(pool_id, ctx.remaining_accounts)?;
+
+    let is_original = is_original_layout(&account, Pool::DISCRIMINATOR, OriginalPool::INIT_SPACE)?;
+    let is_legacy = account.try_borrow_data()?.starts_with(Pool::DISCRIMINATOR);
+    let from_version = if is_legacy {
+        let legacy = if is_original {
+            let original = OriginalPool::deserialize(&mut &account.try_borrow_data()?[8..])?;
+
+            // Stakes were totalled on the pool per token type, move them into
+            // the totals of the mints those token types stood for
+            let legacy_mints = ctx.accounts.betting_pools.legacy_mints;
+            let bet_totals = [original.usdc_bet_totals, original.points_bet_totals];
+            let unclaimed_bets = count_unclaimed_original_bets(original.id, bet_accounts)?;
+            totals_count = 2;
+            let accounts = [
+                &mut ctx.accounts.usdc_totals,
+                &mut ctx.accounts.points_totals,
+            ];
+            for (((pool_totals, mint), bet_totals), unclaimed_bets) in accounts
+                .into_iter()
+                .zip(legacy_mints)
+                .zip(bet_totals)
+                .zip(unclaimed_bets)
+            {
+                let pool_totals = pool_totals
+                    .as_mut()
+                    .ok_or(BettingPoolsError::MissingLegacyTotals)?;
+                pool_totals.set_inner(PoolTotals {
+                    pool_id: original.id,
+                    mint,
+                    bet_totals,
+                    version: PoolTotals::VERSION,
+                    weighted_totals: bet_totals,
+                    staked: bet_totals[0].saturating_add(bet_totals[1]),
+                    unclaimed_bets,
+                    ..Default::default()
+                });
+            }
+
+            Pool {
+                id: original.id,
+                question: original.question,
+                options: original.options,
+                bets_close_at: original.bets_close_at,
+                decision_time: original.decision_time,
+                winning_option: original.winning_option,
+                status: original.status,
+                is_draw: original.is_draw,
+                created_at: original.created_at,
+                original_truth_social_post_id: original.original_truth_social_post_id,
+                image_url: original.image_url,
+                version: 0,
+                unclaimed_bets: 0,
+                next_bet_id: 0,
+            }
+        } else {
+            resize_account(&account, authority, system_program, 8 + Pool::INIT_SPACE)?;
+            Pool::try_deserialize(&mut &account.try_borrow_data()?[..])?
+        };
+
+        // Move the text into the metadata account
+        let metadata = &mut ctx.accounts.pool_metadata;
+        metadata.pool_id = legacy.id;
+        metadata.question = legacy.question;
+        metadata.options = legacy.options;
+        metadata.original_truth_social_post_id = legacy.original_truth_social_post_id;
+        metadata.image_url = legacy.image_url;
+        metadata.version = PoolMetadata::VERSION;
+
+        let mut pool = PoolState {
+            id: legacy.id,
+            bets_close_at: legacy.bets_close_at,
+            decision_time: legacy.decision_time,
+            created_at: legacy.created_at,
+            winning_option: legacy.winning_option,
+            unclaimed_bets: legacy.unclaimed_bets,
+            next_bet_id: legacy.next_bet_id,
+            status: 0,
+            is_draw: legacy.is_draw as u8,
+            version: PoolState::VERSION,
+            is_hidden: 0,
+            forfeit_unrevealed: 0,
+            weight_curve: 0,
+            early_bonus_bp: 0,
+            reveal_ends_at: 0,
+            mode: 0,
+            is_swept: 0,
+            is_settled: 0,
+            totals_count,
+            _padding: [0; 4],
+            next_offer_id: 1,
+            claim_ends_at: 0,
+        };
+        pool.set_status(legacy.status);
+
+        // Bets used to take their ids from the global counter, so start the
+        // pool's own counter past every id that could already be in use
+        if legacy.version < 3 {
+            pool.next_bet_id = ctx.accounts.betting_pools.next_bet_id;
+        }
+
+        // Claims weren't counted, nor pools settled, before version 2. Later pools
+        // were counted in `settled_pools` once their last claim was gone
+        if legacy.version < 2 {
+            pool.unclaimed_bets = count_unclaimed_bets(pool.id, bet_accounts)?;
+        } else {
+            pool.is_settled =
+                (pool.status() != PoolStatus::Pending && pool.unclaimed_bets == 0) as u8;
+        }
+
+        // Rewrite the pool account in the zero-copy layout and refund the freed rent
+        resize_account(&account, authority, system_program, space)?;
+        let mut data = account.try_borrow_mut_data()?;
+        data[..8].copy_from_slice(PoolState::DISCRIMINATOR);
+        data[8..space].copy_from_slice(bytemuck::bytes_of(&pool));
+
+        legacy.version
+    } else {
+        // Zero-copy pools only need room for fields appended since they were written
+        resize_account(&account, authority, system_program, space)?;
+        let mut data = account.try_borrow_mut_data()?;
+        if !data.starts_with(PoolState::DISCRIMINATOR) {
+            return err!(ErrorCode::AccountDiscriminatorMismatch);
+        }
+        let pool: &mut PoolState = bytemuck::from_bytes_mut(&mut data[8..space]);
+        let from_version = pool.version;
+        if from_version >= PoolState::VERSION {
+            return err!(BettingPoolsError::AccountAlreadyMigrated);
+        }
+        if from_version < 8 {
+            pool.next_offer_id = 1;
+        }
+        // Pools used to be counted in `settled_pools` once their last claim was gone
+        if from_version < 10 {
+            pool.is_settled =
+                (pool.status() != PoolStatus::Pending && pool.unclaimed_bets == 0) as u8;
+        }
+        if from_version < 11 {
+            pool.totals_count = totals_count;
+        }
+        pool.version = PoolState::VERSION;
+
+        from_version
+    };
+
+    emit_cpi!(AccountMigrated {
+        account: account.key(),
+        from_version,
+        to_version: PoolState::VERSION,
+    });
+
+    Ok(())
+}
+
+/// Handler of the `migrate_bet` instruction
+pub(crate) fn migrate_bet(ctx: Context<MigrateBet>) -> Result<()> {
+    let account = ctx.accounts.bet.to_account_info();
+    let pool = ctx.accounts.pool.load()?;
+    if is_original_layout(&account, Bet::DISCRIMINATOR, OriginalBet::INIT_SPACE)? {
+        let original = OriginalBet::deserialize(&mut &account.try_borrow_data()?[8..])?;
+        let bet = Bet {
+            id: original.id,
+            owner: original.owner,
+            option: original.option,
+            amount: original.amount,
+            pool_id: original.pool_id,
+            created_at: original.created_at,
+            updated_at: original.updated_at,
+            is_withdrawn: original.is_withdrawn,
+            mint: ctx.accounts.betting_pools.legacy_mints[original.token_type as usize],
+            ..Default::default()
+        };
+        rewrite_account(
+            &account,
+            &ctx.accounts.authority,
+            &ctx.accounts.system_program,
+            &bet,
+        )?;
+    }
+
+    let (_, from_version) = migrate_account::<Bet>(
+        &account,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+        |bet, from_version| {
+            // Every bet placed before hidden pools existed chose its option openly
+            if from_version < 2 {
+                bet.is_revealed = true;
+            }
+            // Weights used to be recomputed from the pool on every claim
+            if from_version < 3 && bet.is_revealed {
+                bet.weight = bet_weight(&pool, bet.amount, bet.created_at);
+            }
+        },
+    )?;
+
+    emit_cpi!(AccountMigrated {
+        account: account.key(),
+        from_version,
+        to_version: Bet::VERSION,
+    });
+
+    Ok(())
+}
+
+/// Handler of the `migrate_pool_totals` instruction
+pub(crate) fn migrate_pool_totals(
+    ctx: Context<MigratePoolTotals>,
+    pool_id: u64,
+    mint: Pubkey,
+) -> Result<()> {
+    let account = ctx.accounts.pool_totals.to_account_info();
+    let unclaimed_bets = count_unclaimed_mint_bets(pool_id, &mint, ctx.remaining_accounts)?;
+    let (_, from_version) = migrate_account::<PoolTotals>(
+        &account,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+        |pool_totals, from_version| {
+            // Bets placed before time weighting all carry a weight equal to their stake
+            if from_version < 1 {
+                pool_totals.weighted_totals = pool_totals.bet_totals;
+            }
+            if from_version < 4 {
+                pool_totals.staked =
+                    pool_totals.bet_totals[0].saturating_add(pool_totals.bet_totals[1]);
+            }
+            if from_version < 5 {
+                pool_totals.unclaimed_bets = unclaimed_bets;
+            }
+        },
+    )?;
+
+    emit_cpi!(AccountMigrated {
+        account: account.key(),
+        from_version,
+        to_version: PoolTotals::VERSION,
+    });
+
+    Ok(())
+}
+
+/// Handler of the `migrate_mint_config` instruction
+pub(crate) fn migrate_mint_config<'info>(
+    ctx: Context<'_, '_, 'info, 'info, MigrateMintConfig<'info>>,
+    mint: Pubkey,
+) -> Result<()> {
+    let account = ctx.accounts.mint_config.to_account_info();
+    let counted = count_mint_liabilities(&mint, ctx.remaining_accounts)?;
+    let (_, from_version) = migrate_account::<MintConfig>(
+        &account,
+        &ctx.accounts.authority,
+        &ctx.accounts.system_program,
+        |mint_config, from_version| {
+            if from_version < 1 {
+                (
+                    mint_config.balances,
+                    mint_config.escrowed,
+                    mint_config.bonds,
+                ) = counted;
+            }
+        },
+    )?;
+
+    emit_cpi!(AccountMigrated {
+        account: account.key(),
+        from_version,
+        to_version: MintConfig::VERSION,
+    });
+
+    Ok(())
+}
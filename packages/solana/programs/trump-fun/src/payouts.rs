@@ -0,0 +1,144 @@
+//! Glue between the program's accounts and the settlement crate: what a bet is paid out,
+//! quoted and announced as, and how a pool's totals freeze their settlement
+
+use anchor_lang::prelude::*;
+
+use crate::*;
+
+/// What a graded pool pays out for a bet
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BetPayout {
+    /// Paid to the bettor, after the fee
+    pub amount: u64,
+    /// Kept by the program
+    pub fee: u64,
+    pub outcome: BetOutcome,
+}
+
+/// Returned by `quote_bet` and `quote_claim`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct BetQuote {
+    pub payout: BetPayout,
+    /// Amount paid out per unit staked, in basis points
+    pub odds_bp: u64,
+}
+
+/// Amount a bet is paid out under its pool's settlement in the bet's mint, after the fee
+pub fn bet_payout(settlement: &settlement::Settlement, bet: &Bet) -> BetPayout {
+    let payout = settlement::settled_payout(
+        settlement,
+        &settlement::Bet {
+            option: bet.option,
+            amount: bet.amount,
+            weight: bet.weight,
+            is_revealed: bet.is_revealed,
+        },
+    );
+
+    BetPayout {
+        amount: payout.amount,
+        fee: payout.fee,
+        outcome: payout.outcome.into(),
+    }
+}
+
+/// A pool's totals in one mint as the settlement rules see them
+pub(crate) fn settlement_pool(pool: &PoolState, pool_totals: &PoolTotals) -> settlement::Pool {
+    settlement::Pool {
+        winning_option: pool.winning_option,
+        is_draw: pool.is_draw(),
+        forfeit_unrevealed: pool.forfeit_unrevealed(),
+        bet_totals: pool_totals.bet_totals,
+        weighted_totals: pool_totals.weighted_totals,
+    }
+}
+
+/// Quote for a stake of `stake` that pays out `payout`; a zero stake quotes zero odds
+pub fn bet_quote(stake: u64, payout: BetPayout) -> BetQuote {
+    BetQuote {
+        payout,
+        odds_bp: (payout.amount as u128 * 10000)
+            .checked_div(stake as u128)
+            .unwrap_or(0) as u64,
+    }
+}
+
+/// Event announcing how a claimed bet was settled, including bets that lost
+pub(crate) fn bet_settled(bet: &Bet, payout: &BetPayout) -> Result<BetSettled> {
+    Ok(BetSettled {
+        bet_id: bet.id,
+        pool_id: bet.pool_id,
+        user: bet.owner,
+        mint: bet.mint,
+        outcome: payout.outcome,
+        amount: payout.amount,
+        fee: payout.fee,
+        slot: Clock::get()?.slot,
+        version: BetSettled::VERSION,
+    })
+}
+
+impl From<settlement::Outcome> for BetOutcome {
+    fn from(outcome: settlement::Outcome) -> Self {
+        match outcome {
+            settlement::Outcome::Won => BetOutcome::Won,
+            settlement::Outcome::Lost => BetOutcome::Lost,
+            settlement::Outcome::Refunded => BetOutcome::Refunded,
+        }
+    }
+}
+
+impl PoolTotals {
+    /// How bets in this mint are paid out, as frozen when `pool` was graded
+    pub fn settlement(&self, pool: &PoolState) -> Result<settlement::Settlement> {
+        if !self.is_settled {
+            return err!(BettingPoolsError::TotalsNotSettled);
+        }
+
+        Ok(settlement::Settlement {
+            winning_option: pool.winning_option,
+            is_refund: self.is_refund,
+            forfeit_unrevealed: pool.forfeit_unrevealed(),
+            payout_numerator: self.payout_numerator,
+            payout_denominator: self.payout_denominator,
+            fee_bp: self.fee_bp,
+        })
+    }
+
+    /// How bets in this mint would be paid out if `pool` were graded now, for quotes
+    pub fn live_settlement(&self, pool: &PoolState, payout_fee_bp: u16) -> settlement::Settlement {
+        settlement::settle(&settlement_pool(pool, self), payout_fee_bp)
+    }
+
+    /// What this mint still owes the pool's bettors: every stake while the pool is open,
+    /// and once it is graded the most its bets can be paid out, fees included, less what
+    /// claims have paid. Nothing once the pool is swept
+    pub fn liability(&self, pool: &PoolState) -> Result<u64> {
+        if self.is_swept {
+            return Ok(0);
+        }
+        if pool.status() != PoolStatus::Graded {
+            return Ok(self.staked);
+        }
+
+        let revealed = self.bet_totals[0] as u128 + self.bet_totals[1] as u128;
+        let unrevealed = (self.staked as u128).saturating_sub(revealed) as u64;
+        let owed = settlement::max_payout(
+            &self.settlement(pool)?,
+            self.bet_totals,
+            self.weighted_totals,
+            unrevealed,
+        );
+        Ok(u64::try_from(owed.saturating_sub(self.claimed as u128)).unwrap_or(u64::MAX))
+    }
+
+    /// Freeze the payout ratio and fee of a graded pool
+    pub(crate) fn settle(&mut self, pool: &PoolState, payout_fee_bp: u16) {
+        let settlement = self.live_settlement(pool, payout_fee_bp);
+        self.is_settled = true;
+        self.is_refund = settlement.is_refund;
+        self.payout_numerator = settlement.payout_numerator;
+        self.payout_denominator = settlement.payout_denominator;
+        self.fee_bp = settlement.fee_bp;
+    }
+}
@@ -0,0 +1,128 @@
+//! Compute units the built program spends on the instructions every pool runs through.
+//! A natively run program spends none, so these only run under `cargo test-sbf`
+
+#![cfg(feature = "test-sbf")]
+
+mod harness;
+
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_spl::token::spl_token;
+use harness::{Harness, STAKE};
+use solana_sdk::signature::{Keypair, Signer};
+use trump_fun_sdk::instructions;
+
+/// Most a hot path may spend, leaving the rest of the default per-instruction limit of
+/// 200k units to transactions that compose it with other instructions
+const BUDGET: u64 = 100_000;
+
+/// Fail if `instruction` spends more than `BUDGET`, then send it
+async fn send_within_budget(
+    h: &mut Harness,
+    name: &str,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) {
+    let units = h
+        .compute_units(std::slice::from_ref(&instruction), signers)
+        .await;
+    assert!(
+        units <= BUDGET,
+        "{name} spent {units} compute units, over its budget of {BUDGET}"
+    );
+    h.send(&[instruction], signers).await.unwrap();
+}
+
+#[tokio::test]
+async fn parimutuel_and_offer_paths_stay_within_budget() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (winner, winner_tokens) = h.user(STAKE * 2).await;
+    let (taker, taker_tokens) = h.user(STAKE).await;
+
+    send_within_budget(
+        &mut h,
+        "place_bet",
+        instructions::place_bet(&winner.pubkey(), None, pool_id, 1, &winner_tokens, 0, STAKE),
+        &[&winner],
+    )
+    .await;
+    send_within_budget(
+        &mut h,
+        "create_offer",
+        instructions::create_offer(
+            &winner.pubkey(),
+            pool_id,
+            1,
+            &winner_tokens,
+            0,
+            STAKE,
+            STAKE,
+        ),
+        &[&winner],
+    )
+    .await;
+    send_within_budget(
+        &mut h,
+        "accept_offer",
+        instructions::accept_offer(&taker.pubkey(), pool_id, 1, 1, &taker_tokens, STAKE),
+        &[&taker],
+    )
+    .await;
+    send_within_budget(
+        &mut h,
+        "grade_bet",
+        instructions::grade_bet(&authority, pool_id, 0, &[mint]),
+        &[],
+    )
+    .await;
+    send_within_budget(
+        &mut h,
+        "claim_payout",
+        instructions::claim_payout(&winner.pubkey(), None, pool_id, 1, &winner_tokens),
+        &[&winner],
+    )
+    .await;
+    send_within_budget(
+        &mut h,
+        "claim_match",
+        instructions::claim_match(&winner.pubkey(), pool_id, 1, 1, &winner_tokens),
+        &[&winner],
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn market_paths_stay_within_budget() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let funding = h.fund(&authority, &mint, &spl_token::ID, STAKE).await;
+    let (buyer, buyer_tokens) = h.user(STAKE).await;
+    h.send(
+        &[instructions::create_market(
+            &authority, pool_id, &funding, 1_000,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    send_within_budget(
+        &mut h,
+        "buy_shares",
+        instructions::buy_shares(&buyer.pubkey(), pool_id, &buyer_tokens, 0, 1_000, STAKE),
+        &[&buyer],
+    )
+    .await;
+    h.grade(pool_id, 0).await;
+    send_within_budget(
+        &mut h,
+        "redeem_shares",
+        instructions::redeem_shares(&buyer.pubkey(), pool_id, &buyer_tokens),
+        &[&buyer],
+    )
+    .await;
+}
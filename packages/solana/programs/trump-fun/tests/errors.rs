@@ -0,0 +1,1294 @@
+//! Every `BettingPoolsError`, triggered through an instruction that checks it.
+//!
+//! `AlreadyInitialized` is no longer returned: `initialize` creates the betting pools
+//! account with `init`, so a second call is refused by the system program
+
+mod harness;
+
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::system_instruction::SystemError;
+use anchor_lang::AccountSerialize;
+use anchor_spl::token::spl_token;
+use anchor_spl::token_interface::spl_token_2022;
+use harness::{assert_error, Harness, STAKE};
+use solana_program_test::BanksClientError;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::TransactionError;
+use trump_fun::{
+    bet_commitment, bet_intent_message, instruction, BettingPoolsError, BettingPoolsState,
+    HiddenBetsConfig, MintConfig, PoolMode, PoolState, PoolTotals, TimeWeighting, WeightCurve,
+    EMERGENCY_WITHDRAW_DELAY, MAX_SESSION_MINTS,
+};
+use trump_fun_sdk::instructions::{self, TokenAccounts};
+use trump_fun_sdk::pda;
+
+/// Point the account at `from` in `instruction` to `to` instead
+fn replace_account(mut instruction: Instruction, from: &Pubkey, to: &Pubkey) -> Instruction {
+    let meta = instruction
+        .accounts
+        .iter_mut()
+        .find(|meta| meta.pubkey == *from)
+        .unwrap();
+    meta.pubkey = *to;
+    instruction
+}
+
+fn place_bet(
+    user: &Keypair,
+    tokens: &TokenAccounts,
+    pool_id: u64,
+    bet_id: u64,
+    option: u64,
+    amount: u64,
+) -> Instruction {
+    instructions::place_bet(
+        &user.pubkey(),
+        None,
+        pool_id,
+        bet_id,
+        tokens,
+        option,
+        amount,
+    )
+}
+
+#[tokio::test]
+async fn initialize_runs_once() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+
+    // The account `init` would create is already in use
+    let result = h.send(&[instructions::initialize(&authority)], &[]).await;
+    let already_in_use = SystemError::AccountAlreadyInUse as u32;
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) if code == already_in_use => {}
+        other => panic!("expected the account to be in use, got {other:?}"),
+    }
+    let betting_pools: BettingPoolsState = h.account(&pda::betting_pools()).await;
+    assert_eq!(betting_pools.next_pool_id, 1);
+}
+
+#[tokio::test]
+async fn fees_are_capped_at_the_whole_amount() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.create_mint().await;
+
+    let result = h
+        .send(
+            &[instructions::add_mint(
+                &authority,
+                &mint,
+                &spl_token::ID,
+                10_001,
+                1,
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidFee);
+
+    let mint = h.mint;
+    let result = h
+        .send(
+            &[instructions::update_mint(
+                &authority, &mint, 10_001, 1, true,
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidFee);
+}
+
+#[tokio::test]
+async fn pool_settings_are_validated() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let now = h.now().await;
+    let pool_id = h.next_pool_id().await;
+
+    let mut params = h.pool_params().await;
+    params.bets_close_at = now;
+    let result = h
+        .send(
+            &[instructions::create_pool(&authority, pool_id, params)],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::BetsCloseTimeInPast);
+
+    let mut params = h.pool_params().await;
+    params.hidden_bets = Some(HiddenBetsConfig {
+        reveal_ends_at: params.bets_close_at,
+        forfeit_unrevealed: false,
+    });
+    let result = h
+        .send(
+            &[instructions::create_pool(&authority, pool_id, params)],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidRevealWindow);
+
+    let mut params = h.pool_params().await;
+    params.time_weighting = Some(TimeWeighting {
+        curve: WeightCurve::Linear,
+        max_bonus_bp: 10_001,
+    });
+    let result = h
+        .send(
+            &[instructions::create_pool(&authority, pool_id, params)],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidTimeWeighting);
+}
+
+#[tokio::test]
+async fn bets_are_validated() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+    let (_, other_tokens) = h.user(STAKE).await;
+
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 2, STAKE)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::InvalidOptionIndex);
+
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 0, 0)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::ZeroAmount);
+
+    // The stake must come from the bettor's own token account
+    let result = h
+        .send(
+            &[place_bet(&user, &other_tokens, pool_id, 1, 0, STAKE)],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::NotBetOwner);
+
+    let instruction = replace_account(
+        place_bet(&user, &tokens, pool_id, 1, 0, STAKE),
+        &pda::vault(&mint),
+        &other_tokens.token_account,
+    );
+    let result = h.send(&[instruction], &[&user]).await;
+    assert_error(result, BettingPoolsError::InvalidVault);
+
+    h.send(
+        &[instructions::update_mint(
+            &authority, &mint, 500, STAKE, true,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let result = h
+        .send(
+            &[place_bet(&user, &tokens, pool_id, 1, 0, STAKE - 1)],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::BetBelowMinimum);
+
+    h.send(
+        &[instructions::update_mint(&authority, &mint, 500, 1, false)],
+        &[],
+    )
+    .await
+    .unwrap();
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 0, STAKE)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::MintNotActive);
+
+    h.send(
+        &[instructions::update_mint(&authority, &mint, 500, 1, true)],
+        &[],
+    )
+    .await
+    .unwrap();
+    let bets_close_at = h.pool(pool_id).await.bets_close_at;
+    h.warp_to(bets_close_at + 1).await;
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 0, STAKE)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::BettingPeriodClosed);
+}
+
+#[tokio::test]
+async fn pools_are_graded_once_by_the_authority() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+
+    let result = h
+        .send(&[instructions::grade_bet(&authority, pool_id, 3, &[])], &[])
+        .await;
+    assert_error(result, BettingPoolsError::GradingError);
+
+    let result = h
+        .send(
+            &[instructions::grade_bet(&user.pubkey(), pool_id, 0, &[])],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::NotAuthorized);
+
+    h.grade(pool_id, 0).await;
+    let result = h
+        .send(&[instructions::grade_bet(&authority, pool_id, 1, &[])], &[])
+        .await;
+    assert_error(result, BettingPoolsError::PoolNotOpen);
+
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 0, STAKE)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::PoolNotOpen);
+}
+
+#[tokio::test]
+async fn bets_are_claimed_once_by_their_owner() {
+    let mut h = Harness::new().await;
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+    let (other, other_tokens) = h.user(STAKE).await;
+    let bet_id = h.bet(&user, &tokens, pool_id, 0, STAKE).await;
+    let claim = instructions::claim_payout(&user.pubkey(), None, pool_id, bet_id, &tokens);
+
+    let result = h.send(std::slice::from_ref(&claim), &[&user]).await;
+    assert_error(result, BettingPoolsError::PoolNotGraded);
+
+    h.grade(pool_id, 0).await;
+    let result = h
+        .send(
+            &[instructions::claim_payout(
+                &other.pubkey(),
+                None,
+                pool_id,
+                bet_id,
+                &other_tokens,
+            )],
+            &[&other],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::NotBetOwner);
+
+    // Payouts are made in the mint the bet was placed in
+    let other_mint = h.create_mint().await;
+    let result = h
+        .send(
+            &[replace_account(claim.clone(), &h.mint, &other_mint)],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidMint);
+
+    h.send(std::slice::from_ref(&claim), &[&user])
+        .await
+        .unwrap();
+    let result = h.send(&[claim], &[&user]).await;
+    assert_error(result, BettingPoolsError::BetAlreadyWithdrawn);
+}
+
+#[tokio::test]
+async fn hidden_bets_are_revealed_during_the_reveal_phase() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let open_pool_id = h.create_pool().await;
+    let mut params = h.pool_params().await;
+    let bets_close_at = params.bets_close_at;
+    let reveal_ends_at = bets_close_at + 3600;
+    params.hidden_bets = Some(HiddenBetsConfig {
+        reveal_ends_at,
+        forfeit_unrevealed: false,
+    });
+    let pool_id = h.create_pool_with(params).await;
+    let (user, tokens) = h.user(STAKE * 2).await;
+    let salt = [7; 32];
+    let commitment = bet_commitment(1, &salt, &user.pubkey());
+
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 0, STAKE)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::HiddenPool);
+
+    let result = h
+        .send(
+            &[instructions::commit_bet(
+                &user.pubkey(),
+                None,
+                open_pool_id,
+                1,
+                &tokens,
+                commitment,
+                STAKE,
+            )],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::PoolNotHidden);
+
+    h.send(
+        &[instructions::commit_bet(
+            &user.pubkey(),
+            None,
+            pool_id,
+            1,
+            &tokens,
+            commitment,
+            STAKE,
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+    let reveal = instructions::reveal_bet(&user.pubkey(), pool_id, 1, &mint, 1, salt);
+    let result = h.send(std::slice::from_ref(&reveal), &[&user]).await;
+    assert_error(result, BettingPoolsError::RevealNotOpen);
+
+    h.warp_to(bets_close_at + 1).await;
+    let result = h
+        .send(&[instructions::grade_bet(&authority, pool_id, 1, &[])], &[])
+        .await;
+    assert_error(result, BettingPoolsError::RevealInProgress);
+
+    let result = h
+        .send(
+            &[instructions::reveal_bet(
+                &user.pubkey(),
+                pool_id,
+                1,
+                &mint,
+                0,
+                salt,
+            )],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidReveal);
+
+    h.send(std::slice::from_ref(&reveal), &[&user])
+        .await
+        .unwrap();
+    let result = h.send(std::slice::from_ref(&reveal), &[&user]).await;
+    assert_error(result, BettingPoolsError::BetAlreadyRevealed);
+
+    h.warp_to(reveal_ends_at + 1).await;
+    let result = h.send(&[reveal], &[&user]).await;
+    assert_error(result, BettingPoolsError::RevealClosed);
+}
+
+#[tokio::test]
+async fn accounts_are_migrated_once() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+
+    let result = h
+        .send(
+            &[instructions::migrate_pool(
+                &authority,
+                pool_id,
+                None,
+                &[],
+                &[],
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::AccountAlreadyMigrated);
+
+    let pool = h.pool(pool_id).await;
+    h.set_pool(
+        pool_id,
+        &PoolState {
+            version: PoolState::VERSION - 1,
+            ..pool
+        },
+    )
+    .await;
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 0, STAKE)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::AccountNotMigrated);
+}
+
+#[tokio::test]
+async fn the_program_closes_only_once_everything_is_settled() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let treasury = h.fund(&authority, &mint, &spl_token::ID, 0).await;
+    let (user, tokens) = h.user(STAKE).await;
+    let other_mint = h.create_mint().await;
+    let other_mint_account = h.fund(&authority, &other_mint, &spl_token::ID, 0).await;
+
+    let result = h
+        .send(&[instructions::close_betting_pool(&authority, &[])], &[])
+        .await;
+    assert_error(result, BettingPoolsError::VaultsNotDrained);
+
+    let instruction = replace_account(
+        instructions::close_betting_pool(&authority, &[treasury]),
+        &pda::vault(&mint),
+        &treasury.token_account,
+    );
+    let result = h.send(&[instruction], &[]).await;
+    assert_error(result, BettingPoolsError::InvalidVault);
+
+    let wrong_mint = TokenAccounts {
+        token_account: other_mint_account.token_account,
+        ..treasury
+    };
+    let result = h
+        .send(
+            &[instructions::close_betting_pool(&authority, &[wrong_mint])],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidMint);
+
+    let result = h
+        .send(
+            &[instructions::close_betting_pool(&authority, &[tokens])],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidTreasury);
+
+    h.send(
+        &[instructions::deposit(&user.pubkey(), &tokens, STAKE)],
+        &[&user],
+    )
+    .await
+    .unwrap();
+    let result = h
+        .send(
+            &[instructions::close_betting_pool(&authority, &[treasury])],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::OpenBalances);
+
+    h.create_pool().await;
+    let result = h
+        .send(
+            &[instructions::close_betting_pool(&authority, &[treasury])],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::PoolsNotSettled);
+}
+
+#[tokio::test]
+async fn pools_are_marked_settled_once_their_claims_are_gone() {
+    let mut h = Harness::new().await;
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+    let bet_id = h.bet(&user, &tokens, pool_id, 0, STAKE).await;
+
+    let result = h
+        .send(&[instructions::mark_pool_settled(pool_id)], &[])
+        .await;
+    assert_error(result, BettingPoolsError::PoolNotSettled);
+
+    h.grade(pool_id, 0).await;
+    let result = h
+        .send(&[instructions::mark_pool_settled(pool_id)], &[])
+        .await;
+    assert_error(result, BettingPoolsError::PoolNotSettled);
+
+    h.send(
+        &[
+            instructions::claim_payout(&user.pubkey(), None, pool_id, bet_id, &tokens),
+            instructions::mark_pool_settled(pool_id),
+        ],
+        &[&user],
+    )
+    .await
+    .unwrap();
+    let result = h
+        .send(&[instructions::mark_pool_settled(pool_id)], &[])
+        .await;
+    assert_error(result, BettingPoolsError::PoolAlreadySettled);
+}
+
+#[tokio::test]
+async fn markets_sell_shares_within_the_slippage_limit() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let funding = h.fund(&authority, &mint, &spl_token::ID, STAKE).await;
+    let (user, tokens) = h.user(STAKE).await;
+    h.send(
+        &[instructions::create_market(
+            &authority, pool_id, &funding, 1,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 0, STAKE)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::WrongPoolMode);
+
+    let result = h
+        .send(
+            &[instructions::buy_shares(
+                &user.pubkey(),
+                pool_id,
+                &tokens,
+                0,
+                1,
+                0,
+            )],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::SlippageExceeded);
+
+    let result = h
+        .send(
+            &[instructions::buy_shares(
+                &user.pubkey(),
+                pool_id,
+                &tokens,
+                0,
+                u64::MAX,
+                u64::MAX,
+            )],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::MathOverflow);
+}
+
+#[tokio::test]
+async fn offers_are_taken_up_to_their_ask() {
+    let mut h = Harness::new().await;
+    let pool_id = h.create_pool().await;
+    let (maker, maker_tokens) = h.user(STAKE * 2).await;
+    let (taker, taker_tokens) = h.user(STAKE * 2).await;
+    for offer_id in 1..=2 {
+        h.send(
+            &[instructions::create_offer(
+                &maker.pubkey(),
+                pool_id,
+                offer_id,
+                &maker_tokens,
+                0,
+                STAKE,
+                STAKE,
+            )],
+            &[&maker],
+        )
+        .await
+        .unwrap();
+    }
+
+    let result = h
+        .send(
+            &[instructions::accept_offer(
+                &taker.pubkey(),
+                pool_id,
+                1,
+                1,
+                &taker_tokens,
+                STAKE + 1,
+            )],
+            &[&taker],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::OfferExceeded);
+
+    let result = h
+        .send(
+            &[instructions::accept_offer(
+                &maker.pubkey(),
+                pool_id,
+                1,
+                1,
+                &maker_tokens,
+                STAKE,
+            )],
+            &[&maker],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::OwnOffer);
+
+    // Taking the whole offer closes it, leaving only the match's two claims
+    h.send(
+        &[instructions::accept_offer(
+            &taker.pubkey(),
+            pool_id,
+            1,
+            1,
+            &taker_tokens,
+            STAKE,
+        )],
+        &[&taker],
+    )
+    .await
+    .unwrap();
+    assert_eq!(h.pool(pool_id).await.unclaimed_bets, 3);
+    let result = h
+        .send(
+            &[instructions::cancel_offer(
+                &maker.pubkey(),
+                pool_id,
+                1,
+                &maker_tokens,
+            )],
+            &[&maker],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::OfferClosed);
+
+    let cancel = instructions::cancel_offer(&maker.pubkey(), pool_id, 2, &maker_tokens);
+    h.send(std::slice::from_ref(&cancel), &[&maker])
+        .await
+        .unwrap();
+    let result = h.send(&[cancel], &[&maker]).await;
+    assert_error(result, BettingPoolsError::OfferClosed);
+    assert_eq!(h.pool(pool_id).await.unclaimed_bets, 2);
+}
+
+#[tokio::test]
+async fn escrowed_amounts_must_reach_the_vault_in_full() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let pool_id = h.create_pool().await;
+    let mint = h.create_transfer_fee_mint(100).await;
+    h.send(
+        &[instructions::add_mint(
+            &authority,
+            &mint,
+            &spl_token_2022::ID,
+            500,
+            1,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let (maker, _) = h.user(0).await;
+    let tokens = h
+        .fund(&maker.pubkey(), &mint, &spl_token_2022::ID, STAKE)
+        .await;
+
+    let result = h
+        .send(
+            &[instructions::create_offer(
+                &maker.pubkey(),
+                pool_id,
+                1,
+                &tokens,
+                0,
+                STAKE,
+                STAKE,
+            )],
+            &[&maker],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::TokenTransferFailed);
+}
+
+#[tokio::test]
+async fn stakes_must_reach_the_vault_as_more_than_zero() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let pool_id = h.create_pool().await;
+    let mint = h.create_transfer_fee_mint(100).await;
+    h.send(
+        &[instructions::add_mint(
+            &authority,
+            &mint,
+            &spl_token_2022::ID,
+            500,
+            0,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let (user, _) = h.user(0).await;
+    let tokens = h
+        .fund(&user.pubkey(), &mint, &spl_token_2022::ID, STAKE)
+        .await;
+
+    // The transfer fee on a one-token stake rounds up to the whole stake
+    let result = h
+        .send(&[place_bet(&user, &tokens, pool_id, 1, 0, 1)], &[&user])
+        .await;
+    assert_error(result, BettingPoolsError::ZeroStake);
+}
+
+#[tokio::test]
+async fn pools_are_edited_only_before_betting_starts() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+    h.bet(&user, &tokens, pool_id, 0, STAKE).await;
+
+    let result = h
+        .send(
+            &[instructions::update_pool(
+                &authority,
+                pool_id,
+                "Edited?".to_string(),
+                ["Yes".to_string(), "No".to_string()],
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::PoolHasBets);
+
+    let bets_close_at = h.pool(pool_id).await.bets_close_at;
+    let result = h
+        .send(
+            &[instructions::extend_bets_close_at(
+                &authority,
+                pool_id,
+                bets_close_at,
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::CloseTimeNotExtended);
+}
+
+#[tokio::test]
+async fn proposals_escrow_a_bond_settled_once() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let (creator, tokens) = h.user(STAKE).await;
+
+    let pool_id = h.next_pool_id().await;
+    let params = h.pool_params().await;
+    let propose = instructions::propose_pool(&creator.pubkey(), pool_id, &tokens, params);
+    h.send(&[instructions::set_pool_bond(&authority, &mint, 0)], &[])
+        .await
+        .unwrap();
+    let result = h.send(std::slice::from_ref(&propose), &[&creator]).await;
+    assert_error(result, BettingPoolsError::BondNotConfigured);
+
+    h.send(
+        &[instructions::set_pool_bond(&authority, &mint, STAKE)],
+        &[],
+    )
+    .await
+    .unwrap();
+    h.send(&[propose], &[&creator]).await.unwrap();
+    let mint_config: MintConfig = h.account(&pda::mint_config(&mint)).await;
+    assert_eq!(mint_config.bonds, STAKE);
+    h.send(
+        &[instructions::flag_pool_invalid(
+            &authority,
+            pool_id,
+            &mint,
+            &[],
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // A slashed bond stays in the vault, for the treasury
+    let mint_config: MintConfig = h.account(&pda::mint_config(&mint)).await;
+    assert_eq!(mint_config.bonds, 0);
+    let result = h
+        .send(&[instructions::return_bond(pool_id, &tokens)], &[])
+        .await;
+    assert_error(result, BettingPoolsError::BondAlreadySettled);
+}
+
+#[tokio::test]
+async fn bets_are_claimed_against_settled_totals() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+    let bet_id = h.bet(&user, &tokens, pool_id, 0, STAKE).await;
+
+    // Grading has to freeze the payouts in every mint the pool took bets in
+    let result = h
+        .send(&[instructions::grade_bet(&authority, pool_id, 0, &[])], &[])
+        .await;
+    assert_error(result, BettingPoolsError::InvalidSettlementAccounts);
+    h.grade(pool_id, 0).await;
+
+    // Pools graded before payouts were frozen left their totals unsettled
+    let address = pda::pool_totals(pool_id, &mint);
+    let mut totals: PoolTotals = h.account(&address).await;
+    totals.is_settled = false;
+    let mut data = Vec::new();
+    totals.try_serialize(&mut data).unwrap();
+    h.set_program_account(&address, data).await;
+
+    let claim = instructions::claim_payout(&user.pubkey(), None, pool_id, bet_id, &tokens);
+    let result = h.send(std::slice::from_ref(&claim), &[&user]).await;
+    assert_error(result, BettingPoolsError::TotalsNotSettled);
+
+    h.send(&[instructions::settle_pool_totals(pool_id, &mint)], &[])
+        .await
+        .unwrap();
+    let result = h
+        .send(&[instructions::settle_pool_totals(pool_id, &mint)], &[])
+        .await;
+    assert_error(result, BettingPoolsError::TotalsAlreadySettled);
+    h.send(&[claim], &[&user]).await.unwrap();
+}
+
+#[tokio::test]
+async fn signed_bets_need_a_fresh_intent() {
+    let mut h = Harness::new().await;
+    let relayer = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (bettor, tokens) = h.user(STAKE).await;
+    let expires_at = h.now().await + 60;
+    let intent = |nonce, expires_at| instruction::PlaceBetSigned {
+        option_index: 0,
+        amount: STAKE,
+        nonce,
+        expires_at,
+    };
+    let signed = |nonce, expires_at| {
+        instructions::place_bet_signed(
+            &relayer,
+            &bettor.pubkey(),
+            pool_id,
+            1,
+            &tokens,
+            intent(nonce, expires_at),
+        )
+    };
+
+    let result = h.send(&[signed(0, expires_at - 120)], &[]).await;
+    assert_error(result, BettingPoolsError::IntentExpired);
+
+    let result = h.send(&[signed(0, expires_at)], &[]).await;
+    assert_error(result, BettingPoolsError::InvalidSignature);
+
+    // A correctly signed intent that skips ahead of the bettor's nonce
+    let message = bet_intent_message(pool_id, 0, STAKE, &mint, 1, expires_at);
+    let signature = bettor.sign_message(&message);
+    let verify = solana_ed25519_program::new_ed25519_instruction_with_signature(
+        &message,
+        &signature.into(),
+        &bettor.pubkey().to_bytes(),
+    );
+    let result = h.send(&[verify, signed(1, expires_at)], &[]).await;
+    assert_error(result, BettingPoolsError::InvalidNonce);
+}
+
+#[tokio::test]
+async fn sessions_spend_within_their_limits() {
+    let mut h = Harness::new().await;
+    let payer = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (owner, tokens) = h.user(STAKE).await;
+    let now = h.now().await;
+    let session_key = Keypair::new();
+    let capped_key = Keypair::new();
+    h.send(
+        &[
+            system_instruction::transfer(&payer, &session_key.pubkey(), 1_000_000_000),
+            system_instruction::transfer(&payer, &capped_key.pubkey(), 1_000_000_000),
+        ],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let result = h
+        .send(
+            &[instructions::create_session(
+                &owner.pubkey(),
+                &session_key.pubkey(),
+                STAKE,
+                now,
+                vec![mint],
+            )],
+            &[&owner],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::SessionExpired);
+
+    let result = h
+        .send(
+            &[instructions::create_session(
+                &owner.pubkey(),
+                &session_key.pubkey(),
+                STAKE,
+                now + 3600,
+                vec![mint; MAX_SESSION_MINTS + 1],
+            )],
+            &[&owner],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::TooManySessionMints);
+
+    h.send(
+        &[
+            instructions::create_session(
+                &owner.pubkey(),
+                &session_key.pubkey(),
+                STAKE,
+                now + 3600,
+                vec![],
+            ),
+            instructions::create_session(
+                &owner.pubkey(),
+                &capped_key.pubkey(),
+                STAKE - 1,
+                now + 3600,
+                vec![mint],
+            ),
+        ],
+        &[&owner],
+    )
+    .await
+    .unwrap();
+
+    let result = h
+        .send(
+            &[instructions::place_bet(
+                &session_key.pubkey(),
+                Some(&owner.pubkey()),
+                pool_id,
+                1,
+                &tokens,
+                0,
+                STAKE,
+            )],
+            &[&session_key],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::SessionMintNotAllowed);
+
+    let result = h
+        .send(
+            &[instructions::place_bet(
+                &capped_key.pubkey(),
+                Some(&owner.pubkey()),
+                pool_id,
+                1,
+                &tokens,
+                0,
+                STAKE,
+            )],
+            &[&capped_key],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::SessionSpendCapExceeded);
+}
+
+#[tokio::test]
+async fn balances_are_spent_and_closed_only_when_covered() {
+    let mut h = Harness::new().await;
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+    h.send(
+        &[instructions::deposit(&user.pubkey(), &tokens, STAKE)],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let result = h
+        .send(
+            &[instructions::withdraw(&user.pubkey(), &tokens, STAKE + 1)],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InsufficientBalance);
+
+    let result = h
+        .send(
+            &[instructions::place_bet_from_balance(
+                &user.pubkey(),
+                pool_id,
+                1,
+                &mint,
+                0,
+                STAKE + 1,
+            )],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InsufficientBalance);
+
+    let result = h
+        .send(
+            &[instructions::close_balance(&user.pubkey(), &mint)],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::BalanceNotEmpty);
+}
+
+#[tokio::test]
+async fn audits_need_each_pool_once_and_a_covered_vault() {
+    let mut h = Harness::new().await;
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let other_pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE * 2).await;
+    h.bet(&user, &tokens, pool_id, 0, STAKE).await;
+    h.bet(&user, &tokens, other_pool_id, 1, STAKE).await;
+
+    // Pools out of order could count the same pool twice
+    let pools = [
+        (other_pool_id, PoolMode::Parimutuel),
+        (pool_id, PoolMode::Parimutuel),
+    ];
+    let audit = instructions::audit_solvency(&mint, &pools, false);
+    let result = h.send(&[audit], &[]).await;
+    assert_error(result, BettingPoolsError::InvalidAuditAccounts);
+
+    h.set_token_balance(&pda::vault(&mint), STAKE).await;
+    let pools = [
+        (pool_id, PoolMode::Parimutuel),
+        (other_pool_id, PoolMode::Parimutuel),
+    ];
+    let audit = instructions::audit_solvency(&mint, &pools, true);
+    let result = h.send(&[audit], &[]).await;
+    assert_error(result, BettingPoolsError::SolvencyShortfall);
+}
+
+#[tokio::test]
+async fn grading_freezes_only_the_pools_own_totals() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let other_pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE).await;
+    h.bet(&user, &tokens, other_pool_id, 0, STAKE).await;
+
+    let grade = replace_account(
+        instructions::grade_bet(&authority, pool_id, 0, &[mint]),
+        &pda::pool_totals(pool_id, &mint),
+        &pda::pool_totals(other_pool_id, &mint),
+    );
+    let result = h.send(&[grade], &[]).await;
+    assert_error(result, BettingPoolsError::InvalidSettlementAccounts);
+}
+
+#[tokio::test]
+async fn claims_close_with_the_claim_window() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+
+    let result = h
+        .send(&[instructions::set_claim_window(&authority, -1)], &[])
+        .await;
+    assert_error(result, BettingPoolsError::InvalidClaimWindow);
+
+    h.send(&[instructions::set_claim_window(&authority, 100)], &[])
+        .await
+        .unwrap();
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE * 2).await;
+    let (taker, taker_tokens) = h.user(STAKE).await;
+    let bet_id = h.bet(&user, &tokens, pool_id, 0, STAKE).await;
+    h.send(
+        &[instructions::create_offer(
+            &user.pubkey(),
+            pool_id,
+            1,
+            &tokens,
+            0,
+            STAKE,
+            STAKE,
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+    h.send(
+        &[instructions::accept_offer(
+            &taker.pubkey(),
+            pool_id,
+            1,
+            1,
+            &taker_tokens,
+            STAKE,
+        )],
+        &[&taker],
+    )
+    .await
+    .unwrap();
+    h.grade(pool_id, 0).await;
+    let treasury = h.fund(&authority, &mint, &spl_token::ID, 0).await;
+    let offer_match = pda::offer_match(pool_id, 1, 1);
+
+    let result = h
+        .send(
+            &[instructions::sweep_unclaimed(
+                &authority, pool_id, &treasury,
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::ClaimWindowOpen);
+    let result = h
+        .send(
+            &[instructions::sweep_expired_claims(
+                &authority,
+                pool_id,
+                &treasury,
+                false,
+                &[offer_match],
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::ClaimWindowOpen);
+
+    let claim_ends_at = h.pool(pool_id).await.claim_ends_at;
+    h.warp_to(claim_ends_at + 1).await;
+    let result = h
+        .send(
+            &[instructions::claim_payout(
+                &user.pubkey(),
+                None,
+                pool_id,
+                bet_id,
+                &tokens,
+            )],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::ClaimWindowExpired);
+    let result = h
+        .send(
+            &[instructions::claim_match(
+                &user.pubkey(),
+                pool_id,
+                1,
+                1,
+                &tokens,
+            )],
+            &[&user],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::ClaimWindowExpired);
+
+    // Claims counted in the instruction have to be passed
+    let mut sweep =
+        instructions::sweep_expired_claims(&authority, pool_id, &treasury, false, &[offer_match]);
+    sweep.accounts.pop();
+    let result = h.send(&[sweep], &[]).await;
+    assert_error(result, BettingPoolsError::InvalidClaimAccounts);
+
+    h.send(
+        &[instructions::sweep_unclaimed(
+            &authority, pool_id, &treasury,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let result = h
+        .send(
+            &[instructions::sweep_unclaimed(
+                &authority, pool_id, &treasury,
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::PoolAlreadySwept);
+}
+
+#[tokio::test]
+async fn emergency_withdrawals_wait_out_the_delay() {
+    let mut h = Harness::new().await;
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE * 2).await;
+    let (taker, taker_tokens) = h.user(STAKE).await;
+    let (outsider, _) = h.user(0).await;
+    let bet_id = h.bet(&user, &tokens, pool_id, 0, STAKE).await;
+    h.send(
+        &[instructions::create_offer(
+            &user.pubkey(),
+            pool_id,
+            1,
+            &tokens,
+            0,
+            STAKE,
+            STAKE,
+        )],
+        &[&user],
+    )
+    .await
+    .unwrap();
+    h.send(
+        &[instructions::accept_offer(
+            &taker.pubkey(),
+            pool_id,
+            1,
+            1,
+            &taker_tokens,
+            STAKE,
+        )],
+        &[&taker],
+    )
+    .await
+    .unwrap();
+
+    let withdraw = instructions::emergency_withdraw(&user.pubkey(), pool_id, bet_id, &tokens);
+    let result = h.send(std::slice::from_ref(&withdraw), &[&user]).await;
+    assert_error(result, BettingPoolsError::EmergencyDelayNotElapsed);
+    let unwind = |claimant: &Keypair| {
+        instructions::emergency_withdraw_match(
+            &claimant.pubkey(),
+            pool_id,
+            1,
+            1,
+            &tokens,
+            &taker_tokens,
+        )
+    };
+    let result = h.send(&[unwind(&user)], &[&user]).await;
+    assert_error(result, BettingPoolsError::EmergencyDelayNotElapsed);
+
+    let bets_close_at = h.pool(pool_id).await.bets_close_at;
+    h.warp_to(bets_close_at + EMERGENCY_WITHDRAW_DELAY + 1)
+        .await;
+    let result = h.send(&[unwind(&outsider)], &[&outsider]).await;
+    assert_error(result, BettingPoolsError::NotBetOwner);
+    h.send(&[unwind(&user)], &[&user]).await.unwrap();
+    let result = h.send(&[unwind(&taker)], &[&taker]).await;
+    assert_error(result, BettingPoolsError::BetAlreadyWithdrawn);
+
+    // Graded pools are claimed, not withdrawn
+    h.grade(pool_id, 0).await;
+    let result = h.send(&[withdraw], &[&user]).await;
+    assert_error(result, BettingPoolsError::PoolNotOpen);
+}
@@ -0,0 +1,487 @@
+//! In-process bank running the program, with the SPL programs bundled with
+//! `solana-program-test`. Shared by the integration tests, each of which uses a part of it.
+//! `cargo test` runs the program natively. `cargo test-sbf` builds `trump_fun.so` and points
+//! `SBF_OUT_DIR` at it, which `ProgramTest` then loads instead
+
+#![allow(dead_code)]
+
+use anchor_lang::prelude::{AccountInfo, Clock, Pubkey};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::AccountDeserialize;
+use anchor_spl::associated_token::{
+    get_associated_token_address_with_program_id, spl_associated_token_account,
+};
+use anchor_spl::token::spl_token;
+use anchor_spl::token_interface::spl_token_2022;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{Instruction, InstructionError};
+use solana_sdk::program_pack::Pack;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::system_instruction;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use spl_token_2022::extension::transfer_fee::instruction::initialize_transfer_fee_config;
+use spl_token_2022::extension::{ExtensionType, StateWithExtensions};
+use trump_fun::{BettingPoolsError, PoolState};
+use trump_fun_sdk::accounts;
+use trump_fun_sdk::events::{self, ProgramEvent};
+use trump_fun_sdk::instructions::{self, PoolParams, TokenAccounts};
+use trump_fun_sdk::pda;
+
+/// Fee the harness's mint charges on payouts
+pub const FEE_BP: u16 = 500;
+/// Tokens a test user starts with
+pub const STAKE: u64 = 10_000_000;
+
+fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    // Anchor's entrypoint ties the slice to the lifetime of the accounts it holds
+    let accounts = unsafe { std::mem::transmute::<&[AccountInfo], &[AccountInfo]>(accounts) };
+    trump_fun::entry(program_id, accounts, data)
+}
+
+/// Fail unless `result` is the program rejecting the transaction with `error`
+pub fn assert_error(result: Result<(), BanksClientError>, error: BettingPoolsError) {
+    let expected = u32::from(error);
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(
+            _,
+            InstructionError::Custom(code),
+        ))) if code == expected => {}
+        other => panic!("expected error {expected}, got {other:?}"),
+    }
+}
+
+/// The program, initialized by the payer with one accepted SPL mint
+pub struct Harness {
+    pub context: ProgramTestContext,
+    pub mint: Pubkey,
+    /// Makes every transaction unique, so retrying one isn't dropped as already processed
+    sent: u64,
+}
+
+impl Harness {
+    pub async fn new() -> Self {
+        let program_test =
+            ProgramTest::new("trump_fun", trump_fun::ID, processor!(process_instruction));
+        let context = program_test.start_with_context().await;
+        let mut harness = Harness {
+            context,
+            mint: Pubkey::default(),
+            sent: 0,
+        };
+
+        harness.mint = harness.create_mint().await;
+        let authority = harness.authority();
+        harness
+            .send(
+                &[
+                    instructions::initialize(&authority),
+                    instructions::add_mint(&authority, &harness.mint, &spl_token::ID, FEE_BP, 1),
+                ],
+                &[],
+            )
+            .await
+            .unwrap();
+
+        harness
+    }
+
+    pub fn authority(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    async fn transaction(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<Transaction, BanksClientError> {
+        self.sent += 1;
+        let mut all_instructions =
+            vec![ComputeBudgetInstruction::set_compute_unit_price(self.sent)];
+        all_instructions.extend_from_slice(instructions);
+
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        let blockhash = self.context.banks_client.get_latest_blockhash().await?;
+        Ok(Transaction::new_signed_with_payer(
+            &all_instructions,
+            Some(&self.context.payer.pubkey()),
+            &all_signers,
+            blockhash,
+        ))
+    }
+
+    /// Send `instructions` paid for and signed by the payer and `signers`
+    pub async fn send(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Result<(), BanksClientError> {
+        let transaction = self.transaction(instructions, signers).await?;
+        self.context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+    }
+
+    /// Simulate `instructions` and return the events the program emits
+    pub async fn simulate_events(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> Vec<ProgramEvent> {
+        let transaction = self.transaction(instructions, signers).await.unwrap();
+        let account_keys = transaction.message.account_keys.clone();
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        let inner_instructions = simulation
+            .simulation_details
+            .unwrap()
+            .inner_instructions
+            .unwrap();
+        events::parse_inner_instructions(
+            &account_keys,
+            inner_instructions
+                .iter()
+                .flatten()
+                .map(|inner| &inner.instruction),
+        )
+    }
+
+    /// Simulate `instructions`, which must succeed, and return the compute units they spend
+    pub async fn compute_units(
+        &mut self,
+        instructions: &[Instruction],
+        signers: &[&Keypair],
+    ) -> u64 {
+        let transaction = self.transaction(instructions, signers).await.unwrap();
+        let simulation = self
+            .context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        simulation.result.unwrap().unwrap();
+        simulation.simulation_details.unwrap().units_consumed
+    }
+
+    pub async fn now(&mut self) -> i64 {
+        self.clock().await.unix_timestamp
+    }
+
+    async fn clock(&mut self) -> Clock {
+        self.context
+            .banks_client
+            .get_sysvar::<Clock>()
+            .await
+            .unwrap()
+    }
+
+    /// Move the bank's clock to `unix_timestamp`
+    pub async fn warp_to(&mut self, unix_timestamp: i64) {
+        let clock = Clock {
+            unix_timestamp,
+            ..self.clock().await
+        };
+        self.context.set_sysvar(&clock);
+    }
+
+    /// A new SPL mint the payer can mint
+    pub async fn create_mint(&mut self) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.authority();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    rent.minimum_balance(spl_token::state::Mint::LEN),
+                    spl_token::state::Mint::LEN as u64,
+                    &spl_token::ID,
+                ),
+                spl_token::instruction::initialize_mint2(
+                    &spl_token::ID,
+                    &mint.pubkey(),
+                    &payer,
+                    None,
+                    6,
+                )
+                .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    /// A new Token-2022 mint the payer can mint, charging `fee_bp` on every transfer
+    pub async fn create_transfer_fee_mint(&mut self, fee_bp: u16) -> Pubkey {
+        let mint = Keypair::new();
+        let payer = self.authority();
+        let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+            ExtensionType::TransferFeeConfig,
+        ])
+        .unwrap();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.send(
+            &[
+                system_instruction::create_account(
+                    &payer,
+                    &mint.pubkey(),
+                    rent.minimum_balance(space),
+                    space as u64,
+                    &spl_token_2022::ID,
+                ),
+                initialize_transfer_fee_config(
+                    &spl_token_2022::ID,
+                    &mint.pubkey(),
+                    None,
+                    None,
+                    fee_bp,
+                    u64::MAX,
+                )
+                .unwrap(),
+                spl_token_2022::instruction::initialize_mint2(
+                    &spl_token_2022::ID,
+                    &mint.pubkey(),
+                    &payer,
+                    None,
+                    6,
+                )
+                .unwrap(),
+            ],
+            &[&mint],
+        )
+        .await
+        .unwrap();
+        mint.pubkey()
+    }
+
+    /// `owner`'s associated token account for `mint`, created and credited with `amount`
+    pub async fn fund(
+        &mut self,
+        owner: &Pubkey,
+        mint: &Pubkey,
+        token_program: &Pubkey,
+        amount: u64,
+    ) -> TokenAccounts {
+        let payer = self.authority();
+        let token_account =
+            get_associated_token_address_with_program_id(owner, mint, token_program);
+        self.send(
+            &[
+                spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                    &payer,
+                    owner,
+                    mint,
+                    token_program,
+                ),
+                spl_token_2022::instruction::mint_to(
+                    token_program,
+                    mint,
+                    &token_account,
+                    &payer,
+                    &[],
+                    amount,
+                )
+                .unwrap(),
+            ],
+            &[],
+        )
+        .await
+        .unwrap();
+
+        TokenAccounts {
+            mint: *mint,
+            token_account,
+            token_program: *token_program,
+        }
+    }
+
+    /// A new wallet with SOL for rent and `amount` of the harness's mint
+    pub async fn user(&mut self, amount: u64) -> (Keypair, TokenAccounts) {
+        let user = Keypair::new();
+        let payer = self.authority();
+        self.send(
+            &[system_instruction::transfer(
+                &payer,
+                &user.pubkey(),
+                1_000_000_000,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+        let mint = self.mint;
+        let tokens = self
+            .fund(&user.pubkey(), &mint, &spl_token::ID, amount)
+            .await;
+        (user, tokens)
+    }
+
+    /// Settings of an open parimutuel pool that closes in an hour
+    pub async fn pool_params(&mut self) -> PoolParams {
+        PoolParams {
+            question: "Will the harness cover every instruction?".to_string(),
+            options: ["Yes".to_string(), "No".to_string()],
+            bets_close_at: self.now().await + 3600,
+            original_truth_social_post_id: "1".to_string(),
+            image_url: String::new(),
+            hidden_bets: None,
+            time_weighting: None,
+        }
+    }
+
+    /// Create a pool from `params` and return its id
+    pub async fn create_pool_with(&mut self, params: PoolParams) -> u64 {
+        let pool_id = self.next_pool_id().await;
+        let authority = self.authority();
+        self.send(
+            &[instructions::create_pool(&authority, pool_id, params)],
+            &[],
+        )
+        .await
+        .unwrap();
+        pool_id
+    }
+
+    pub async fn create_pool(&mut self) -> u64 {
+        let params = self.pool_params().await;
+        self.create_pool_with(params).await
+    }
+
+    pub async fn next_pool_id(&mut self) -> u64 {
+        self.account::<trump_fun::BettingPoolsState>(&pda::betting_pools())
+            .await
+            .next_pool_id
+    }
+
+    /// Stake `amount` on `option_index` and return the bet's id
+    pub async fn bet(
+        &mut self,
+        user: &Keypair,
+        tokens: &TokenAccounts,
+        pool_id: u64,
+        option_index: u64,
+        amount: u64,
+    ) -> u64 {
+        let bet_id = self.pool(pool_id).await.next_bet_id;
+        self.send(
+            &[instructions::place_bet(
+                &user.pubkey(),
+                None,
+                pool_id,
+                bet_id,
+                tokens,
+                option_index,
+                amount,
+            )],
+            &[user],
+        )
+        .await
+        .unwrap();
+        bet_id
+    }
+
+    /// Grade a pool, freezing its payouts in the harness mint if it has bets in it
+    pub async fn grade(&mut self, pool_id: u64, response_option: u64) {
+        let authority = self.authority();
+        let mint = self.mint;
+        let mints = match self.account_data(&pda::pool_totals(pool_id, &mint)).await {
+            Some(_) => vec![mint],
+            None => vec![],
+        };
+        self.send(
+            &[instructions::grade_bet(
+                &authority,
+                pool_id,
+                response_option,
+                &mints,
+            )],
+            &[],
+        )
+        .await
+        .unwrap();
+    }
+
+    pub async fn account_data(&mut self, address: &Pubkey) -> Option<Vec<u8>> {
+        self.context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .map(|account| account.data)
+    }
+
+    pub async fn account<T: AccountDeserialize>(&mut self, address: &Pubkey) -> T {
+        let data = self.account_data(address).await.unwrap();
+        accounts::decode(&data).unwrap()
+    }
+
+    pub async fn pool(&mut self, pool_id: u64) -> PoolState {
+        let data = self.account_data(&pda::pool(pool_id)).await.unwrap();
+        accounts::decode_pool(&data).unwrap()
+    }
+
+    /// Overwrite a pool's state, e.g. to roll it back to an older version
+    pub async fn set_pool(&mut self, pool_id: u64, pool: &PoolState) {
+        let address = pda::pool(pool_id);
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(address)
+            .await
+            .unwrap()
+            .unwrap();
+        let body = bytemuck::bytes_of(pool);
+        account.data[8..8 + body.len()].copy_from_slice(body);
+        self.context.set_account(&address, &account.into());
+    }
+
+    /// Create or overwrite a program account, e.g. with a layout an earlier version wrote
+    pub async fn set_program_account(&mut self, address: &Pubkey, data: Vec<u8>) {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let account = Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: trump_fun::ID,
+            executable: false,
+            rent_epoch: 0,
+        };
+        self.context.set_account(address, &account.into());
+    }
+
+    /// Overwrite the balance of an SPL token account, e.g. to drain a vault
+    pub async fn set_token_balance(&mut self, address: &Pubkey, amount: u64) {
+        let mut account = self
+            .context
+            .banks_client
+            .get_account(*address)
+            .await
+            .unwrap()
+            .unwrap();
+        let mut state = spl_token::state::Account::unpack(&account.data).unwrap();
+        state.amount = amount;
+        spl_token::state::Account::pack(state, &mut account.data).unwrap();
+        self.context.set_account(address, &account.into());
+    }
+
+    pub async fn token_balance(&mut self, address: &Pubkey) -> u64 {
+        let data = self.account_data(address).await.unwrap();
+        StateWithExtensions::<spl_token_2022::state::Account>::unpack(&data)
+            .unwrap()
+            .base
+            .amount
+    }
+}
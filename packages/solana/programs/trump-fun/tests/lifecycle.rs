@@ -0,0 +1,679 @@
+//! A pool's life from creation to the program's close: bets won, lost and refunded,
+//! and the fees left in the vault swept to the treasury
+
+mod harness;
+
+use anchor_spl::token::spl_token;
+use harness::{Harness, FEE_BP, STAKE};
+use solana_sdk::signature::Signer;
+use trump_fun::{
+    bet_commitment, Bet, BettingPoolsState, HiddenBetsConfig, MintConfig, PoolMetadata, PoolMode,
+    PoolStatus, PoolTotals, TimeWeighting, UserBalance, WeightCurve, EMERGENCY_WITHDRAW_DELAY,
+};
+use trump_fun_sdk::events::ProgramEvent;
+use trump_fun_sdk::instructions;
+use trump_fun_sdk::pda;
+
+/// What a winner of an evenly split pool is paid: both stakes less the fee
+const WINNINGS: u64 = STAKE * 2 - STAKE * 2 * FEE_BP as u64 / 10_000;
+
+#[tokio::test]
+async fn winners_are_paid_and_losers_get_nothing() {
+    let mut h = Harness::new().await;
+    let pool_id = h.create_pool().await;
+    let (winner, winner_tokens) = h.user(STAKE).await;
+    let (loser, loser_tokens) = h.user(STAKE).await;
+    let winning_bet = h.bet(&winner, &winner_tokens, pool_id, 0, STAKE).await;
+    let losing_bet = h.bet(&loser, &loser_tokens, pool_id, 1, STAKE).await;
+
+    h.grade(pool_id, 0).await;
+    let pool = h.pool(pool_id).await;
+    assert!(pool.status() == PoolStatus::Graded);
+    assert_eq!(pool.winning_option, 0);
+
+    h.send(
+        &[
+            instructions::claim_payout(
+                &winner.pubkey(),
+                None,
+                pool_id,
+                winning_bet,
+                &winner_tokens,
+            ),
+            instructions::claim_payout(&loser.pubkey(), None, pool_id, losing_bet, &loser_tokens),
+        ],
+        &[&winner, &loser],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        h.token_balance(&winner_tokens.token_account).await,
+        WINNINGS
+    );
+    assert_eq!(h.token_balance(&loser_tokens.token_account).await, 0);
+    let bet: Bet = h.account(&pda::bet(pool_id, losing_bet)).await;
+    assert!(bet.is_withdrawn);
+    assert_eq!(h.pool(pool_id).await.unclaimed_bets, 0);
+}
+
+#[tokio::test]
+async fn anyone_can_claim_a_bet_to_its_owners_balance() {
+    let mut h = Harness::new().await;
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (winner, winner_tokens) = h.user(STAKE).await;
+    let (loser, loser_tokens) = h.user(STAKE).await;
+    let (crank, _) = h.user(0).await;
+    let winning_bet = h.bet(&winner, &winner_tokens, pool_id, 0, STAKE).await;
+    h.bet(&loser, &loser_tokens, pool_id, 1, STAKE).await;
+    h.grade(pool_id, 0).await;
+
+    h.send(
+        &[instructions::claim_payout_to_balance(
+            &crank.pubkey(),
+            &winner.pubkey(),
+            pool_id,
+            winning_bet,
+            &mint,
+        )],
+        &[&crank],
+    )
+    .await
+    .unwrap();
+
+    let balance: UserBalance = h.account(&pda::user_balance(&winner.pubkey(), &mint)).await;
+    assert_eq!(balance.owner, winner.pubkey());
+    assert_eq!(balance.amount, WINNINGS);
+    assert!(h
+        .account_data(&pda::user_balance(&crank.pubkey(), &mint))
+        .await
+        .is_none());
+}
+
+#[tokio::test]
+async fn fee_changes_after_grading_leave_claims_alone() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (winner, winner_tokens) = h.user(STAKE).await;
+    let (loser, loser_tokens) = h.user(STAKE).await;
+    let (maker, maker_tokens) = h.user(STAKE).await;
+    let (taker, taker_tokens) = h.user(STAKE).await;
+    let winning_bet = h.bet(&winner, &winner_tokens, pool_id, 0, STAKE).await;
+    h.bet(&loser, &loser_tokens, pool_id, 1, STAKE).await;
+    h.send(
+        &[instructions::create_offer(
+            &maker.pubkey(),
+            pool_id,
+            1,
+            &maker_tokens,
+            0,
+            STAKE,
+            STAKE,
+        )],
+        &[&maker],
+    )
+    .await
+    .unwrap();
+    h.send(
+        &[instructions::accept_offer(
+            &taker.pubkey(),
+            pool_id,
+            1,
+            1,
+            &taker_tokens,
+            STAKE,
+        )],
+        &[&taker],
+    )
+    .await
+    .unwrap();
+
+    h.grade(pool_id, 0).await;
+    let totals: PoolTotals = h.account(&pda::pool_totals(pool_id, &mint)).await;
+    assert!(totals.is_settled);
+    assert!(!totals.is_refund);
+    assert_eq!(totals.payout_numerator, STAKE);
+    assert_eq!(totals.payout_denominator, STAKE);
+    assert_eq!(totals.fee_bp, FEE_BP);
+
+    h.send(
+        &[instructions::update_mint(
+            &authority,
+            &mint,
+            FEE_BP * 4,
+            1,
+            true,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    h.send(
+        &[
+            instructions::claim_payout(
+                &winner.pubkey(),
+                None,
+                pool_id,
+                winning_bet,
+                &winner_tokens,
+            ),
+            instructions::claim_match(&maker.pubkey(), pool_id, 1, 1, &maker_tokens),
+        ],
+        &[&winner, &maker],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        h.token_balance(&winner_tokens.token_account).await,
+        WINNINGS
+    );
+    assert_eq!(h.token_balance(&maker_tokens.token_account).await, WINNINGS);
+}
+
+#[tokio::test]
+async fn draws_refund_every_stake() {
+    let mut h = Harness::new().await;
+    let pool_id = h.create_pool().await;
+    let (first, first_tokens) = h.user(STAKE).await;
+    let (second, second_tokens) = h.user(STAKE).await;
+    let first_bet = h.bet(&first, &first_tokens, pool_id, 0, STAKE).await;
+    let second_bet = h.bet(&second, &second_tokens, pool_id, 1, STAKE / 2).await;
+
+    h.grade(pool_id, 2).await;
+    assert!(h.pool(pool_id).await.is_draw());
+
+    h.send(
+        &[
+            instructions::claim_payout(&first.pubkey(), None, pool_id, first_bet, &first_tokens),
+            instructions::claim_payout(&second.pubkey(), None, pool_id, second_bet, &second_tokens),
+        ],
+        &[&first, &second],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(h.token_balance(&first_tokens.token_account).await, STAKE);
+    assert_eq!(h.token_balance(&second_tokens.token_account).await, STAKE);
+}
+
+#[tokio::test]
+async fn bettors_take_their_stakes_back_from_abandoned_pools() {
+    let mut h = Harness::new().await;
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (first, first_tokens) = h.user(STAKE).await;
+    let (second, second_tokens) = h.user(STAKE).await;
+    let (maker, maker_tokens) = h.user(STAKE).await;
+    let (taker, taker_tokens) = h.user(STAKE).await;
+    let first_bet = h.bet(&first, &first_tokens, pool_id, 0, STAKE).await;
+    let second_bet = h.bet(&second, &second_tokens, pool_id, 1, STAKE).await;
+    h.send(
+        &[instructions::create_offer(
+            &maker.pubkey(),
+            pool_id,
+            1,
+            &maker_tokens,
+            0,
+            STAKE,
+            STAKE,
+        )],
+        &[&maker],
+    )
+    .await
+    .unwrap();
+    h.send(
+        &[instructions::accept_offer(
+            &taker.pubkey(),
+            pool_id,
+            1,
+            1,
+            &taker_tokens,
+            STAKE,
+        )],
+        &[&taker],
+    )
+    .await
+    .unwrap();
+
+    let bets_close_at = h.pool(pool_id).await.bets_close_at;
+    h.warp_to(bets_close_at + EMERGENCY_WITHDRAW_DELAY + 1)
+        .await;
+
+    // Either side of a match unwinds it for both
+    h.send(
+        &[instructions::emergency_withdraw_match(
+            &taker.pubkey(),
+            pool_id,
+            1,
+            1,
+            &maker_tokens,
+            &taker_tokens,
+        )],
+        &[&taker],
+    )
+    .await
+    .unwrap();
+    assert_eq!(h.token_balance(&maker_tokens.token_account).await, STAKE);
+    assert_eq!(h.token_balance(&taker_tokens.token_account).await, STAKE);
+    let mint_config: MintConfig = h.account(&pda::mint_config(&mint)).await;
+    assert_eq!(mint_config.escrowed, 0);
+    h.send(
+        &[instructions::emergency_withdraw(
+            &first.pubkey(),
+            pool_id,
+            first_bet,
+            &first_tokens,
+        )],
+        &[&first],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(h.token_balance(&first_tokens.token_account).await, STAKE);
+    let totals: PoolTotals = h.account(&pda::pool_totals(pool_id, &mint)).await;
+    assert_eq!(totals.bet_totals, [0, STAKE]);
+    assert_eq!(h.pool(pool_id).await.unclaimed_bets, 1);
+
+    // Grading later settles the bets left as if the withdrawn one was never placed
+    h.grade(pool_id, 1).await;
+    h.send(
+        &[instructions::claim_payout(
+            &second.pubkey(),
+            None,
+            pool_id,
+            second_bet,
+            &second_tokens,
+        )],
+        &[&second],
+    )
+    .await
+    .unwrap();
+    assert_eq!(h.token_balance(&second_tokens.token_account).await, STAKE);
+    assert_eq!(h.token_balance(&pda::vault(&mint)).await, 0);
+}
+
+#[tokio::test]
+async fn set_image_replaces_the_pool_image() {
+    let mut h = Harness::new().await;
+    let pool_id = h.create_pool().await;
+    let authority = h.authority();
+
+    h.send(
+        &[instructions::set_image(
+            &authority,
+            pool_id,
+            "https://example.com/pool.png".to_string(),
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let metadata: PoolMetadata = h.account(&pda::pool_metadata(pool_id)).await;
+    assert_eq!(metadata.image_url, "https://example.com/pool.png");
+}
+
+#[tokio::test]
+async fn closing_the_program_sweeps_fees_to_the_treasury() {
+    let mut h = Harness::new().await;
+    let pool_id = h.create_pool().await;
+    let (winner, winner_tokens) = h.user(STAKE).await;
+    let (loser, loser_tokens) = h.user(STAKE).await;
+    let winning_bet = h.bet(&winner, &winner_tokens, pool_id, 1, STAKE).await;
+    let losing_bet = h.bet(&loser, &loser_tokens, pool_id, 0, STAKE).await;
+    h.grade(pool_id, 1).await;
+    h.send(
+        &[
+            instructions::claim_payout(
+                &winner.pubkey(),
+                None,
+                pool_id,
+                winning_bet,
+                &winner_tokens,
+            ),
+            instructions::claim_payout(&loser.pubkey(), None, pool_id, losing_bet, &loser_tokens),
+        ],
+        &[&winner, &loser],
+    )
+    .await
+    .unwrap();
+    // Claims leave the global state alone, the settled pool is counted separately
+    let betting_pools: BettingPoolsState = h.account(&pda::betting_pools()).await;
+    assert_eq!(betting_pools.settled_pools, 0);
+    h.send(&[instructions::mark_pool_settled(pool_id)], &[])
+        .await
+        .unwrap();
+    let betting_pools: BettingPoolsState = h.account(&pda::betting_pools()).await;
+    assert_eq!(betting_pools.settled_pools, 1);
+
+    // Fees go to the authority until a treasury is set
+    let authority = h.authority();
+    let mint = h.mint;
+    let treasury = h.fund(&authority, &mint, &spl_token::ID, 0).await;
+    h.send(
+        &[instructions::close_betting_pool(&authority, &[treasury])],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        h.token_balance(&treasury.token_account).await,
+        STAKE * 2 - WINNINGS
+    );
+    assert!(h.account_data(&pda::vault(&mint)).await.is_none());
+    assert!(h.account_data(&pda::mint_config(&mint)).await.is_none());
+    assert!(h.account_data(&pda::betting_pools()).await.is_none());
+}
+
+#[tokio::test]
+async fn unclaimed_stakes_go_to_the_treasury_after_the_claim_window() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    h.send(&[instructions::set_claim_window(&authority, 3600)], &[])
+        .await
+        .unwrap();
+    let pool_id = h.create_pool().await;
+    let (winner, winner_tokens) = h.user(STAKE).await;
+    let (loser, loser_tokens) = h.user(STAKE).await;
+    h.bet(&winner, &winner_tokens, pool_id, 0, STAKE).await;
+    let losing_bet = h.bet(&loser, &loser_tokens, pool_id, 1, STAKE).await;
+    h.grade(pool_id, 0).await;
+    h.send(
+        &[instructions::claim_payout(
+            &loser.pubkey(),
+            None,
+            pool_id,
+            losing_bet,
+            &loser_tokens,
+        )],
+        &[&loser],
+    )
+    .await
+    .unwrap();
+
+    let pool = h.pool(pool_id).await;
+    assert_eq!(pool.claim_ends_at, pool.decision_time + 3600);
+    h.warp_to(pool.claim_ends_at + 1).await;
+
+    // The winner never claimed, so both stakes are left
+    let treasury = h.fund(&authority, &mint, &spl_token::ID, 0).await;
+    h.send(
+        &[instructions::sweep_unclaimed(
+            &authority, pool_id, &treasury,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(h.token_balance(&treasury.token_account).await, STAKE * 2);
+    assert_eq!(h.token_balance(&pda::vault(&mint)).await, 0);
+    let pool = h.pool(pool_id).await;
+    assert!(pool.is_swept());
+    assert_eq!(pool.unclaimed_bets, 0);
+    let totals: PoolTotals = h.account(&pda::pool_totals(pool_id, &mint)).await;
+    assert!(totals.is_swept);
+    let betting_pools: BettingPoolsState = h.account(&pda::betting_pools()).await;
+    assert_eq!(betting_pools.settled_pools, 1);
+}
+
+#[tokio::test]
+async fn forfeited_stakes_and_expired_matches_go_to_the_treasury() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    h.send(&[instructions::set_claim_window(&authority, 3600)], &[])
+        .await
+        .unwrap();
+    let mut params = h.pool_params().await;
+    let bets_close_at = params.bets_close_at;
+    let reveal_ends_at = bets_close_at + 3600;
+    params.hidden_bets = Some(HiddenBetsConfig {
+        reveal_ends_at,
+        forfeit_unrevealed: true,
+    });
+    let pool_id = h.create_pool_with(params).await;
+    let (bettor, bettor_tokens) = h.user(STAKE * 2).await;
+    let (maker, maker_tokens) = h.user(STAKE).await;
+    let (taker, taker_tokens) = h.user(STAKE).await;
+    let salt = [7; 32];
+    h.send(
+        &[
+            instructions::commit_bet(
+                &bettor.pubkey(),
+                None,
+                pool_id,
+                1,
+                &bettor_tokens,
+                bet_commitment(0, &salt, &bettor.pubkey()),
+                STAKE,
+            ),
+            instructions::commit_bet(
+                &bettor.pubkey(),
+                None,
+                pool_id,
+                2,
+                &bettor_tokens,
+                bet_commitment(1, &salt, &bettor.pubkey()),
+                STAKE,
+            ),
+        ],
+        &[&bettor],
+    )
+    .await
+    .unwrap();
+    h.send(
+        &[instructions::create_offer(
+            &maker.pubkey(),
+            pool_id,
+            1,
+            &maker_tokens,
+            0,
+            STAKE,
+            STAKE,
+        )],
+        &[&maker],
+    )
+    .await
+    .unwrap();
+    h.send(
+        &[instructions::accept_offer(
+            &taker.pubkey(),
+            pool_id,
+            1,
+            1,
+            &taker_tokens,
+            STAKE,
+        )],
+        &[&taker],
+    )
+    .await
+    .unwrap();
+
+    // The second bet is never revealed, so its stake is forfeited
+    h.warp_to(bets_close_at + 1).await;
+    h.send(
+        &[instructions::reveal_bet(
+            &bettor.pubkey(),
+            pool_id,
+            1,
+            &mint,
+            0,
+            salt,
+        )],
+        &[&bettor],
+    )
+    .await
+    .unwrap();
+    h.warp_to(reveal_ends_at + 1).await;
+    h.grade(pool_id, 0).await;
+    let claim_ends_at = h.pool(pool_id).await.claim_ends_at;
+    h.warp_to(claim_ends_at + 1).await;
+
+    // Both bets' stakes are swept, leaving the match's two claims
+    let treasury = h.fund(&authority, &mint, &spl_token::ID, 0).await;
+    h.send(
+        &[instructions::sweep_unclaimed(
+            &authority, pool_id, &treasury,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(h.token_balance(&treasury.token_account).await, STAKE * 2);
+    let pool = h.pool(pool_id).await;
+    assert_eq!(pool.unclaimed_bets, 2);
+    assert!(!pool.is_settled());
+    let totals: PoolTotals = h.account(&pda::pool_totals(pool_id, &mint)).await;
+    assert_eq!(totals.unclaimed_bets, 0);
+
+    h.send(
+        &[instructions::sweep_expired_claims(
+            &authority,
+            pool_id,
+            &treasury,
+            false,
+            &[pda::offer_match(pool_id, 1, 1)],
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    assert_eq!(h.token_balance(&treasury.token_account).await, STAKE * 4);
+    assert_eq!(h.token_balance(&pda::vault(&mint)).await, 0);
+    let pool = h.pool(pool_id).await;
+    assert_eq!(pool.unclaimed_bets, 0);
+    assert!(pool.is_settled());
+    let mint_config: MintConfig = h.account(&pda::mint_config(&mint)).await;
+    assert_eq!(mint_config.escrowed, 0);
+}
+
+#[tokio::test]
+async fn audits_compare_the_vault_with_what_is_owed() {
+    let mut h = Harness::new().await;
+    let mint = h.mint;
+    let graded_pool = h.create_pool().await;
+    let open_pool = h.create_pool().await;
+    let (winner, winner_tokens) = h.user(STAKE * 2).await;
+    let (loser, loser_tokens) = h.user(STAKE).await;
+    let (maker, maker_tokens) = h.user(STAKE * 2).await;
+    h.bet(&winner, &winner_tokens, graded_pool, 0, STAKE).await;
+    let losing_bet = h.bet(&loser, &loser_tokens, graded_pool, 1, STAKE).await;
+    h.bet(&winner, &winner_tokens, open_pool, 1, STAKE).await;
+    h.grade(graded_pool, 0).await;
+    h.send(
+        &[
+            instructions::claim_payout(
+                &loser.pubkey(),
+                None,
+                graded_pool,
+                losing_bet,
+                &loser_tokens,
+            ),
+            instructions::deposit(&maker.pubkey(), &maker_tokens, STAKE),
+            instructions::create_offer(
+                &maker.pubkey(),
+                open_pool,
+                1,
+                &maker_tokens,
+                0,
+                STAKE,
+                STAKE,
+            ),
+        ],
+        &[&loser, &maker],
+    )
+    .await
+    .unwrap();
+
+    // The graded pool owes its winner both stakes with the fee, the open pool its stake
+    // back, and the maker's deposit and offer are held for them
+    let pools = [
+        (graded_pool, PoolMode::Parimutuel),
+        (open_pool, PoolMode::Parimutuel),
+    ];
+    let events = h
+        .simulate_events(&[instructions::audit_solvency(&mint, &pools, true)], &[])
+        .await;
+    let Some(ProgramEvent::SolvencyReport(report)) = events.last() else {
+        panic!("no solvency report");
+    };
+    assert_eq!(report.vault_balance, STAKE * 5);
+    assert_eq!(report.pools_owe, STAKE * 3);
+    assert_eq!(report.balances, STAKE);
+    assert_eq!(report.escrowed, STAKE);
+    assert_eq!(report.bonds, 0);
+    assert_eq!(report.liabilities, STAKE * 5);
+    assert_eq!(report.shortfall, 0);
+    assert_eq!(report.pools_audited, 2);
+
+    h.set_token_balance(&pda::vault(&mint), STAKE).await;
+    let events = h
+        .simulate_events(&[instructions::audit_solvency(&mint, &pools, false)], &[])
+        .await;
+    let Some(ProgramEvent::SolvencyReport(report)) = events.last() else {
+        panic!("no solvency report");
+    };
+    assert_eq!(report.shortfall, STAKE * 4);
+}
+
+#[tokio::test]
+async fn extending_a_weighted_pool_keeps_winnings_within_its_stakes() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let mut params = h.pool_params().await;
+    params.time_weighting = Some(TimeWeighting {
+        curve: WeightCurve::Linear,
+        max_bonus_bp: 10_000,
+    });
+    let bets_close_at = params.bets_close_at;
+    let pool_id = h.create_pool_with(params).await;
+    let (early, early_tokens) = h.user(STAKE).await;
+    let (late, late_tokens) = h.user(STAKE).await;
+    let (loser, loser_tokens) = h.user(STAKE * 10).await;
+    let early_bet = h.bet(&early, &early_tokens, pool_id, 0, STAKE).await;
+    h.warp_to(bets_close_at - 1800).await;
+    let late_bet = h.bet(&late, &late_tokens, pool_id, 0, STAKE).await;
+    h.bet(&loser, &loser_tokens, pool_id, 1, STAKE * 10).await;
+    let late_weight = h.account::<Bet>(&pda::bet(pool_id, late_bet)).await.weight;
+    assert!(late_weight > STAKE);
+
+    // Half the window was left for the late bet. Against the extended close time far
+    // more would be, but its weight stays what the totals were given
+    h.send(
+        &[instructions::extend_bets_close_at(
+            &authority,
+            pool_id,
+            bets_close_at + 36_000,
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    h.grade(pool_id, 0).await;
+    h.send(
+        &[
+            instructions::claim_payout(&early.pubkey(), None, pool_id, early_bet, &early_tokens),
+            instructions::claim_payout(&late.pubkey(), None, pool_id, late_bet, &late_tokens),
+        ],
+        &[&early, &late],
+    )
+    .await
+    .unwrap();
+
+    let late_bet: Bet = h.account(&pda::bet(pool_id, late_bet)).await;
+    assert_eq!(late_bet.weight, late_weight);
+    let paid = h.token_balance(&early_tokens.token_account).await
+        + h.token_balance(&late_tokens.token_account).await;
+    assert!(paid <= STAKE * 12);
+    let totals: PoolTotals = h.account(&pda::pool_totals(pool_id, &mint)).await;
+    assert_eq!(h.token_balance(&pda::vault(&mint)).await, STAKE * 12 - paid);
+    assert_eq!(totals.claimed, paid);
+}
@@ -0,0 +1,244 @@
+//! Accounts written by the version of the program that launched, upgraded in place by
+//! the `migrate_*` instructions
+
+mod harness;
+
+use anchor_lang::{AnchorSerialize, Discriminator, Space};
+use harness::{assert_error, Harness, STAKE};
+use solana_sdk::signature::Signer;
+use trump_fun::{
+    Bet, BettingPoolsError, BettingPoolsState, MintConfig, OriginalBet, OriginalBettingPoolsState,
+    OriginalPool, Pool, PoolMetadata, PoolState, PoolStatus, PoolTotals, TokenType, Versioned,
+};
+use trump_fun_sdk::instructions;
+use trump_fun_sdk::pda;
+
+/// An account's data as the launch version allocated it: exactly `space` bytes
+/// after the discriminator, zero padded past the serialized state
+fn original_account(discriminator: &[u8], state: &impl AnchorSerialize, space: usize) -> Vec<u8> {
+    let mut data = discriminator.to_vec();
+    state.serialize(&mut data).unwrap();
+    data.resize(8 + space, 0);
+    data
+}
+
+#[tokio::test]
+async fn accounts_in_the_original_layout_are_migrated() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let usdc_mint = h.mint;
+    let points_mint = h.create_mint().await;
+    let pool_id = 1;
+
+    let betting_pools = OriginalBettingPoolsState {
+        authority,
+        usdc_mint,
+        freedom_mint: points_mint,
+        next_pool_id: pool_id + 1,
+        next_bet_id: 3,
+        payout_fee_bp: 500,
+        is_initialized: true,
+    };
+    let pool = OriginalPool {
+        id: pool_id,
+        question: "Will it happen?".to_string(),
+        options: ["Yes".to_string(), "No".to_string()],
+        bets_close_at: 1_000,
+        decision_time: 0,
+        usdc_bet_totals: [STAKE, 0],
+        points_bet_totals: [0, STAKE * 2],
+        winning_option: 0,
+        status: PoolStatus::Pending,
+        is_draw: false,
+        created_at: 500,
+        original_truth_social_post_id: "1".to_string(),
+        image_url: String::new(),
+    };
+    let bets = [
+        (1, 0, STAKE, TokenType::Usdc),
+        (2, 1, STAKE * 2, TokenType::Points),
+    ];
+
+    h.set_program_account(
+        &pda::betting_pools(),
+        original_account(
+            BettingPoolsState::DISCRIMINATOR,
+            &betting_pools,
+            OriginalBettingPoolsState::INIT_SPACE,
+        ),
+    )
+    .await;
+    h.set_program_account(
+        &pda::pool(pool_id),
+        original_account(Pool::DISCRIMINATOR, &pool, OriginalPool::INIT_SPACE),
+    )
+    .await;
+    for (bet_id, option, amount, token_type) in bets {
+        let bet = OriginalBet {
+            id: bet_id,
+            owner: authority,
+            option,
+            amount,
+            pool_id,
+            created_at: 600,
+            updated_at: 600,
+            // The points bet was already refunded
+            is_withdrawn: token_type == TokenType::Points,
+            token_type,
+        };
+        h.set_program_account(
+            &pda::bet(pool_id, bet_id),
+            original_account(Bet::DISCRIMINATOR, &bet, OriginalBet::INIT_SPACE),
+        )
+        .await;
+    }
+
+    h.send(&[instructions::migrate_betting_pools(&authority)], &[])
+        .await
+        .unwrap();
+    let state: BettingPoolsState = h.account(&pda::betting_pools()).await;
+    assert_eq!(state.authority, authority);
+    assert_eq!(state.next_pool_id, pool_id + 1);
+    assert_eq!(state.next_bet_id, 3);
+    assert_eq!(state.legacy_mints, [usdc_mint, points_mint]);
+    assert_eq!(state.version, BettingPoolsState::VERSION);
+
+    // The pool's stakes have nowhere to go without the legacy mints' totals
+    let result = h
+        .send(
+            &[instructions::migrate_pool(
+                &authority,
+                pool_id,
+                None,
+                &[],
+                &[1, 2],
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::MissingLegacyTotals);
+
+    // Bets have to be in id order for each to be counted once
+    for bet_ids in [[2, 1], [1, 1]] {
+        let result = h
+            .send(
+                &[instructions::migrate_pool(
+                    &authority,
+                    pool_id,
+                    Some(&state.legacy_mints),
+                    &[],
+                    &bet_ids,
+                )],
+                &[],
+            )
+            .await;
+        assert_error(result, BettingPoolsError::InvalidMigrationAccounts);
+    }
+
+    h.send(
+        &[instructions::migrate_pool(
+            &authority,
+            pool_id,
+            Some(&state.legacy_mints),
+            &[],
+            &[1, 2],
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let migrated = h.pool(pool_id).await;
+    assert_eq!(migrated.version, PoolState::VERSION);
+    assert!(migrated.status() == PoolStatus::Pending);
+    assert_eq!(migrated.bets_close_at, 1_000);
+    assert_eq!(migrated.next_bet_id, 3);
+    assert_eq!(migrated.unclaimed_bets, 1);
+    assert!(!migrated.is_settled());
+    assert_eq!(migrated.totals_count, 2);
+    let metadata: PoolMetadata = h.account(&pda::pool_metadata(pool_id)).await;
+    assert_eq!(metadata.question, "Will it happen?");
+    for (mint, bet_totals, unclaimed_bets) in
+        [(usdc_mint, [STAKE, 0], 1), (points_mint, [0, STAKE * 2], 0)]
+    {
+        let totals: PoolTotals = h.account(&pda::pool_totals(pool_id, &mint)).await;
+        assert_eq!(totals.pool_id, pool_id);
+        assert_eq!(totals.bet_totals, bet_totals);
+        assert_eq!(totals.weighted_totals, bet_totals);
+        assert_eq!(totals.staked, bet_totals[0] + bet_totals[1]);
+        assert_eq!(totals.unclaimed_bets, unclaimed_bets);
+    }
+
+    for (bet_id, option, amount, mint) in [(1, 0, STAKE, usdc_mint), (2, 1, STAKE * 2, points_mint)]
+    {
+        h.send(
+            &[instructions::migrate_bet(&authority, pool_id, bet_id)],
+            &[],
+        )
+        .await
+        .unwrap();
+        let bet: Bet = h.account(&pda::bet(pool_id, bet_id)).await;
+        assert_eq!(bet.owner, authority);
+        assert_eq!(bet.option, option);
+        assert_eq!(bet.amount, amount);
+        assert_eq!(bet.mint, mint);
+        assert!(bet.is_revealed);
+        // Bets from before time weighting weigh what they staked
+        assert_eq!(bet.weight, amount);
+        assert_eq!(bet.version, Bet::VERSION);
+    }
+}
+
+#[tokio::test]
+async fn mint_configs_recount_what_they_hold() {
+    let mut h = Harness::new().await;
+    let authority = h.authority();
+    let mint = h.mint;
+    let pool_id = h.create_pool().await;
+    let (user, tokens) = h.user(STAKE * 3).await;
+    h.send(
+        &[
+            instructions::deposit(&user.pubkey(), &tokens, STAKE),
+            instructions::create_offer(&user.pubkey(), pool_id, 1, &tokens, 0, STAKE * 2, STAKE),
+        ],
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    // Cut the config back to the fields it had before it was versioned
+    let address = pda::mint_config(&mint);
+    let mut data = h.account_data(&address).await.unwrap();
+    data.truncate(8 + 32 + 32 + 1 + 2 + 8 + 1);
+    h.set_program_account(&address, data).await;
+
+    let balance = pda::user_balance(&user.pubkey(), &mint);
+    let offer = pda::offer(pool_id, 1);
+    let result = h
+        .send(
+            &[instructions::migrate_mint_config(
+                &authority,
+                &mint,
+                &[balance, offer, balance],
+            )],
+            &[],
+        )
+        .await;
+    assert_error(result, BettingPoolsError::InvalidMigrationAccounts);
+
+    h.send(
+        &[instructions::migrate_mint_config(
+            &authority,
+            &mint,
+            &[balance, offer],
+        )],
+        &[],
+    )
+    .await
+    .unwrap();
+    let mint_config: MintConfig = h.account(&address).await;
+    assert_eq!(mint_config.version, MintConfig::VERSION);
+    assert_eq!(mint_config.balances, STAKE);
+    assert_eq!(mint_config.escrowed, STAKE * 2);
+    assert_eq!(mint_config.bonds, 0);
+    assert!(mint_config.is_active);
+}
@@ -3,18 +3,33 @@ import { Program } from '@coral-xyz/anchor';
 import {
   ASSOCIATED_TOKEN_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
-  getOrCreateAssociatedTokenAccount,
+  createApproveInstruction,
+  getAssociatedTokenAddress,
 } from '@solana/spl-token';
 import { expect } from 'chai';
 
 import { TrumpFun } from '../target/types/trump_fun';
 import { getOrCreateFreedomMint, getUsdcMint } from './create-token';
 import {
+  BALANCE_SEED,
   BETTING_POOLS_SEED,
   BET_SEED,
-  TokenType,
+  MARKET_SEED,
+  MATCH_SEED,
+  NONCE_SEED,
+  OFFER_SEED,
+  POOL_BOND_SEED,
+  POOL_METADATA_SEED,
+  POOL_SEED,
+  POSITION_SEED,
+  SESSION_SEED,
+  betCommitment,
+  betIntentMessage,
   createBettingPool,
   createFundedUser,
+  fetchCpiEvents,
+  findMintAccounts,
+  findPoolTotals,
   tokensToLamports,
 } from './utils';
 
@@ -83,7 +98,7 @@ describe('solana', () => {
         // If fetch fails, the account doesn't exist yet, so initialize it
         console.log('BettingPools not initialized, initializing...', e);
         const tx = await program.methods
-          .initialize(usdcMint, freedomMint)
+          .initialize()
           .accounts({
             bettingPools: bettingPoolsAddress,
             authority: wallet.publicKey,
@@ -93,28 +108,61 @@ describe('solana', () => {
         console.log('Your transaction signature', tx);
       }
 
+      // Accept USDC and FREEDOM for betting if they are not accepted yet
+      for (const mint of [usdcMint, freedomMint]) {
+        const { mintConfig, vault } = findMintAccounts(program.programId, mint);
+        const existingConfig = await program.account.mintConfig.fetchNullable(mintConfig);
+        if (!existingConfig) {
+          await program.methods
+            .addMint(90, new anchor.BN(0))
+            .accounts({
+              bettingPools: bettingPoolsAddress,
+              mint,
+              mintConfig,
+              vault,
+              authority: wallet.publicKey,
+              tokenProgram: TOKEN_PROGRAM_ID,
+              systemProgram: anchor.web3.SystemProgram.programId,
+            } as any)
+            .rpc();
+        }
+        const config = await program.account.mintConfig.fetch(mintConfig);
+        void expect(config.vault.equals(vault)).to.be.true;
+        void expect(config.isActive).to.be.true;
+      }
+
       const bettingPoolsState = await program.account.bettingPoolsState.fetch(bettingPoolsAddress);
       console.log('---bettingPoolsAddress state post creation---');
       console.log(bettingPoolsState);
 
       console.log('Betting pools state post creation');
       void expect(bettingPoolsState.authority.equals(wallet.publicKey)).to.be.true;
-      void expect(
-        bettingPoolsState.usdcMint.equals(usdcMint) || bettingPoolsState.usdcMint.toString() !== ''
-      ).to.be.true;
-      void expect(
-        bettingPoolsState.freedomMint.equals(freedomMint) ||
-          bettingPoolsState.freedomMint.toString() !== ''
-      ).to.be.true;
       void expect(parseInt(bettingPoolsState.nextPoolId.toString())).to.be.at.least(1);
-      void expect(parseInt(bettingPoolsState.nextBetId.toString())).to.be.at.least(1);
-      void expect(bettingPoolsState.payoutFeeBp).to.equal(90);
     } catch (e) {
       console.error('Error in initialize test:', e);
       throw e;
     }
   });
 
+  it('migrateBettingPools rejects an account already at the current version', async () => {
+    try {
+      await program.methods
+        .migrateBettingPools()
+        .accounts({
+          bettingPools: bettingPoolsAddress,
+          authority: wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .rpc();
+      expect.fail('Expected migrateBettingPools to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('AccountAlreadyMigrated');
+    }
+
+    const bettingPoolsState = await program.account.bettingPoolsState.fetch(bettingPoolsAddress);
+    void expect(bettingPoolsState.version).to.equal(2);
+  });
+
   it('Create a new betting pool', async () => {
     try {
       console.log('Creating pool');
@@ -129,6 +177,7 @@ describe('solana', () => {
       // Create the pool using our utility function
       const {
         poolAddress: newPoolAddress,
+        poolMetadataAddress: newPoolMetadataAddress,
         poolId: newPoolId,
         tx,
       } = await createBettingPool(program, bettingPoolsAddress, wallet.publicKey, {
@@ -143,17 +192,19 @@ describe('solana', () => {
 
       // Fetch the pool account
       console.log('Pool address:', newPoolAddress);
-      const poolAccount = await program.account.pool.fetch(newPoolAddress);
+      const poolAccount = await program.account.poolState.fetch(newPoolAddress);
+      const poolMetadata = await program.account.poolMetadata.fetch(newPoolMetadataAddress);
       console.log('Pool account:', poolAccount);
 
       // Verify the pool data
       void expect(poolAccount.id.toString()).to.equal(newPoolId.toString());
-      void expect(poolAccount.question).to.equal(question);
-      void expect(poolAccount.options).to.deep.equal(options);
       void expect(poolAccount.betsCloseAt.toString()).to.equal(betsCloseAt.toString());
-      void expect('pending' in poolAccount.status).to.be.true; // Check that it's the Pending status
-      void expect(poolAccount.originalTruthSocialPostId).to.equal(original_truth_social_post_id);
-      void expect(poolAccount.imageUrl).to.equal(image_url);
+      void expect(poolAccount.status).to.equal(1); // Check that it's the Pending status
+      void expect(poolMetadata.poolId.toString()).to.equal(newPoolId.toString());
+      void expect(poolMetadata.question).to.equal(question);
+      void expect(poolMetadata.options).to.deep.equal(options);
+      void expect(poolMetadata.originalTruthSocialPostId).to.equal(original_truth_social_post_id);
+      void expect(poolMetadata.imageUrl).to.equal(image_url);
 
       // Check if next_pool_id was incremented in the betting pools state
       const updatedBettingPoolsState =
@@ -191,12 +242,8 @@ describe('solana', () => {
       poolAddress = newPoolAddress;
       poolId = newPoolId;
 
-      // Check initial pool state
-      const initialPool = await program.account.pool.fetch(poolAddress);
-      console.log(
-        'Initial pool point bet totals:',
-        initialPool.pointsBetTotals.map((t: any) => t.toString())
-      );
+      // Pool totals for FREEDOM are created by the first bet
+      const poolTotalsAddress = findPoolTotals(program.programId, poolId, freedomMint);
 
       // Predetermine the bet amounts and options for each user
       const betPlans = [
@@ -230,23 +277,12 @@ describe('solana', () => {
         );
       }
 
-      // Find the program token account (for FREEDOM)
-      const programTokenSeed = Buffer.from('program_token');
-      const [programTokenAuthority] = anchor.web3.PublicKey.findProgramAddressSync(
-        [programTokenSeed],
-        program.programId
-      );
-      const programTokenAccount = await getOrCreateAssociatedTokenAccount(
-        connection,
-        payerKeypair,
-        freedomMint,
-        programTokenAuthority,
-        true
-      );
+      // Find the FREEDOM mint config and vault
+      const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
 
       // Place bets according to the plan
       console.log(`Placing ${betPlans.length} predetermined bets...`);
-      let nextBetId = bettingPoolsState.nextBetId;
+      let nextBetId = (await program.account.poolState.fetch(poolAddress)).nextBetId;
       const optionTotals = [0, 0]; // Track total amounts for each option
 
       for (let i = 0; i < betPlans.length; i++) {
@@ -255,7 +291,6 @@ describe('solana', () => {
         const { user: bettor, tokenAccount: bettorTokenAccount } = usersArray[userIndex];
         const lamports = tokensToLamports(tokenAmount);
         const amount = new anchor.BN(lamports);
-        const tokenType = TokenType.Points;
 
         // Find the bet account PDA
         const [betAddress] = anchor.web3.PublicKey.findProgramAddressSync(
@@ -265,14 +300,17 @@ describe('solana', () => {
 
         // Execute the placeBet instruction
         const betTx = await program.methods
-          .placeBet(new anchor.BN(optionIndex), amount, tokenType)
+          .placeBet(new anchor.BN(optionIndex), amount)
           .accounts({
             bettingPools: bettingPoolsAddress,
             pool: poolAddress,
             bet: betAddress,
             bettor: bettor.publicKey,
+            mint: freedomMint,
+            mintConfig,
+            poolTotals: poolTotalsAddress,
             bettorTokenAccount: bettorTokenAccount,
-            programTokenAccount: programTokenAccount.address,
+            programTokenAccount: vault,
             tokenProgram: TOKEN_PROGRAM_ID,
             associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
             systemProgram: anchor.web3.SystemProgram.programId,
@@ -288,7 +326,7 @@ describe('solana', () => {
         void expect(betAccount.option.toNumber()).to.equal(optionIndex);
         void expect(betAccount.amount.toString()).to.equal(amount.toString());
         void expect(betAccount.poolId.toString()).to.equal(poolId.toString());
-        void expect('points' in betAccount.tokenType).to.be.true;
+        void expect(betAccount.mint.equals(freedomMint)).to.be.true;
 
         // Update running totals
         optionTotals[optionIndex] += lamports;
@@ -296,23 +334,20 @@ describe('solana', () => {
       }
 
       // Verify final pool totals
-      const finalPool = await program.account.pool.fetch(poolAddress);
+      const finalPool = await program.account.poolState.fetch(poolAddress);
+      const finalTotals = await program.account.poolTotals.fetch(poolTotalsAddress);
       console.log('\nFinal bet summary:');
       console.log(`Total bets placed: ${betPlans.length}`);
       console.log(
-        `Final pool point bet totals: [${finalPool.pointsBetTotals.map((t: any) => t.toString())}]`
+        `Final pool FREEDOM bet totals: [${finalTotals.betTotals.map((t: any) => t.toString())}]`
       );
       console.log(`Expected totals: [${optionTotals[0]}, ${optionTotals[1]}]`);
 
-      void expect(finalPool.pointsBetTotals[0].toString()).to.equal(optionTotals[0].toString());
-      void expect(finalPool.pointsBetTotals[1].toString()).to.equal(optionTotals[1].toString());
+      void expect(finalTotals.betTotals[0].toString()).to.equal(optionTotals[0].toString());
+      void expect(finalTotals.betTotals[1].toString()).to.equal(optionTotals[1].toString());
 
-      // Verify betting pools nextBetId was incremented correctly
-      const updatedBettingPools =
-        await program.account.bettingPoolsState.fetch(bettingPoolsAddress);
-      void expect(updatedBettingPools.nextBetId.toNumber()).to.equal(
-        bettingPoolsState.nextBetId.toNumber() + betPlans.length
-      );
+      // Verify the pool's nextBetId was incremented correctly
+      void expect(finalPool.nextBetId.toNumber()).to.equal(nextBetId.toNumber());
 
       console.log('Successfully verified multiple bets from different users with FREEDOM tokens');
     } catch (e) {
@@ -340,10 +375,947 @@ describe('solana', () => {
     console.log('Close pool transaction:', closeTx);
 
     // Fetch the pool account and verify it is graded
-    const closedPool = await program.account.pool.fetch(poolAddress);
+    const closedPool = await program.account.poolState.fetch(poolAddress);
     // Verify the pool status is GRADED
-    void expect(closedPool.status).to.have.property('graded');
+    void expect(closedPool.status).to.equal(2);
     void expect(closedPool.winningOption.toNumber()).to.equal(selectedOption);
     void expect(closedPool.decisionTime.toNumber()).to.be.gt(0);
   });
+
+  it('commitBet hides the option of bets on a hidden pool until it is revealed', async () => {
+    const betsCloseAt = new anchor.BN(Math.floor(Date.now() / 1000) + 86400);
+    const { poolAddress: hiddenPoolAddress, poolId: hiddenPoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will SOL flip ETH by the end of 2025?',
+        options: ['Yes', 'No'],
+        betsCloseAt,
+        hiddenBets: { revealEndsAt: betsCloseAt.addn(3600), forfeitUnrevealed: true },
+      }
+    );
+
+    const { user: bettor, tokenAccount: bettorTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const poolTotalsAddress = findPoolTotals(program.programId, hiddenPoolId, freedomMint);
+    const [betAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [BET_SEED, hiddenPoolId.toBuffer('le', 8), new anchor.BN(1).toBuffer('le', 8)],
+      program.programId
+    );
+    const betAccounts = {
+      bettingPools: bettingPoolsAddress,
+      pool: hiddenPoolAddress,
+      bet: betAddress,
+      bettor: bettor.publicKey,
+      mint: freedomMint,
+      mintConfig,
+      poolTotals: poolTotalsAddress,
+      bettorTokenAccount,
+      programTokenAccount: vault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+      rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+    };
+
+    // Open bets are rejected on a hidden pool
+    try {
+      await program.methods
+        .placeBet(new anchor.BN(0), new anchor.BN(tokensToLamports(10)))
+        .accounts(betAccounts as any)
+        .signers([bettor])
+        .rpc();
+      expect.fail('Expected placeBet to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('HiddenPool');
+    }
+
+    const salt = Buffer.alloc(32, 7);
+    const commitment = betCommitment(1, salt, bettor.publicKey);
+    await program.methods
+      .commitBet(commitment, new anchor.BN(tokensToLamports(10)))
+      .accounts(betAccounts as any)
+      .signers([bettor])
+      .rpc();
+
+    const bet = await program.account.bet.fetch(betAddress);
+    void expect(bet.isRevealed).to.be.false;
+    void expect(bet.commitment).to.deep.equal(commitment);
+    const totals = await program.account.poolTotals.fetch(poolTotalsAddress);
+    void expect(totals.betTotals.map((t: anchor.BN) => t.toNumber())).to.deep.equal([0, 0]);
+
+    // Bets can't be revealed while betting is still open
+    try {
+      await program.methods
+        .revealBet(new anchor.BN(1), [...salt])
+        .accounts({
+          pool: hiddenPoolAddress,
+          bet: betAddress,
+          bettor: bettor.publicKey,
+          poolTotals: poolTotalsAddress,
+        } as any)
+        .signers([bettor])
+        .rpc();
+      expect.fail('Expected revealBet to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('RevealNotOpen');
+    }
+  });
+
+  it('placeBet weights early bets on a time-weighted pool', async () => {
+    const { poolAddress: weightedPoolAddress, poolId: weightedPoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will BTC dominance drop below 40% in 2025?',
+        options: ['Yes', 'No'],
+        timeWeighting: { curve: { linear: {} }, maxBonusBp: 5000 },
+      }
+    );
+
+    const poolState = await program.account.poolState.fetch(weightedPoolAddress);
+    void expect(poolState.weightCurve).to.equal(1);
+    void expect(poolState.earlyBonusBp).to.equal(5000);
+
+    const { user: bettor, tokenAccount: bettorTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const poolTotalsAddress = findPoolTotals(program.programId, weightedPoolId, freedomMint);
+    const [betAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [BET_SEED, weightedPoolId.toBuffer('le', 8), new anchor.BN(1).toBuffer('le', 8)],
+      program.programId
+    );
+    const amount = tokensToLamports(100);
+
+    await program.methods
+      .placeBet(new anchor.BN(0), new anchor.BN(amount))
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        pool: weightedPoolAddress,
+        bet: betAddress,
+        bettor: bettor.publicKey,
+        mint: freedomMint,
+        mintConfig,
+        poolTotals: poolTotalsAddress,
+        bettorTokenAccount,
+        programTokenAccount: vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      } as any)
+      .signers([bettor])
+      .rpc();
+
+    // A bet placed right after the pool opens carries close to the full bonus
+    const totals = await program.account.poolTotals.fetch(poolTotalsAddress);
+    void expect(totals.betTotals[0].toNumber()).to.equal(amount);
+    void expect(totals.weightedTotals[0].toNumber()).to.be.gt(amount);
+    void expect(totals.weightedTotals[0].toNumber()).to.be.at.most(amount * 1.5);
+  });
+
+  it('buyShares prices LMSR shares and enforces the maximum cost', async () => {
+    const { poolAddress: marketPoolAddress, poolId: marketPoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will the Fed cut rates in June?',
+        options: ['Yes', 'No'],
+      }
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const [marketAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [MARKET_SEED, marketPoolId.toBuffer('le', 8)],
+      program.programId
+    );
+
+    // Subsidise the market with the authority's FREEDOM
+    const liquidity = tokensToLamports(10);
+    await program.methods
+      .createMarket(new anchor.BN(liquidity))
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        pool: marketPoolAddress,
+        market: marketAddress,
+        mint: freedomMint,
+        mintConfig,
+        authorityTokenAccount: await getAssociatedTokenAddress(freedomMint, wallet.publicKey),
+        programTokenAccount: vault,
+        authority: wallet.publicKey,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .rpc();
+
+    const market = await program.account.lmsrMarket.fetch(marketAddress);
+    void expect(market.subsidy.toNumber()).to.be.closeTo(liquidity * Math.LN2, 2);
+
+    const { user: buyer, tokenAccount: buyerTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const [positionAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [POSITION_SEED, marketPoolId.toBuffer('le', 8), buyer.publicKey.toBuffer()],
+      program.programId
+    );
+    const shares = new anchor.BN(tokensToLamports(10));
+    const buyAccounts = {
+      pool: marketPoolAddress,
+      market: marketAddress,
+      position: positionAddress,
+      buyer: buyer.publicKey,
+      mint: freedomMint,
+      mintConfig,
+      buyerTokenAccount,
+      programTokenAccount: vault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    // Buying b shares from a fresh market costs b ln((e + 1) / 2)
+    const expectedCost = liquidity * Math.log((Math.E + 1) / 2);
+    try {
+      await program.methods
+        .buyShares(new anchor.BN(0), shares, new anchor.BN(Math.floor(expectedCost) - 10))
+        .accounts(buyAccounts as any)
+        .signers([buyer])
+        .rpc();
+      expect.fail('Expected buyShares to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('SlippageExceeded');
+    }
+
+    await program.methods
+      .buyShares(new anchor.BN(0), shares, new anchor.BN(Math.ceil(expectedCost) + 10))
+      .accounts(buyAccounts as any)
+      .signers([buyer])
+      .rpc();
+
+    const position = await program.account.lmsrPosition.fetch(positionAddress);
+    void expect(position.shares[0].toString()).to.equal(shares.toString());
+    const updatedMarket = await program.account.lmsrMarket.fetch(marketAddress);
+    void expect(updatedMarket.balance.toNumber() - market.balance.toNumber()).to.be.closeTo(
+      expectedCost,
+      2
+    );
+  });
+
+  it('acceptOffer matches part of an offer at its fixed odds', async () => {
+    const { poolAddress: offerPoolAddress, poolId: offerPoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will the Senate pass the budget this week?',
+        options: ['Yes', 'No'],
+      }
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const { user: maker, tokenAccount: makerTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const { user: taker, tokenAccount: takerTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const offerId = new anchor.BN(1);
+    const [offerAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [OFFER_SEED, offerPoolId.toBuffer('le', 8), offerId.toBuffer('le', 8)],
+      program.programId
+    );
+
+    // Maker stakes 10 on Yes at 3:1, asking takers for 30 in total
+    await program.methods
+      .createOffer(
+        new anchor.BN(0),
+        new anchor.BN(tokensToLamports(10)),
+        new anchor.BN(tokensToLamports(30))
+      )
+      .accounts({
+        pool: offerPoolAddress,
+        offer: offerAddress,
+        maker: maker.publicKey,
+        mint: freedomMint,
+        mintConfig,
+        makerTokenAccount,
+        programTokenAccount: vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([maker])
+      .rpc();
+
+    const matchId = new anchor.BN(1);
+    const [matchAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [
+        MATCH_SEED,
+        offerPoolId.toBuffer('le', 8),
+        offerId.toBuffer('le', 8),
+        matchId.toBuffer('le', 8),
+      ],
+      program.programId
+    );
+    await program.methods
+      .acceptOffer(new anchor.BN(tokensToLamports(12)))
+      .accounts({
+        pool: offerPoolAddress,
+        offer: offerAddress,
+        offerMatch: matchAddress,
+        taker: taker.publicKey,
+        mint: freedomMint,
+        mintConfig,
+        takerTokenAccount,
+        programTokenAccount: vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([taker])
+      .rpc();
+
+    const offerMatch = await program.account.offerMatch.fetch(matchAddress);
+    void expect(offerMatch.makerStake.toNumber()).to.equal(tokensToLamports(4));
+    void expect(offerMatch.takerStake.toNumber()).to.equal(tokensToLamports(12));
+    const offer = await program.account.offer.fetch(offerAddress);
+    void expect(offer.stakeRemaining.toNumber()).to.equal(tokensToLamports(6));
+    void expect(offer.askRemaining.toNumber()).to.equal(tokensToLamports(18));
+
+    // Cancelling refunds the unmatched part of the maker's stake
+    const balanceBefore = (await connection.getTokenAccountBalance(makerTokenAccount)).value.amount;
+    await program.methods
+      .cancelOffer()
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        pool: offerPoolAddress,
+        offer: offerAddress,
+        maker: maker.publicKey,
+        mint: freedomMint,
+        mintConfig,
+        makerTokenAccount,
+        programTokenAccount: vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      } as any)
+      .signers([maker])
+      .rpc();
+
+    const balanceAfter = (await connection.getTokenAccountBalance(makerTokenAccount)).value.amount;
+    void expect(BigInt(balanceAfter) - BigInt(balanceBefore)).to.equal(BigInt(tokensToLamports(6)));
+  });
+
+  it('updatePool fixes the text and extendBetsCloseAt pushes back the close time', async () => {
+    const {
+      poolAddress: draftPoolAddress,
+      poolMetadataAddress: draftPoolMetadataAddress,
+    } = await createBettingPool(program, bettingPoolsAddress, wallet.publicKey, {
+      question: 'Will the Snate pass the bill?',
+      options: ['Yes', 'Nope'],
+    });
+
+    await program.methods
+      .updatePool('Will the Senate pass the bill?', ['Yes', 'No'])
+      .accounts({
+        pool: draftPoolAddress,
+        poolMetadata: draftPoolMetadataAddress,
+        bettingPools: bettingPoolsAddress,
+        authority: wallet.publicKey,
+      } as any)
+      .rpc();
+
+    const poolMetadata = await program.account.poolMetadata.fetch(draftPoolMetadataAddress);
+    void expect(poolMetadata.question).to.equal('Will the Senate pass the bill?');
+    void expect(poolMetadata.options).to.deep.equal(['Yes', 'No']);
+
+    const pool = await program.account.poolState.fetch(draftPoolAddress);
+    const extendAccounts = {
+      pool: draftPoolAddress,
+      bettingPools: bettingPoolsAddress,
+      authority: wallet.publicKey,
+    };
+
+    // The close time can't be brought forward
+    try {
+      await program.methods
+        .extendBetsCloseAt(pool.betsCloseAt.subn(60))
+        .accounts(extendAccounts as any)
+        .rpc();
+      expect.fail('Expected extendBetsCloseAt to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('CloseTimeNotExtended');
+    }
+
+    const betsCloseAt = pool.betsCloseAt.addn(3600);
+    await program.methods
+      .extendBetsCloseAt(betsCloseAt)
+      .accounts(extendAccounts as any)
+      .rpc();
+
+    const extendedPool = await program.account.poolState.fetch(draftPoolAddress);
+    void expect(extendedPool.betsCloseAt.toString()).to.equal(betsCloseAt.toString());
+  });
+
+  it('proposePool escrows a bond that is slashed when the pool is flagged invalid', async () => {
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const bond = tokensToLamports(5);
+    await program.methods
+      .setPoolBond(new anchor.BN(bond))
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        mint: freedomMint,
+        mintConfig,
+        authority: wallet.publicKey,
+      } as any)
+      .rpc();
+
+    const { user: creator, tokenAccount: creatorTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const { nextPoolId: proposedPoolId } =
+      await program.account.bettingPoolsState.fetch(bettingPoolsAddress);
+    const [proposedPoolAddress, proposedMetadataAddress, bondAddress] = [
+      POOL_SEED,
+      POOL_METADATA_SEED,
+      POOL_BOND_SEED,
+    ].map(
+      (seed) =>
+        anchor.web3.PublicKey.findProgramAddressSync(
+          [seed, proposedPoolId.toBuffer('le', 8)],
+          program.programId
+        )[0]
+    );
+
+    await program.methods
+      .proposePool(
+        'Will the community pick the right market?',
+        ['Yes', 'No'],
+        new anchor.BN(Math.floor(Date.now() / 1000) + 86400),
+        '',
+        'https://example.com/image.jpg',
+        null,
+        null
+      )
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        pool: proposedPoolAddress,
+        poolMetadata: proposedMetadataAddress,
+        poolBond: bondAddress,
+        creator: creator.publicKey,
+        mint: freedomMint,
+        mintConfig,
+        creatorTokenAccount,
+        programTokenAccount: vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([creator])
+      .rpc();
+
+    const poolBond = await program.account.poolBond.fetch(bondAddress);
+    void expect(poolBond.creator.equals(creator.publicKey)).to.be.true;
+    void expect(poolBond.amount.toNumber()).to.equal(bond);
+
+    await program.methods
+      .flagPoolInvalid()
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        pool: proposedPoolAddress,
+        poolBond: bondAddress,
+        authority: wallet.publicKey,
+      } as any)
+      .rpc();
+
+    const flaggedPool = await program.account.poolState.fetch(proposedPoolAddress);
+    void expect(flaggedPool.status).to.equal(2);
+    void expect(flaggedPool.isDraw).to.equal(1);
+    const slashedBond = await program.account.poolBond.fetch(bondAddress);
+    void expect(slashedBond.isSlashed).to.be.true;
+
+    // A slashed bond can't be returned
+    try {
+      await program.methods
+        .returnBond()
+        .accounts({
+          bettingPools: bettingPoolsAddress,
+          pool: proposedPoolAddress,
+          poolBond: bondAddress,
+          mint: freedomMint,
+          mintConfig,
+          creatorTokenAccount,
+          programTokenAccount: vault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        } as any)
+        .rpc();
+      expect.fail('Expected returnBond to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('BondAlreadySettled');
+    }
+  });
+
+  it('placeBetSigned places a relayed bet once per signed intent', async () => {
+    const { poolAddress: relayPoolAddress, poolId: relayPoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will the relayer pay for this bet?',
+        options: ['Yes', 'No'],
+      }
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const { user: bettor, tokenAccount: bettorTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const amount = new anchor.BN(tokensToLamports(10));
+
+    // The bettor lets the betting pools PDA move their stake
+    await anchor.web3.sendAndConfirmTransaction(
+      connection,
+      new anchor.web3.Transaction().add(
+        createApproveInstruction(
+          bettorTokenAccount,
+          bettingPoolsAddress,
+          bettor.publicKey,
+          BigInt(tokensToLamports(50))
+        )
+      ),
+      [bettor]
+    );
+
+    const nonce = new anchor.BN(0);
+    const expiresAt = new anchor.BN(Math.floor(Date.now() / 1000) + 600);
+    const message = betIntentMessage(program.programId, {
+      poolId: relayPoolId,
+      optionIndex: 1,
+      amount,
+      mint: freedomMint,
+      nonce,
+      expiresAt,
+    });
+    const signatureIx = anchor.web3.Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: bettor.secretKey,
+      message,
+    });
+
+    const relayPool = await program.account.poolState.fetch(relayPoolAddress);
+    const [betAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [BET_SEED, relayPoolId.toBuffer('le', 8), relayPool.nextBetId.toBuffer('le', 8)],
+      program.programId
+    );
+    const [userNonceAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [NONCE_SEED, bettor.publicKey.toBuffer()],
+      program.programId
+    );
+    const signedAccounts = {
+      bettingPools: bettingPoolsAddress,
+      pool: relayPoolAddress,
+      bet: betAddress,
+      bettor: bettor.publicKey,
+      userNonce: userNonceAddress,
+      relayer: wallet.publicKey,
+      mint: freedomMint,
+      mintConfig,
+      poolTotals: findPoolTotals(program.programId, relayPoolId, freedomMint),
+      bettorTokenAccount,
+      programTokenAccount: vault,
+      instructions: anchor.web3.SYSVAR_INSTRUCTIONS_PUBKEY,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    await program.methods
+      .placeBetSigned(new anchor.BN(1), amount, nonce, expiresAt)
+      .accounts(signedAccounts as any)
+      .preInstructions([signatureIx])
+      .rpc();
+
+    const bet = await program.account.bet.fetch(betAddress);
+    void expect(bet.owner.equals(bettor.publicKey)).to.be.true;
+    void expect(bet.option.toNumber()).to.equal(1);
+    void expect(bet.amount.toString()).to.equal(amount.toString());
+
+    // Replaying the same intent is rejected
+    const [nextBetAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [BET_SEED, relayPoolId.toBuffer('le', 8), relayPool.nextBetId.addn(1).toBuffer('le', 8)],
+      program.programId
+    );
+    try {
+      await program.methods
+        .placeBetSigned(new anchor.BN(1), amount, nonce, expiresAt)
+        .accounts({ ...signedAccounts, bet: nextBetAddress } as any)
+        .preInstructions([signatureIx])
+        .rpc();
+      expect.fail('Expected placeBetSigned to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('InvalidNonce');
+    }
+  });
+
+  it('placeBet accepts a session key acting for its owner up to the spend cap', async () => {
+    const { poolAddress: sessionPoolAddress, poolId: sessionPoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will the bot place this bet?',
+        options: ['Yes', 'No'],
+      }
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const { user: owner, tokenAccount: ownerTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const { user: sessionKey } = await createFundedUser(connection, payerKeypair, freedomMint, 0);
+    const [sessionAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [SESSION_SEED, owner.publicKey.toBuffer(), sessionKey.publicKey.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .createSession(
+        sessionKey.publicKey,
+        new anchor.BN(tokensToLamports(15)),
+        new anchor.BN(Math.floor(Date.now() / 1000) + 3600),
+        [freedomMint]
+      )
+      .accounts({
+        session: sessionAddress,
+        owner: owner.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([owner])
+      .rpc();
+
+    // The session key spends through the owner's approval to the betting pools PDA
+    await anchor.web3.sendAndConfirmTransaction(
+      connection,
+      new anchor.web3.Transaction().add(
+        createApproveInstruction(
+          ownerTokenAccount,
+          bettingPoolsAddress,
+          owner.publicKey,
+          BigInt(tokensToLamports(50))
+        )
+      ),
+      [owner]
+    );
+
+    const placeSessionBet = async (betId: anchor.BN) => {
+      const [betAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+        [BET_SEED, sessionPoolId.toBuffer('le', 8), betId.toBuffer('le', 8)],
+        program.programId
+      );
+      await program.methods
+        .placeBet(new anchor.BN(0), new anchor.BN(tokensToLamports(10)))
+        .accounts({
+          bettingPools: bettingPoolsAddress,
+          pool: sessionPoolAddress,
+          bet: betAddress,
+          bettor: sessionKey.publicKey,
+          session: sessionAddress,
+          mint: freedomMint,
+          mintConfig,
+          poolTotals: findPoolTotals(program.programId, sessionPoolId, freedomMint),
+          bettorTokenAccount: ownerTokenAccount,
+          programTokenAccount: vault,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+          systemProgram: anchor.web3.SystemProgram.programId,
+          rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+        } as any)
+        .signers([sessionKey])
+        .rpc();
+      return betAddress;
+    };
+
+    const betAddress = await placeSessionBet(new anchor.BN(1));
+    const bet = await program.account.bet.fetch(betAddress);
+    void expect(bet.owner.equals(owner.publicKey)).to.be.true;
+    const session = await program.account.session.fetch(sessionAddress);
+    void expect(session.spent.toNumber()).to.equal(tokensToLamports(10));
+
+    // A second bet would take the session past its cap
+    try {
+      await placeSessionBet(new anchor.BN(2));
+      expect.fail('Expected placeBet to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('SessionSpendCapExceeded');
+    }
+
+    await program.methods
+      .revokeSession()
+      .accounts({ session: sessionAddress, owner: owner.publicKey } as any)
+      .signers([owner])
+      .rpc();
+    void expect(await connection.getAccountInfo(sessionAddress)).to.be.null;
+  });
+
+  it('placeBetFromBalance stakes from an internal balance without a token transfer', async () => {
+    const { poolAddress: balancePoolAddress, poolId: balancePoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will internal balances make bets cheaper?',
+        options: ['Yes', 'No'],
+      }
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const { user: owner, tokenAccount: ownerTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const [balanceAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [BALANCE_SEED, owner.publicKey.toBuffer(), freedomMint.toBuffer()],
+      program.programId
+    );
+    const tokenAccounts = {
+      bettingPools: bettingPoolsAddress,
+      userBalance: balanceAddress,
+      owner: owner.publicKey,
+      mint: freedomMint,
+      mintConfig,
+      ownerTokenAccount,
+      programTokenAccount: vault,
+      tokenProgram: TOKEN_PROGRAM_ID,
+      systemProgram: anchor.web3.SystemProgram.programId,
+    };
+
+    await program.methods
+      .deposit(new anchor.BN(tokensToLamports(20)))
+      .accounts(tokenAccounts as any)
+      .signers([owner])
+      .rpc();
+
+    const [betAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [BET_SEED, balancePoolId.toBuffer('le', 8), new anchor.BN(1).toBuffer('le', 8)],
+      program.programId
+    );
+    await program.methods
+      .placeBetFromBalance(new anchor.BN(0), new anchor.BN(tokensToLamports(5)))
+      .accounts({
+        pool: balancePoolAddress,
+        bet: betAddress,
+        bettor: owner.publicKey,
+        userBalance: balanceAddress,
+        mintConfig,
+        poolTotals: findPoolTotals(program.programId, balancePoolId, freedomMint),
+        systemProgram: anchor.web3.SystemProgram.programId,
+      } as any)
+      .signers([owner])
+      .rpc();
+
+    const bet = await program.account.bet.fetch(betAddress);
+    void expect(bet.amount.toNumber()).to.equal(tokensToLamports(5));
+    const balance = await program.account.userBalance.fetch(balanceAddress);
+    void expect(balance.amount.toNumber()).to.equal(tokensToLamports(15));
+
+    // The balance can't be overdrawn
+    try {
+      await program.methods
+        .withdraw(new anchor.BN(tokensToLamports(16)))
+        .accounts(tokenAccounts as any)
+        .signers([owner])
+        .rpc();
+      expect.fail('Expected withdraw to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('InsufficientBalance');
+    }
+
+    await program.methods
+      .withdraw(new anchor.BN(tokensToLamports(15)))
+      .accounts(tokenAccounts as any)
+      .signers([owner])
+      .rpc();
+    await program.methods
+      .closeBalance()
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        userBalance: balanceAddress,
+        owner: owner.publicKey,
+      } as any)
+      .signers([owner])
+      .rpc();
+    void expect(await connection.getAccountInfo(balanceAddress)).to.be.null;
+  });
+
+  it('placeBet emits the pool totals after the bet', async () => {
+    const { poolAddress: eventPoolAddress, poolId: eventPoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will indexers stop recomputing odds?',
+        options: ['Yes', 'No'],
+      }
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const { user: bettor, tokenAccount: bettorTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const [betAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [BET_SEED, eventPoolId.toBuffer('le', 8), new anchor.BN(1).toBuffer('le', 8)],
+      program.programId
+    );
+
+    const signature = await program.methods
+      .placeBet(new anchor.BN(1), new anchor.BN(tokensToLamports(10)))
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        pool: eventPoolAddress,
+        bet: betAddress,
+        bettor: bettor.publicKey,
+        mint: freedomMint,
+        mintConfig,
+        poolTotals: findPoolTotals(program.programId, eventPoolId, freedomMint),
+        bettorTokenAccount,
+        programTokenAccount: vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      } as any)
+      .signers([bettor])
+      .rpc({ commitment: 'confirmed' });
+
+    const event = (await fetchCpiEvents(program, signature)).find(
+      (e) => e.name === 'betPlaced'
+    ).data;
+
+    void expect(event.version).to.equal(2);
+    void expect(event.betTotals[0].toNumber()).to.equal(0);
+    void expect(event.betTotals[1].toNumber()).to.equal(tokensToLamports(10));
+    void expect(event.slot.toNumber()).to.be.gt(0);
+  });
+
+  it('quoteBet prices a bet against the pool totals', async () => {
+    const { poolAddress: quotePoolAddress, poolId: quotePoolId } = await createBettingPool(
+      program,
+      bettingPoolsAddress,
+      wallet.publicKey,
+      {
+        question: 'Will clients stop reimplementing the payout formula?',
+        options: ['Yes', 'No'],
+      }
+    );
+    const { mintConfig, vault } = findMintAccounts(program.programId, freedomMint);
+    const poolTotals = findPoolTotals(program.programId, quotePoolId, freedomMint);
+    const stake = new anchor.BN(tokensToLamports(10));
+
+    // With nothing on the other side the bet would be refunded
+    const emptyQuote = await program.methods
+      .quoteBet(new anchor.BN(1), stake)
+      .accounts({ pool: quotePoolAddress, mintConfig, poolTotals: null } as any)
+      .view();
+    void expect(emptyQuote.payout.amount.eq(stake)).to.be.true;
+    void expect(emptyQuote.payout.outcome).to.deep.equal({ refunded: {} });
+    void expect(emptyQuote.oddsBp.toNumber()).to.equal(10000);
+
+    const { user: bettor, tokenAccount: bettorTokenAccount } = await createFundedUser(
+      connection,
+      payerKeypair,
+      freedomMint,
+      100
+    );
+    const [betAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+      [BET_SEED, quotePoolId.toBuffer('le', 8), new anchor.BN(1).toBuffer('le', 8)],
+      program.programId
+    );
+    await program.methods
+      .placeBet(new anchor.BN(0), stake)
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        pool: quotePoolAddress,
+        bet: betAddress,
+        bettor: bettor.publicKey,
+        mint: freedomMint,
+        mintConfig,
+        poolTotals,
+        bettorTokenAccount,
+        programTokenAccount: vault,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        rent: anchor.web3.SYSVAR_RENT_PUBKEY,
+      } as any)
+      .signers([bettor])
+      .rpc();
+
+    // A winning bet on the other side takes the whole losing stake, less the fee
+    const { payoutFeeBp } = await program.account.mintConfig.fetch(mintConfig);
+    const winnings = stake.muln(2);
+    const fee = winnings.muln(payoutFeeBp).divn(10000);
+    const quote = await program.methods
+      .quoteBet(new anchor.BN(1), stake)
+      .accounts({ pool: quotePoolAddress, mintConfig, poolTotals } as any)
+      .view();
+    void expect(quote.payout.amount.eq(winnings.sub(fee))).to.be.true;
+    void expect(quote.payout.fee.eq(fee)).to.be.true;
+    void expect(quote.payout.outcome).to.deep.equal({ won: {} });
+
+    const claimQuote = await program.methods
+      .quoteClaim()
+      .accounts({ pool: quotePoolAddress, bet: betAddress, mintConfig, poolTotals } as any)
+      .view();
+    void expect(claimQuote.payout.outcome).to.deep.equal({ refunded: {} });
+  });
+
+  it('setTreasury updates the fee recipient', async () => {
+    await program.methods
+      .setTreasury(wallet.publicKey)
+      .accounts({
+        bettingPools: bettingPoolsAddress,
+        authority: wallet.publicKey,
+      } as any)
+      .rpc();
+
+    const bettingPoolsState = await program.account.bettingPoolsState.fetch(bettingPoolsAddress);
+    void expect(bettingPoolsState.treasury.equals(wallet.publicKey)).to.be.true;
+  });
+
+  it('closeBettingPool refuses while pools have unclaimed bets', async () => {
+    try {
+      await program.methods
+        .closeBettingPool()
+        .accounts({
+          bettingPools: bettingPoolsAddress,
+          authority: wallet.publicKey,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        } as any)
+        .rpc();
+      expect.fail('Expected closeBettingPool to fail');
+    } catch (e: any) {
+      void expect(e.error?.errorCode?.code).to.equal('PoolsNotSettled');
+    }
+  });
 });
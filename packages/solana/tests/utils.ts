@@ -1,5 +1,6 @@
 import * as anchor from '@coral-xyz/anchor';
 import { Program } from '@coral-xyz/anchor';
+import { createHash } from 'crypto';
 import {
   createAssociatedTokenAccountInstruction,
   createMintToInstruction,
@@ -12,14 +13,112 @@ import { TrumpFun } from '../target/types/trump_fun';
 // Constants
 export const BETTING_POOLS_SEED = Buffer.from('betting_pools_v1');
 export const POOL_SEED = Buffer.from('pool_v1');
+export const POOL_METADATA_SEED = Buffer.from('pool_metadata_v1');
 export const BET_SEED = Buffer.from('bet_v1');
+export const MINT_CONFIG_SEED = Buffer.from('mint_config_v1');
+export const VAULT_SEED = Buffer.from('vault_v1');
+export const POOL_TOTALS_SEED = Buffer.from('pool_totals_v1');
+export const MARKET_SEED = Buffer.from('market_v1');
+export const POSITION_SEED = Buffer.from('position_v1');
+export const OFFER_SEED = Buffer.from('offer_v1');
+export const MATCH_SEED = Buffer.from('match_v1');
+export const POOL_BOND_SEED = Buffer.from('pool_bond_v1');
+export const NONCE_SEED = Buffer.from('nonce_v1');
+export const SESSION_SEED = Buffer.from('session_v1');
+export const BALANCE_SEED = Buffer.from('balance_v1');
+export const BET_INTENT_DOMAIN = Buffer.from('trump-fun:bet_intent:v1');
 export const TOKEN_DECIMALS = 6;
 
-// TokenType helpers for Anchor's enum representation
-export const TokenType = {
-  Usdc: { usdc: {} },
-  Points: { points: {} },
-} as const;
+// Find the config and vault PDAs of an accepted mint
+export function findMintAccounts(
+  programId: anchor.web3.PublicKey,
+  mint: anchor.web3.PublicKey
+): { mintConfig: anchor.web3.PublicKey; vault: anchor.web3.PublicKey } {
+  const [mintConfig] = anchor.web3.PublicKey.findProgramAddressSync(
+    [MINT_CONFIG_SEED, mint.toBuffer()],
+    programId
+  );
+  const [vault] = anchor.web3.PublicKey.findProgramAddressSync(
+    [VAULT_SEED, mint.toBuffer()],
+    programId
+  );
+  return { mintConfig, vault };
+}
+
+// Find the PDA holding a pool's totals for one mint
+export function findPoolTotals(
+  programId: anchor.web3.PublicKey,
+  poolId: anchor.BN,
+  mint: anchor.web3.PublicKey
+): anchor.web3.PublicKey {
+  const [poolTotals] = anchor.web3.PublicKey.findProgramAddressSync(
+    [POOL_TOTALS_SEED, poolId.toBuffer('le', 8), mint.toBuffer()],
+    programId
+  );
+  return poolTotals;
+}
+
+// Hash a hidden bet commits to: sha256(option_index as u64 LE || salt || owner)
+export function betCommitment(
+  optionIndex: number,
+  salt: Buffer,
+  owner: anchor.web3.PublicKey
+): number[] {
+  return [
+    ...createHash('sha256')
+      .update(new anchor.BN(optionIndex).toBuffer('le', 8))
+      .update(salt)
+      .update(owner.toBuffer())
+      .digest(),
+  ];
+}
+
+// Message a bettor signs for placeBetSigned, matching `bet_intent_message` in the program
+export function betIntentMessage(
+  programId: anchor.web3.PublicKey,
+  params: {
+    poolId: anchor.BN;
+    optionIndex: number;
+    amount: anchor.BN;
+    mint: anchor.web3.PublicKey;
+    nonce: anchor.BN;
+    expiresAt: anchor.BN;
+  }
+): Buffer {
+  return Buffer.concat([
+    BET_INTENT_DOMAIN,
+    programId.toBuffer(),
+    params.poolId.toBuffer('le', 8),
+    new anchor.BN(params.optionIndex).toBuffer('le', 8),
+    params.amount.toBuffer('le', 8),
+    params.mint.toBuffer(),
+    params.nonce.toBuffer('le', 8),
+    params.expiresAt.toTwos(64).toBuffer('le', 8),
+  ]);
+}
+
+// Events a transaction emitted through the program's self-CPI, read from its inner instructions
+export async function fetchCpiEvents(
+  program: Program<TrumpFun>,
+  signature: string
+): Promise<anchor.Event[]> {
+  const tx = await program.provider.connection.getTransaction(signature, {
+    commitment: 'confirmed',
+    maxSupportedTransactionVersion: 0,
+  });
+  const accountKeys = tx.transaction.message.getAccountKeys({
+    accountKeysFromLookups: tx.meta.loadedAddresses,
+  });
+  const eventIxTag = new anchor.BN('1d9acb512ea545e4', 16).toArrayLike(Buffer, 'le', 8);
+
+  return tx.meta.innerInstructions
+    .flatMap((inner) => inner.instructions)
+    .filter((ix) => accountKeys.get(ix.programIdIndex).equals(program.programId))
+    .map((ix) => anchor.utils.bytes.bs58.decode(ix.data))
+    .filter((data) => eventIxTag.equals(data.subarray(0, 8)))
+    .map((data) => program.coder.events.decode(anchor.utils.bytes.base64.encode(data.subarray(8))))
+    .filter((event) => event !== null);
+}
 
 // Convert a token amount to lamports (internal representation)
 export function tokensToLamports(tokens: number): number {
@@ -42,9 +141,12 @@ export async function createBettingPool(
     betsCloseAt?: anchor.BN;
     original_truth_social_post_id?: string;
     image_url?: string;
+    hiddenBets?: { revealEndsAt: anchor.BN; forfeitUnrevealed: boolean };
+    timeWeighting?: { curve: object; maxBonusBp: number };
   }
 ): Promise<{
   poolAddress: anchor.web3.PublicKey;
+  poolMetadataAddress: anchor.web3.PublicKey;
   poolId: anchor.BN;
   tx: string;
 }> {
@@ -57,6 +159,10 @@ export async function createBettingPool(
     [POOL_SEED, poolId.toBuffer('le', 8)],
     program.programId
   );
+  const [poolMetadataAddress] = anchor.web3.PublicKey.findProgramAddressSync(
+    [POOL_METADATA_SEED, poolId.toBuffer('le', 8)],
+    program.programId
+  );
 
   // Default values
   const betsCloseAt = params.betsCloseAt || new anchor.BN(Math.floor(Date.now() / 1000) + 86400); // 24 hours from now
@@ -70,17 +176,20 @@ export async function createBettingPool(
       params.options,
       betsCloseAt,
       original_truth_social_post_id,
-      image_url
+      image_url,
+      params.hiddenBets ?? null,
+      params.timeWeighting ?? null
     )
     .accounts({
       bettingPools: bettingPoolsAddress,
       pool: poolAddress,
+      poolMetadata: poolMetadataAddress,
       authority: authority,
       systemProgram: anchor.web3.SystemProgram.programId,
     })
     .rpc();
 
-  return { poolAddress, poolId, tx };
+  return { poolAddress, poolMetadataAddress, poolId, tx };
 }
 
 // Helper function to create a user with funded tokens